@@ -1,15 +1,24 @@
 use bevy::math::DVec3;
 use lazy_static::lazy_static;
 
+// Default settings. Those used by the simulation's systems can be overridden at runtime through
+// the PhysicsConfig resource.
+
 // Play area
-// TODO make this user defined and not hard-coded.
 pub static PLAY_AREA_CENTRE_X: f64 = 0.0;
 pub static PLAY_AREA_CENTRE_Y: f64 = 100.0;
+pub static PLAY_AREA_CENTRE_Z: f64 = 0.0;
 pub static PLAY_AREA_EXTENT_X: f64 = 100.0;
 pub static PLAY_AREA_EXTENT_Y: f64 = 100.0;
+pub static PLAY_AREA_EXTENT_Z: f64 = 100.0;
+pub static PLAY_AREA_GROWTH_FACTOR: f64 = 2.0;
 
 // Physics
 
+// --Timestep
+pub static DEFAULT_TIMESTEP: f64 = 1.0 / 60.0;
+pub static DEFAULT_MAX_SUBSTEPS: u32 = 5;
+
 // --Body dimensions
 pub static DEFAULT_RADIUS: f64 = 1.0;
 // --Damping
@@ -20,6 +29,35 @@ pub static DEFAULT_K1: f64 = 0.1;
 pub static DEFAULT_K2: f64 = 0.1;
 // --Restitution
 pub static RESTITUTION_COEFF: f64 = 1.0;
+// --Friction
+pub static STATIC_FRICTION_COEFF: f64 = 0.5;
+pub static DYNAMIC_FRICTION_COEFF: f64 = 0.3;
+// --Contact solver
+pub static VELOCITY_ITERATIONS: u32 = 4;
+pub static POSITION_ITERATIONS: u32 = 4;
+pub static VELOCITY_EPSILON: f64 = 0.01;
+pub static POSITION_EPSILON: f64 = 0.001;
+pub static WARM_START_FACTOR: f64 = 0.8;
+// --Joints
+pub static JOINT_ITERATIONS: u32 = 8;
+// --Sleeping
+pub static SLEEP_LINEAR_THRESHOLD: f64 = 0.1;
+pub static SLEEP_ANGULAR_THRESHOLD: f64 = 0.1;
+pub static TIME_TO_SLEEP: f64 = 0.5;
+// --Continuous collision detection
+pub static CCD_ALLOWED_PENETRATION: f64 = 0.01;
+pub static CCD_MAX_ITERATIONS: u32 = 32;
+pub static CCD_TOLERANCE: f64 = 0.0001;
+// --GJK/EPA
+pub static GJK_MAX_ITERATIONS: u32 = 64;
+pub static GJK_TOLERANCE: f64 = 0.000001;
+pub static EPA_MAX_ITERATIONS: u32 = 64;
+pub static EPA_TOLERANCE: f64 = 0.0001;
+// --Shape casting
+pub static SHAPE_CAST_MAX_ITERATIONS: u32 = 64;
+pub static SHAPE_CAST_TOLERANCE: f64 = 0.0001;
+// --Contact manifolds
+pub static CONTACT_MATCH_TOLERANCE: f64 = 0.1;
 // --Float precision
 pub static LOW_VELOCITY_THRESHOLD: f64 = 0.1;
 pub static LOW_ROTATION_THRESHOLD: f64 = 0.0001;
@@ -27,6 +65,8 @@ pub static LOW_ROTATION_THRESHOLD: f64 = 0.0001;
 lazy_static! {
     pub static ref DEFAULT_GRAVITY: DVec3 = DVec3::new(0.0, -20.0, 0.0); // value of 'g'.
 }
+pub static GRAVITATIONAL_CONSTANT: f64 = 1.0;
+pub static ATTRACTOR_MIN_DISTANCE: f64 = 0.1;
 // --Mass
 pub static DEFAULT_MASS: f64 = 10.0;
 pub static DEFAULT_INVERSE_MASS: f64 = 0.1;
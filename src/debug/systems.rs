@@ -22,6 +22,7 @@ use crate::{
         Torque,
         Velocity,
     },
+    physics::resources::PhysicsConfig,
     user_interaction::components::{
         Player,
     },
@@ -129,6 +130,7 @@ pub fn initialize(mut commands: Commands, asset_server: Res<AssetServer>) {
 
 /// Populates the player section of text with relevant up-to-date values.
 fn update_player_debug_text(
+    config: Res<PhysicsConfig>,
     mut query: Query<&mut Text, With<PlayerDebugText>>,
     player: Query<(&AngularVelocity, &Drag, &Force, &Gravity, &Mass, &PhysTransform, &Thrust,
                    &Torque, &Velocity), With<Player>>,
@@ -149,7 +151,7 @@ fn update_player_debug_text(
             player_position = transform.translation();
             player_thrust = thrust.vector();
             player_drag = drag.vector();
-            player_gravity = gravity.vector(mass.value());
+            player_gravity = gravity.vector(mass.value(), config.gravity);
             player_force = force.vector();
             player_torque = torque.vector();
             player_velocity = velocity.vector();
@@ -0,0 +1,10 @@
+/// A component that opts a body in to continuous collision detection (CCD), preventing it from
+/// tunnelling through thin colliders or boundaries when it moves a long way in a single step.
+///
+/// Each step, a sphere at the core of the body's shape is swept from its previous position to its
+/// new one. If the sphere would hit another collider or boundary on the way, the body's motion is
+/// clamped to the time of impact so that a contact is generated there instead.
+///
+/// TriMeshes and HeightFields have no interior, so only their centre is swept.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Ccd;
@@ -0,0 +1,62 @@
+/// A component that restricts which bodies an entity can collide with, using bitmasks.
+///
+/// Each bit represents a group. The memberships are the groups the entity belongs to and the
+/// filters are the groups it can collide with. Two entities are only tested for contact if each
+/// belongs to at least one of the groups in the other's filters. Entities without CollisionGroups
+/// belong to, and collide with, all groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionGroups {
+    pub memberships: u32,
+    pub filters: u32,
+}
+
+impl CollisionGroups {
+    /// A CollisionGroups that belongs to, and collides with, all groups.
+    pub const ALL: Self = Self { memberships: u32::MAX, filters: u32::MAX };
+
+    /// A CollisionGroups that belongs to, and collides with, no groups.
+    pub const NONE: Self = Self { memberships: 0, filters: 0 };
+
+    /// Creates a new CollisionGroups with the given membership and filter bitmasks.
+    pub fn new(memberships: u32, filters: u32) -> Self {
+        Self { memberships, filters }
+    }
+
+    /// Returns true if an entity with these groups can collide with an entity with the other given
+    /// groups.
+    pub fn interacts_with(&self, other: &Self) -> bool {
+        (self.memberships & other.filters) != 0 && (other.memberships & self.filters) != 0
+    }
+}
+
+impl Default for CollisionGroups {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_interacts_with() {
+        let player = CollisionGroups::new(0b001, 0b110);
+        let projectile = CollisionGroups::new(0b010, 0b100);
+        let wall = CollisionGroups::new(0b100, 0b011);
+
+        // the player's projectiles don't hit the player, but both hit walls.
+        assert!(!player.interacts_with(&projectile));
+        assert!(!projectile.interacts_with(&player));
+        assert!(player.interacts_with(&wall));
+        assert!(projectile.interacts_with(&wall));
+
+        // both entities must accept each other.
+        let one_way = CollisionGroups::new(0b001, 0b000);
+        assert!(!one_way.interacts_with(&CollisionGroups::ALL));
+        assert!(!CollisionGroups::ALL.interacts_with(&one_way));
+
+        assert!(CollisionGroups::default().interacts_with(&CollisionGroups::ALL));
+        assert!(!CollisionGroups::NONE.interacts_with(&CollisionGroups::ALL));
+    }
+}
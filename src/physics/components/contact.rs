@@ -6,10 +6,16 @@ use bevy::{
 #[derive(Debug)]
 /// Describes a contact between two separate bodies associated with two entities. Contains the two
 /// Entitys involved in the contact (unless one of the Entitys is an immovable plane, in which case
-/// just the body that impacts the plane is referenced), the point of contact, amount of
-/// inter-penetration, the contact normal vector and the contact point(s) relative to each body.
+/// just the body that impacts the plane is referenced and the plane is held separately as the
+/// boundary), the point of contact, amount of inter-penetration, the contact normal vector and the
+/// contact point(s) relative to each body.
+///
+/// The feature id identifies the features of the shapes (e.g. vertex, edge or face) that generated
+/// the contact, so that it can be matched with the same contact in the next physics step.
 pub struct Contact {
     pub entities: Vec<Entity>,
+    pub boundary: Option<Entity>,
+    pub feature_id: u32,
     pub normal: DVec3,
     pub penetration: f64,
     pub point: DVec3,
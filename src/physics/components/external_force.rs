@@ -0,0 +1,122 @@
+use bevy::math::DVec3;
+
+use crate::{
+    physics::components::{
+        Force,
+        PhysTransform,
+        Torque,
+    },
+    physics::systems::force_and_torque,
+};
+
+#[derive(Debug, Default, Clone)]
+/// A component holding continuous forces and torques applied to a body by gameplay code. They are
+/// added to the body's Force and Torque accumulators every step until they are cleared.
+///
+/// Forces applied at a point in global coords produce a torque fixed at the time they are added,
+/// whilst those applied at a point in body coords move and turn with the body.
+pub struct ExternalForce {
+    force: DVec3,
+    torque: DVec3,
+    /// Forces and their points of application, both in body coords.
+    body_forces: Vec<(DVec3, DVec3)>,
+}
+
+impl ExternalForce {
+    /// Creates a new ExternalForce with the given force, through the centre of mass, and torque,
+    /// both in global coords.
+    pub fn new(force: DVec3, torque: DVec3) -> Self {
+        Self {
+            force,
+            torque,
+            body_forces: vec![],
+        }
+    }
+
+    /// Adds the given force, in global coords, acting through the centre of mass.
+    pub fn add(&mut self, force: DVec3) {
+        self.force += force;
+    }
+
+    /// Adds the given torque, in global coords.
+    pub fn add_torque(&mut self, torque: DVec3) {
+        self.torque += torque;
+    }
+
+    /// Adds the given force applied at the given point, both in global coords, to the body with
+    /// the given transform.
+    pub fn add_at_point(&mut self, force: DVec3, point: DVec3, transform: &PhysTransform) {
+        self.force += force;
+        self.torque += (point - transform.translation()).cross(force);
+    }
+
+    /// Adds the given force applied at the given point, both in body coords.
+    pub fn add_at_body_point(&mut self, force: DVec3, point: DVec3) {
+        self.body_forces.push((force, point));
+    }
+
+    /// Removes all forces and torques.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Returns true if no force or torque is applied.
+    pub fn is_zero(&self) -> bool {
+        self.force == DVec3::ZERO && self.torque == DVec3::ZERO && self.body_forces.is_empty()
+    }
+
+    /// Adds the forces and torques to the given accumulators of the body with the given transform.
+    pub fn update_force_and_torque(
+        &self,
+        force_accum: &mut Force,
+        torque_accum: &mut Torque,
+        transform: &PhysTransform,
+    ) {
+        force_and_torque::add_force(self.force, force_accum);
+        torque_accum.add(self.torque);
+
+        for (force, point) in self.body_forces.iter() {
+            force_and_torque::add_body_force_at_body_point(
+                *force,
+                *point,
+                transform,
+                force_accum,
+                torque_accum,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use bevy::math::DQuat;
+
+    const EPSILON: f64 = 0.000001;
+
+    #[test]
+    fn test_update_force_and_torque() {
+        let transform = PhysTransform::from_rotation_translation(
+            DQuat::from_rotation_z(std::f64::consts::FRAC_PI_2),
+            DVec3::new(1.0, 0.0, 0.0),
+        );
+
+        let mut external = ExternalForce::new(DVec3::Y, DVec3::new(0.0, 0.0, 0.5));
+        // pushing a point a unit above the body along x, turning it clockwise about z.
+        external.add_at_point(DVec3::X, DVec3::new(1.0, 1.0, 0.0), &transform);
+        // pushing the body's local x-axis (the global y-axis), 2 units out, along its local -y-axis
+        // (global x), turning it further clockwise.
+        external.add_at_body_point(DVec3::new(0.0, -1.0, 0.0), DVec3::new(2.0, 0.0, 0.0));
+
+        let mut force = Force::default();
+        let mut torque = Torque::default();
+        external.update_force_and_torque(&mut force, &mut torque, &transform);
+
+        assert!((force.vector() - DVec3::new(2.0, 1.0, 0.0)).length() < EPSILON);
+        assert!((torque.vector() - DVec3::new(0.0, 0.0, -2.5)).length() < EPSILON);
+
+        external.clear();
+        assert!(external.is_zero());
+    }
+}
@@ -0,0 +1,110 @@
+use bevy::math::{
+    DMat3,
+    DVec3,
+};
+
+use crate::physics::components::{
+    AngularVelocity,
+    PhysTransform,
+    Velocity,
+};
+
+#[derive(Debug, Default, Clone, Copy)]
+/// A component holding one-off impulses and impulsive torques applied to a body by gameplay code,
+/// e.g. for a jump or an explosion. They are applied to the body's Velocity and AngularVelocity at
+/// the start of the next physics step, then cleared.
+pub struct ExternalImpulse {
+    impulse: DVec3,
+    torque_impulse: DVec3,
+}
+
+impl ExternalImpulse {
+    /// Creates a new ExternalImpulse with the given impulse, through the centre of mass, and
+    /// impulsive torque, both in global coords.
+    pub fn new(impulse: DVec3, torque_impulse: DVec3) -> Self {
+        Self { impulse, torque_impulse }
+    }
+
+    /// Applies the given impulse, in global coords, through the centre of mass.
+    pub fn apply(&mut self, impulse: DVec3) {
+        self.impulse += impulse;
+    }
+
+    /// Applies the given impulsive torque, in global coords.
+    pub fn apply_torque(&mut self, torque_impulse: DVec3) {
+        self.torque_impulse += torque_impulse;
+    }
+
+    /// Applies the given impulse at the given point, both in global coords, to the body with the
+    /// given transform.
+    pub fn apply_at_point(&mut self, impulse: DVec3, point: DVec3, transform: &PhysTransform) {
+        self.impulse += impulse;
+        self.torque_impulse += (point - transform.translation()).cross(impulse);
+    }
+
+    /// Applies the given impulse at the given point, both in body coords, to the body with the
+    /// given transform.
+    pub fn apply_at_body_point(&mut self, impulse: DVec3, point: DVec3, transform: &PhysTransform) {
+        self.apply_at_point(
+            transform.get_direction_in_global_space(impulse),
+            transform.get_point_in_global_space(point),
+            transform,
+        );
+    }
+
+    /// Returns the total impulse applied, in global coords.
+    pub fn impulse(&self) -> DVec3 {
+        self.impulse
+    }
+
+    /// Returns the total impulsive torque applied, in global coords.
+    pub fn torque_impulse(&self) -> DVec3 {
+        self.torque_impulse
+    }
+
+    /// Returns true if no impulse or impulsive torque is applied.
+    pub fn is_zero(&self) -> bool {
+        self.impulse == DVec3::ZERO && self.torque_impulse == DVec3::ZERO
+    }
+
+    /// Changes the given velocities of a body with the given inverse mass and inverse inertia
+    /// tensor (in global coords) by the impulses applied, then clears them.
+    pub fn update_velocities(
+        &mut self,
+        velocity: &mut Velocity,
+        angular_velocity: &mut AngularVelocity,
+        inverse_mass: f64,
+        inverse_inertia_tensor: DMat3,
+    ) {
+        velocity.add(self.impulse * inverse_mass);
+        angular_velocity.add(inverse_inertia_tensor * self.torque_impulse);
+
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EPSILON: f64 = 0.000001;
+
+    #[test]
+    fn test_update_velocities() {
+        let transform = PhysTransform::from_xyz(0.0, 1.0, 0.0);
+
+        // an upward impulse at the front of the body.
+        let mut external = ExternalImpulse::default();
+        external.apply_at_point(DVec3::new(0.0, 2.0, 0.0), DVec3::new(0.0, 1.0, 1.0), &transform);
+        assert!((external.torque_impulse() - DVec3::new(-2.0, 0.0, 0.0)).length() < EPSILON);
+
+        let mut velocity = Velocity::default();
+        let mut angular_velocity = AngularVelocity::default();
+        external.update_velocities(&mut velocity, &mut angular_velocity, 0.5,
+                                   DMat3::from_diagonal(DVec3::splat(0.25)));
+
+        assert!((velocity.vector() - DVec3::Y).length() < EPSILON);
+        assert!((angular_velocity.vector() - DVec3::new(-0.5, 0.0, 0.0)).length() < EPSILON);
+        assert!(external.is_zero());
+    }
+}
@@ -31,10 +31,7 @@ impl Drag {
 
     /// Returns the force currently generated by drag.
     fn force(&mut self, velocity: DVec3) -> DVec3 {
-        let v_mag = velocity.length();
-        let coeff = self.k1 * v_mag + self.k2 * v_mag.powi(2);
-
-        let drag = -coeff * velocity.normalize_or_zero();
+        let drag = calc_drag(self.k1, self.k2, velocity);
 
         self.last = drag;
 
@@ -47,6 +44,15 @@ impl Drag {
     }
 }
 
+/// Returns the drag, with the given coefficients, on a body with the given velocity. Also used for
+/// angular drag, given an angular velocity.
+pub(super) fn calc_drag(k1: f64, k2: f64, velocity: DVec3) -> DVec3 {
+    let v_mag = velocity.length();
+    let coeff = k1 * v_mag + k2 * v_mag.powi(2);
+
+    -coeff * velocity.normalize_or_zero()
+}
+
 impl Default for Drag {
     fn default() -> Self {
         Self {
@@ -0,0 +1,270 @@
+use bevy::math::DVec3;
+use std::f64::consts::PI;
+
+use crate::{
+    physics::components::{
+        Force,
+        PhysTransform,
+        Torque,
+    },
+    physics::shapes::{
+        CollisionPrimative,
+        Cuboid,
+        Sphere,
+    },
+    physics::systems::force_and_torque,
+};
+
+use super::drag;
+
+/// A component that turns an entity with a Cuboid Collider into a volume of fluid, e.g. water.
+///
+/// The surface of the fluid is the upper face of the Cuboid (in its local +y direction). Bodies
+/// with a Gravity component and a Sphere or Cuboid Collider, whose centre lies within the volume
+/// or close enough above its surface to reach into it, are buoyed up against their gravity in
+/// proportion to the volume submerged beneath the surface, with the force acting at the centre of
+/// buoyancy. A Sphere's centre may be up to its radius above the surface, and a Cuboid's up to
+/// the distance from its centre to a corner. Submerged bodies are also slowed by the fluid's
+/// linear and angular drag, in proportion to the fraction of the body submerged. A fluid volume
+/// is usually also a Sensor, so that bodies pass into it.
+#[derive(Debug, Clone, Copy)]
+pub struct FluidVolume {
+    density: f64,
+    linear_drag: f64,
+    angular_drag: f64,
+}
+
+impl FluidVolume {
+    /// Creates a new FluidVolume with the given density and drag coefficients. The drag
+    /// coefficients scale the drag proportional to the linear and angular velocities.
+    pub fn new(density: f64, linear_drag: f64, angular_drag: f64) -> Self {
+        Self { density, linear_drag, angular_drag }
+    }
+
+    /// Returns the density of the fluid.
+    pub fn density(&self) -> f64 {
+        self.density
+    }
+
+    /// Returns true if the given point, in global coords, is within the fluid's Cuboid, or above
+    /// it by no more than the given height.
+    pub fn contains(&self, cuboid: &Cuboid, transform: &PhysTransform, point: DVec3, height: f64) -> bool {
+        let extents = cuboid.extents();
+        let point = transform.get_point_in_local_space(point);
+
+        point.x.abs() <= extents.x && point.z.abs() <= extents.z
+            && point.y >= -extents.y && point.y <= extents.y + height
+    }
+
+    /// Adds the buoyancy and drag currently generated by the fluid, on a body with the given
+    /// Collider shape, transform, velocity and angular velocity, to the given accumulators. The
+    /// surface of the fluid is given as a point on it and its upward normal, and 'g' is the
+    /// acceleration due to gravity of the body, which the buoyancy opposes. Returns false if the
+    /// body's shape is unsupported.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_force_and_torque(
+        &self,
+        force_accum: &mut Force,
+        torque_accum: &mut Torque,
+        primative: &dyn CollisionPrimative,
+        transform: &PhysTransform,
+        velocity: DVec3,
+        angular_velocity: DVec3,
+        surface: (DVec3, DVec3),
+        g: DVec3,
+    ) -> bool {
+        let (volume, centre, fraction) = match calc_submerged(primative, transform, surface) {
+            Some(submerged) => submerged,
+            None => return false,
+        };
+
+        if volume <= 0.0 { return true; }
+
+        // The velocity of the fluid relative to the centre of buoyancy.
+        let relative_centre = centre - transform.translation();
+        let centre_velocity = velocity + angular_velocity.cross(relative_centre);
+
+        let buoyancy = -self.density * volume * g;
+        let drag = drag::calc_drag(self.linear_drag * fraction, 0.0, centre_velocity);
+
+        force_and_torque::add_force_at_point(
+            buoyancy + drag,
+            centre,
+            transform.translation(),
+            force_accum,
+            torque_accum,
+        );
+        torque_accum.add(drag::calc_drag(self.angular_drag * fraction, 0.0, angular_velocity));
+
+        true
+    }
+}
+
+// --- Helper methods
+
+/// Returns the volume of the given shape submerged beneath the given surface, the centre of that
+/// volume in global coords, and the fraction of the shape submerged. The surface is given as a
+/// point on it and its upward normal. Returns None if the shape is not a Sphere or Cuboid.
+fn calc_submerged(
+    primative: &dyn CollisionPrimative,
+    transform: &PhysTransform,
+    (surface_point, up): (DVec3, DVec3),
+) -> Option<(f64, DVec3, f64)> {
+    let depth = |point: DVec3| (surface_point - point).dot(up);
+
+    if let Some(sphere) = primative.downcast_ref::<Sphere>() {
+        let r = sphere.radius();
+        let centre = transform.translation();
+
+        // the height of the spherical cap beneath the surface.
+        let h = (r + depth(centre)).min(2.0 * r);
+        if h <= 0.0 { return Some((0.0, centre, 0.0)); }
+
+        let volume = PI * h * h * (3.0 * r - h) / 3.0;
+        let offset = 3.0 * (2.0 * r - h).powi(2) / (4.0 * (3.0 * r - h));
+
+        Some((volume, centre - up * offset, volume / (4.0 / 3.0 * PI * r.powi(3))))
+    } else if let Some(cuboid) = primative.downcast_ref::<Cuboid>() {
+        let vertices = cuboid.vertices(transform);
+        let depths: Vec<f64> = vertices.iter().map(|v| depth(*v)).collect();
+
+        // split into 6 tetrahedra about the diagonal between vertices 0 and 7.
+        let (volume, moment) = [(1, 3), (1, 5), (2, 3), (2, 6), (4, 5), (4, 6)].iter()
+            .map(|(a, b)| calc_submerged_tetrahedron(
+                [vertices[0], vertices[*a], vertices[*b], vertices[7]],
+                [depths[0], depths[*a], depths[*b], depths[7]],
+            ))
+            .fold((0.0, DVec3::ZERO), |total, (v, m)| (total.0 + v, total.1 + m));
+
+        if volume <= 0.0 { return Some((0.0, transform.translation(), 0.0)); }
+
+        let extents = cuboid.extents();
+        Some((volume, moment / volume, volume / (8.0 * extents.x * extents.y * extents.z)))
+    } else {
+        None
+    }
+}
+
+/// Returns the volume of the given tetrahedron beneath a surface, given the depth of each vertex
+/// beneath that surface, and the volume multiplied by the centre of that volume.
+fn calc_submerged_tetrahedron(vertices: [DVec3; 4], depths: [f64; 4]) -> (f64, DVec3) {
+    let (below, above): (Vec<usize>, Vec<usize>) = (0..4).partition(|i| depths[*i] > 0.0);
+
+    // the point at which the edge between the given vertices crosses the surface.
+    let cross = |i: usize, j: usize| {
+        vertices[i] + (vertices[j] - vertices[i]) * depths[i] / (depths[i] - depths[j])
+    };
+
+    match below.len() {
+        0 => (0.0, DVec3::ZERO),
+        1 => {
+            let a = below[0];
+            calc_tetrahedron([vertices[a], cross(a, above[0]), cross(a, above[1]), cross(a, above[2])])
+        },
+        2 => {
+            let (a, b) = (below[0], below[1]);
+            let (c, d) = (above[0], above[1]);
+
+            // a triangular prism, split into 3 tetrahedra.
+            let prism = [
+                [vertices[a], cross(a, c), cross(a, d), vertices[b]],
+                [cross(a, c), cross(a, d), vertices[b], cross(b, c)],
+                [cross(a, d), vertices[b], cross(b, c), cross(b, d)],
+            ];
+
+            prism.iter()
+                .map(|t| calc_tetrahedron(*t))
+                .fold((0.0, DVec3::ZERO), |total, (v, m)| (total.0 + v, total.1 + m))
+        },
+        3 => {
+            let d = above[0];
+            let whole = calc_tetrahedron(vertices);
+            let tip = calc_tetrahedron([vertices[d], cross(d, below[0]), cross(d, below[1]), cross(d, below[2])]);
+
+            (whole.0 - tip.0, whole.1 - tip.1)
+        },
+        _ => calc_tetrahedron(vertices),
+    }
+}
+
+/// Returns the volume of the given tetrahedron, and the volume multiplied by its centroid.
+fn calc_tetrahedron(vertices: [DVec3; 4]) -> (f64, DVec3) {
+    let [a, b, c, d] = vertices;
+    let volume = (b - a).dot((c - a).cross(d - a)).abs() / 6.0;
+
+    (volume, volume * (a + b + c + d) / 4.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy::math::DQuat;
+
+    const EPSILON: f64 = 0.000001;
+
+    #[test]
+    fn test_calc_submerged_cuboid() {
+        let cuboid = Cuboid::new(DVec3::new(1.0, 1.0, 2.0));
+        let transform = PhysTransform::from_xyz(0.0, 0.5, 0.0);
+
+        // a quarter of the cuboid is beneath the surface at y = 0.
+        let (volume, centre, fraction) = calc_submerged(&cuboid, &transform, (DVec3::ZERO, DVec3::Y))
+            .unwrap();
+        assert!((volume - 4.0).abs() < EPSILON);
+        assert!((centre - DVec3::new(0.0, -0.25, 0.0)).length() < EPSILON);
+        assert!((fraction - 0.25).abs() < EPSILON);
+
+        // the volumes either side of the surface make up the whole, at any orientation.
+        let transform = PhysTransform::from_rotation_translation(
+            DQuat::from_axis_angle(DVec3::new(1.0, 2.0, 3.0).normalize(), 0.7),
+            DVec3::new(0.0, 0.3, 0.0),
+        );
+        let (below, _, _) = calc_submerged(&cuboid, &transform, (DVec3::ZERO, DVec3::Y)).unwrap();
+        let (above, _, _) = calc_submerged(&cuboid, &transform, (DVec3::ZERO, -DVec3::Y)).unwrap();
+        assert!((below + above - 16.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_calc_submerged_sphere() {
+        let sphere = Sphere::new(2.0);
+
+        // half submerged.
+        let transform = PhysTransform::from_xyz(0.0, 0.0, 0.0);
+        let (volume, centre, fraction) = calc_submerged(&sphere, &transform, (DVec3::ZERO, DVec3::Y))
+            .unwrap();
+        assert!((volume - 16.0 / 3.0 * PI).abs() < EPSILON);
+        assert!((centre - DVec3::new(0.0, -0.75, 0.0)).length() < EPSILON);
+        assert!((fraction - 0.5).abs() < EPSILON);
+
+        // fully submerged, and clear of the surface.
+        let transform = PhysTransform::from_xyz(0.0, -5.0, 0.0);
+        let (_, centre, fraction) = calc_submerged(&sphere, &transform, (DVec3::ZERO, DVec3::Y))
+            .unwrap();
+        assert!((centre - transform.translation()).length() < EPSILON);
+        assert!((fraction - 1.0).abs() < EPSILON);
+
+        let transform = PhysTransform::from_xyz(0.0, 5.0, 0.0);
+        let (volume, _, _) = calc_submerged(&sphere, &transform, (DVec3::ZERO, DVec3::Y)).unwrap();
+        assert_eq!(0.0, volume);
+    }
+
+    #[test]
+    fn test_update_force_and_torque() {
+        let fluid = FluidVolume::new(2.0, 1.0, 1.0);
+        let cuboid = Cuboid::new(DVec3::new(2.0, 0.5, 2.0));
+
+        // a half submerged raft tilted about z is pushed back upright.
+        let transform = PhysTransform::from_rotation_translation(
+            DQuat::from_rotation_z(0.3),
+            DVec3::ZERO,
+        );
+        let mut force = Force::default();
+        let mut torque = Torque::default();
+        fluid.update_force_and_torque(&mut force, &mut torque, &cuboid, &transform, DVec3::ZERO,
+                                      DVec3::ZERO, (DVec3::ZERO, DVec3::Y),
+                                      DVec3::new(0.0, -10.0, 0.0));
+
+        assert!((force.vector() - DVec3::new(0.0, 160.0, 0.0)).length() < EPSILON);
+        assert!(torque.vector().z < 0.0);
+    }
+}
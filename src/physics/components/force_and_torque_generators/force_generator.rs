@@ -0,0 +1,41 @@
+use crate::physics::components::{
+    AngularVelocity,
+    Force,
+    InertiaTensor,
+    Mass,
+    PhysTransform,
+    Torque,
+    Velocity,
+};
+
+/// The state of a body, passed to a ForceGenerator when it is updated.
+pub struct ForceGeneratorBody<'a> {
+    pub mass: &'a Mass,
+    pub velocity: &'a Velocity,
+    pub angular_velocity: &'a AngularVelocity,
+    pub transform: &'a PhysTransform,
+    pub inertia_tensor: &'a InertiaTensor,
+    /// The duration of the physics step.
+    pub step: f64,
+}
+
+/// A user-defined force and torque generator, e.g. wind, magnetism or scripted forces.
+///
+/// A type implementing ForceGenerator is added to a body as a component, and must be registered
+/// with 'AppBuilder::add_force_generator' for it to be updated. It is then updated once per
+/// physics step, alongside the built-in generators, for each body it is added to. Sleeping bodies
+/// are included, and are woken by any non-zero force or torque the generator adds, so a generator
+/// should add nothing when it has no effect, e.g. once the wind has dropped.
+///
+/// Forces and torques are added to the accumulators in the same way as the built-in generators,
+/// typically through the functions re-exported from the physics module, e.g. 'add_force_at_point'.
+pub trait ForceGenerator: Send + Sync + 'static {
+    /// Adds the force and torque currently generated, on the given body, to the given
+    /// accumulators.
+    fn update_force_and_torque(
+        &mut self,
+        body: &ForceGeneratorBody,
+        force_accum: &mut Force,
+        torque_accum: &mut Torque,
+    );
+}
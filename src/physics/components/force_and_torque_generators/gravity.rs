@@ -1,44 +1,46 @@
 use bevy::math::DVec3;
 
 use crate::{
-    constants,
     physics::components::Force,
     physics::systems::force_and_torque,
 };
 
-#[derive(Debug)]
-/// A force generator for Gravity.
+#[derive(Debug, Default)]
+/// A force generator for Gravity. By default the value of 'g' is taken from the PhysicsConfig
+/// resource, unless the generator is given its own. Only bodies with a Gravity component are
+/// affected by GravityZones and PointAttractors.
 pub struct Gravity {
-    g: DVec3,
+    g: Option<DVec3>,
 }
 
 impl Gravity {
-    /// Creates a new gravity force generator with the given value for g.
+    /// Creates a new gravity force generator with the given value for g, overriding the value in
+    /// the PhysicsConfig.
     pub fn new(g: DVec3) -> Self {
-        Self { g }
+        Self { g: Some(g) }
     }
 
     /// Adds the force generated by gravity acting on the given mass to the given Force accumulator.
-    pub fn update_force(&self, force_accum: &mut Force, mass: f64) {
-        force_and_torque::add_force(self.force(mass), force_accum);
+    /// The given world value of g is used unless this generator has its own.
+    pub fn update_force(&self, force_accum: &mut Force, mass: f64, world_g: DVec3) {
+        force_and_torque::add_force(self.force(mass, world_g), force_accum);
 
     }
 
-    /// Returns the force currently generated by gravity on the given mass.
-    fn force(&self, mass: f64) -> DVec3 {
-        mass * self.g
+    /// Returns the value of 'g' acting on the body. The given world value of g is used unless this
+    /// generator has its own.
+    pub fn g(&self, world_g: DVec3) -> DVec3 {
+        self.g.unwrap_or(world_g)
     }
 
-    /// Returns the current force of gravity as a vector.
-    pub fn vector(&self, mass: f64) -> DVec3 {
-        self.force(mass)
+    /// Returns the force currently generated by gravity on the given mass.
+    fn force(&self, mass: f64, world_g: DVec3) -> DVec3 {
+        mass * self.g(world_g)
     }
-}
 
-impl Default for Gravity {
-    fn default() -> Self {
-        Self {
-            g: *constants::DEFAULT_GRAVITY,
-        }
+    /// Returns the current force of gravity as a vector. The given world value of g is used unless
+    /// this generator has its own.
+    pub fn vector(&self, mass: f64, world_g: DVec3) -> DVec3 {
+        self.force(mass, world_g)
     }
 }
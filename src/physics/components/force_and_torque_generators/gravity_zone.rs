@@ -0,0 +1,91 @@
+use bevy::math::DVec3;
+
+/// How a GravityZone combines with the gravity otherwise acting on a body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GravityZoneMode {
+    /// Replaces the value of 'g' otherwise acting on the body, including one given by its Gravity.
+    Override,
+    /// Adds to the value of 'g' otherwise acting on the body.
+    Add,
+}
+
+/// A component that turns an entity with a Collider into a volume of gravity. Bodies with a
+/// Gravity component whose centre of mass is inside the Collider have their value of 'g'
+/// overridden by, or added to, that of the zone.
+///
+/// Where override zones overlap, the one with the highest priority applies. A zone is usually
+/// also a Sensor, so that bodies pass through it.
+#[derive(Debug, Clone, Copy)]
+pub struct GravityZone {
+    g: DVec3,
+    mode: GravityZoneMode,
+    priority: i32,
+}
+
+impl GravityZone {
+    /// Creates a new GravityZone that replaces gravity with the given value of 'g'.
+    pub fn overriding(g: DVec3) -> Self {
+        Self { g, mode: GravityZoneMode::Override, priority: 0 }
+    }
+
+    /// Creates a new GravityZone that adds the given value of 'g' to gravity.
+    pub fn adding(g: DVec3) -> Self {
+        Self { g, mode: GravityZoneMode::Add, priority: 0 }
+    }
+
+    /// Returns the GravityZone with the given priority, used to choose between overlapping
+    /// override zones.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Returns the value of 'g' of the zone.
+    pub fn g(&self) -> DVec3 {
+        self.g
+    }
+
+    /// Returns the GravityZoneMode of the zone.
+    pub fn mode(&self) -> GravityZoneMode {
+        self.mode
+    }
+
+    /// Returns the value of 'g' acting on a body inside all of the given zones, given the value
+    /// of 'g' that would otherwise act on it.
+    pub fn combine<'a>(g: DVec3, zones: impl Iterator<Item = &'a GravityZone>) -> DVec3 {
+        let mut overriding: Option<&GravityZone> = None;
+        let mut added = DVec3::ZERO;
+
+        for zone in zones {
+            match zone.mode {
+                GravityZoneMode::Add => added += zone.g,
+                GravityZoneMode::Override => {
+                    if !matches!(overriding, Some(o) if o.priority >= zone.priority) {
+                        overriding = Some(zone);
+                    }
+                },
+            }
+        }
+
+        overriding.map_or(g, |o| o.g) + added
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_combine() {
+        let g = DVec3::new(0.0, -10.0, 0.0);
+        let low = GravityZone::overriding(DVec3::X);
+        let high = GravityZone::overriding(DVec3::Z).with_priority(1);
+        let wind = GravityZone::adding(DVec3::Y);
+
+        assert_eq!(g, GravityZone::combine(g, [].iter()));
+        assert_eq!(g + DVec3::Y, GravityZone::combine(g, [wind].iter()));
+        assert_eq!(DVec3::X + DVec3::Y, GravityZone::combine(g, [low, wind].iter()));
+        assert_eq!(DVec3::Z, GravityZone::combine(g, [low, high].iter()));
+        assert_eq!(DVec3::Z, GravityZone::combine(g, [high, low].iter()));
+    }
+}
@@ -1,9 +1,28 @@
 mod drag;
+mod fluid_volume;
+mod force_generator;
 mod gravity;
+mod gravity_zone;
+mod point_attractor;
 mod rotator;
+mod spring;
 mod thrust;
 
 pub use drag::Drag;
+pub use fluid_volume::FluidVolume;
+pub use force_generator::{
+    ForceGenerator,
+    ForceGeneratorBody,
+};
 pub use gravity::Gravity;
+pub use gravity_zone::{
+    GravityZone,
+    GravityZoneMode,
+};
+pub use point_attractor::PointAttractor;
 pub use rotator::Rotator;
+pub use spring::{
+    Spring,
+    SpringAnchor,
+};
 pub use thrust::Thrust;
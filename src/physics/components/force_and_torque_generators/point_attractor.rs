@@ -0,0 +1,70 @@
+use bevy::math::DVec3;
+
+use crate::constants;
+
+/// A component that attracts bodies with a Gravity component towards the entity's position,
+/// e.g. a planet. The attraction follows the inverse-square law, scaled by the gravitational
+/// constant in the PhysicsConfig, out to the falloff radius beyond which it has no effect.
+#[derive(Debug, Clone, Copy)]
+pub struct PointAttractor {
+    mass: f64,
+    falloff_radius: f64,
+}
+
+impl PointAttractor {
+    /// Creates a new PointAttractor with the given mass, which attracts bodies within the given
+    /// falloff radius.
+    pub fn new(mass: f64, falloff_radius: f64) -> Self {
+        Self { mass, falloff_radius }
+    }
+
+    /// Returns the mass of the attractor.
+    pub fn mass(&self) -> f64 {
+        self.mass
+    }
+
+    /// Returns the radius beyond which the attractor has no effect.
+    pub fn falloff_radius(&self) -> f64 {
+        self.falloff_radius
+    }
+
+    /// Returns the acceleration, due to the attractor at the given position, of a body at the
+    /// given point. Within a minimum distance of the attractor the acceleration is capped, to
+    /// avoid the singularity at its centre.
+    pub fn acceleration(&self, position: DVec3, point: DVec3, gravitational_constant: f64) -> DVec3 {
+        let separation = position - point;
+        let distance = separation.length();
+
+        if distance == 0.0 || distance > self.falloff_radius {
+            return DVec3::ZERO;
+        }
+
+        let distance_sq = distance.max(constants::ATTRACTOR_MIN_DISTANCE).powi(2);
+
+        separation / distance * gravitational_constant * self.mass / distance_sq
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EPSILON: f64 = 0.000001;
+
+    #[test]
+    fn test_acceleration() {
+        let attractor = PointAttractor::new(100.0, 10.0);
+        let position = DVec3::new(0.0, 2.0, 0.0);
+
+        let a = attractor.acceleration(position, DVec3::new(0.0, 7.0, 0.0), 0.5);
+        assert!((a - DVec3::new(0.0, -2.0, 0.0)).length() < EPSILON);
+
+        // inverse-square.
+        let a = attractor.acceleration(position, DVec3::new(4.5, 2.0, 0.0), 0.5);
+        assert!((a - DVec3::new(-4.0 * 50.0 / 81.0, 0.0, 0.0)).length() < EPSILON);
+
+        // beyond the falloff radius, or at the centre.
+        assert_eq!(DVec3::ZERO, attractor.acceleration(position, DVec3::new(0.0, 13.0, 0.0), 0.5));
+        assert_eq!(DVec3::ZERO, attractor.acceleration(position, position, 0.5));
+    }
+}
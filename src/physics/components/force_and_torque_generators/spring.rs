@@ -0,0 +1,283 @@
+use bevy::{
+    prelude::*,
+    math::DVec3,
+};
+
+use crate::{
+    physics::components::{
+        Force,
+        Mass,
+        PhysTransform,
+        Torque,
+    },
+    physics::systems::force_and_torque,
+};
+
+/// The end of a Spring that is not attached to the body.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpringAnchor {
+    /// A point on another Entity, in that Entity's body coords.
+    Entity(Entity, DVec3),
+    /// A fixed point in global coords.
+    World(DVec3),
+}
+
+#[derive(Debug)]
+/// A force and torque generator representing a spring, with a damper, attaching a point on a body
+/// to an anchor.
+///
+/// The spring only acts on the body it is added to; add a matching Spring to an anchoring Entity
+/// for it to be pulled in return.
+pub struct Spring {
+    connection_point: DVec3,
+    anchor: SpringAnchor,
+    rest_length: f64,
+    stiffness: f64,
+    damping: f64,
+    bungee: bool,
+    stiff: bool,
+    last: DVec3, // cached value.
+}
+
+impl Spring {
+    /// Creates a new Spring attaching the given point, in body coords, to the given anchor. The
+    /// force exerted is proportional to the stiffness and the extension beyond the rest length,
+    /// plus the damping and the speed at which the spring is extending.
+    pub fn new(
+        connection_point: DVec3,
+        anchor: SpringAnchor,
+        rest_length: f64,
+        stiffness: f64,
+        damping: f64,
+    ) -> Self {
+        Self {
+            connection_point,
+            anchor,
+            rest_length,
+            stiffness,
+            damping,
+            bungee: false,
+            stiff: false,
+            last: DVec3::ZERO,
+        }
+    }
+
+    /// Returns the Spring as a bungee, which only pulls when extended beyond its rest length and
+    /// exerts no force when compressed.
+    pub fn bungee(mut self) -> Self {
+        self.bungee = true;
+        self
+    }
+
+    /// Returns the Spring as a stiff spring, which remains stable at large timesteps. Rather than
+    /// applying the spring force directly, the motion of the spring over the step is predicted
+    /// and the force that reproduces that motion is applied. Only under-damped springs are
+    /// predicted; any others act as a normal spring.
+    pub fn stiff(mut self) -> Self {
+        self.stiff = true;
+        self
+    }
+
+    /// Returns the anchor of the Spring.
+    pub fn anchor(&self) -> SpringAnchor {
+        self.anchor
+    }
+
+    /// Adds the force and torque currently generated by the spring to the given accumulators. The
+    /// body has the given transform, velocity, angular velocity and Mass. The anchor is given as
+    /// its position and velocity in global coords, and the step is the duration of the physics
+    /// step.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_force_and_torque(
+        &mut self,
+        force_accum: &mut Force,
+        torque_accum: &mut Torque,
+        transform: &PhysTransform,
+        velocity: DVec3,
+        angular_velocity: DVec3,
+        mass: &Mass,
+        anchor: (DVec3, DVec3),
+        step: f64,
+    ) {
+        let point = transform.get_point_in_global_space(self.connection_point);
+        let point_velocity = velocity + angular_velocity.cross(point - transform.translation());
+
+        let force = self.force(point - anchor.0, point_velocity - anchor.1, mass, step);
+
+        force_and_torque::add_body_force_at_body_point(
+            transform.get_direction_in_local_space(force),
+            self.connection_point,
+            transform,
+            force_accum,
+            torque_accum,
+        );
+    }
+
+    /// Adds the force and torque currently generated by the spring to the given accumulators of a
+    /// sleeping body, but only if the force has changed since the body fell asleep by enough to
+    /// change its velocity over the step by at least the given threshold, e.g. because the anchor
+    /// has moved. The non-zero accumulators then wake the body. Returns true if they were added.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_sleeping_force_and_torque(
+        &mut self,
+        force_accum: &mut Force,
+        torque_accum: &mut Torque,
+        transform: &PhysTransform,
+        mass: &Mass,
+        anchor: (DVec3, DVec3),
+        step: f64,
+        threshold: f64,
+    ) -> bool {
+        let resting = self.last;
+
+        let mut force = Force::default();
+        let mut torque = Torque::default();
+        self.update_force_and_torque(&mut force, &mut torque, transform, DVec3::ZERO, DVec3::ZERO,
+                                     mass, anchor, step);
+
+        if (self.last - resting).length() * mass.inverse() * step < threshold {
+            self.last = resting;
+            return false;
+        }
+
+        force_accum.add(force.vector());
+        torque_accum.add(torque.vector());
+        true
+    }
+
+    /// Returns the force currently generated by the spring, in global coords, given the
+    /// separation and relative velocity of the connection point from the anchor.
+    fn force(&mut self, separation: DVec3, relative_velocity: DVec3, mass: &Mass, step: f64) -> DVec3 {
+        let length = separation.length();
+        let extension = length - self.rest_length;
+
+        self.last = if length == 0.0 || (self.bungee && extension <= 0.0) {
+            DVec3::ZERO
+        } else {
+            let direction = separation / length;
+            let speed = relative_velocity.dot(direction);
+
+            let magnitude = if self.stiff && mass.is_normal() && step > 0.0 {
+                self.calc_stiff_magnitude(extension, speed, mass.value(), step)
+            } else {
+                None
+            };
+
+            direction * magnitude.unwrap_or(-self.stiffness * extension - self.damping * speed)
+        };
+
+        self.last
+    }
+
+    /// Returns the magnitude of the force, along the spring, that moves the body to where a
+    /// damped harmonic oscillator with the given extension and speed would be at the end of the
+    /// step, or None if the spring is not under-damped.
+    fn calc_stiff_magnitude(&self, extension: f64, speed: f64, mass: f64, step: f64) -> Option<f64> {
+        let stiffness = self.stiffness / mass;
+        let damping = self.damping / mass;
+
+        let discriminant = 4.0 * stiffness - damping * damping;
+        if discriminant <= 0.0 { return None; }
+
+        // x(t) = (x0 cos(gamma t) + c sin(gamma t)) e^(-damping t / 2)
+        let gamma = 0.5 * discriminant.sqrt();
+        let c = extension * damping / (2.0 * gamma) + speed / gamma;
+
+        let target = (extension * (gamma * step).cos() + c * (gamma * step).sin())
+            * (-0.5 * damping * step).exp();
+
+        // The acceleration that, once integrated over the step, takes the body to the target.
+        let acceleration = (target - extension) / (step * step) - speed / step;
+
+        Some(mass * acceleration)
+    }
+
+    /// Returns the last calculated spring force as a vector, in global coords.
+    pub fn vector(&self) -> DVec3 {
+        self.last
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EPSILON: f64 = 0.000001;
+
+    #[test]
+    fn test_update_force_and_torque() {
+        let mut spring = Spring::new(DVec3::X, SpringAnchor::World(DVec3::X), 1.0, 10.0, 2.0);
+        let transform = PhysTransform::from_xyz(0.0, 3.0, 0.0);
+        let mass = Mass::new(1.0);
+
+        // extended by 2, and extending at 1.
+        let mut force = Force::default();
+        let mut torque = Torque::default();
+        spring.update_force_and_torque(&mut force, &mut torque, &transform, DVec3::Y, DVec3::ZERO,
+                                       &mass, (DVec3::X, DVec3::ZERO), 0.1);
+        assert!((force.vector() - DVec3::new(0.0, -22.0, 0.0)).length() < EPSILON);
+        assert!((torque.vector() - DVec3::new(0.0, 0.0, -22.0)).length() < EPSILON);
+
+        // compressed, a spring pushes and a bungee goes slack.
+        let transform = PhysTransform::from_xyz(0.0, 0.5, 0.0);
+        spring.force(DVec3::new(0.0, 0.5, 0.0), DVec3::ZERO, &mass, 0.1);
+        assert!((spring.vector() - DVec3::new(0.0, 5.0, 0.0)).length() < EPSILON);
+
+        let mut bungee = Spring::new(DVec3::X, SpringAnchor::World(DVec3::X), 1.0, 10.0, 2.0)
+            .bungee();
+        let mut force = Force::default();
+        bungee.update_force_and_torque(&mut force, &mut torque, &transform, DVec3::ZERO,
+                                       DVec3::ZERO, &mass, (DVec3::X, DVec3::ZERO), 0.1);
+        assert_eq!(DVec3::ZERO, force.vector());
+    }
+
+    #[test]
+    fn test_update_sleeping_force_and_torque() {
+        let mut spring = Spring::new(DVec3::ZERO, SpringAnchor::World(DVec3::ZERO), 1.0, 10.0, 0.0);
+        let transform = PhysTransform::from_xyz(0.0, -2.0, 0.0);
+        let mass = Mass::new(1.0);
+        let mut force = Force::default();
+        let mut torque = Torque::default();
+
+        // the body fell asleep hanging from the spring, extended by 1.
+        spring.force(DVec3::new(0.0, -2.0, 0.0), DVec3::ZERO, &mass, 0.1);
+
+        // a still anchor leaves the body asleep.
+        assert!(!spring.update_sleeping_force_and_torque(&mut force, &mut torque, &transform,
+                                                         &mass, (DVec3::ZERO, DVec3::ZERO), 0.1,
+                                                         0.1));
+        assert_eq!(DVec3::ZERO, force.vector());
+
+        // a slight move of the anchor is not enough to wake it, nor is it remembered.
+        let anchor = (DVec3::new(0.0, 0.05, 0.0), DVec3::ZERO);
+        assert!(!spring.update_sleeping_force_and_torque(&mut force, &mut torque, &transform,
+                                                         &mass, anchor, 0.1, 0.1));
+        assert!((spring.vector() - DVec3::new(0.0, 10.0, 0.0)).length() < EPSILON);
+
+        // raising the anchor further pulls hard enough to wake it.
+        let anchor = (DVec3::new(0.0, 1.0, 0.0), DVec3::ZERO);
+        assert!(spring.update_sleeping_force_and_torque(&mut force, &mut torque, &transform,
+                                                        &mass, anchor, 0.1, 0.1));
+        assert!((force.vector() - DVec3::new(0.0, 20.0, 0.0)).length() < EPSILON);
+    }
+
+    #[test]
+    fn test_stiff() {
+        // a spring far too stiff for the step, extended by 2.
+        let mass = Mass::new(1.0);
+        let separation = DVec3::new(0.0, 3.0, 0.0);
+        let step = 1.0;
+
+        // the body's extension after integrating the force over the step.
+        let calc_extension = |spring: &mut Spring| {
+            let acceleration = spring.force(separation, DVec3::ZERO, &mass, step).y;
+            separation.y + acceleration * step * step - 1.0
+        };
+
+        let mut spring = Spring::new(DVec3::ZERO, SpringAnchor::World(DVec3::ZERO), 1.0, 100.0, 1.0);
+        assert!(calc_extension(&mut spring).abs() > 2.0);
+
+        let mut spring = spring.stiff();
+        assert!(calc_extension(&mut spring).abs() <= 2.0);
+    }
+}
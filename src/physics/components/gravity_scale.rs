@@ -0,0 +1,10 @@
+/// A component that scales the effect of all gravity on a body, including GravityZones and
+/// PointAttractors. Bodies without a GravityScale have a scale of 1.0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GravityScale(pub f64);
+
+impl Default for GravityScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
@@ -1,5 +1,7 @@
 use bevy::math::{DMat3, DMat4, DVec3};
 
+use crate::physics::components::PhysTransform;
+
 use crate::constants;
 
 #[derive(Debug)]
@@ -41,6 +43,35 @@ impl InertiaTensor {
         Self::new(DMat3::from_diagonal(DVec3::new(element, element, element)))
     }
 
+    /// Instantiates an inertia tensor for a solid capsule, aligned with the y-axis, with the given
+    /// mass, radius and half-length of its central segment.
+    pub fn capsule(mass: f64, radius: f64, half_height: f64) -> Self {
+        // split the mass between the cylindrical body and the hemispherical ends by volume.
+        let height = 2.0 * half_height;
+        let cylinder_volume = std::f64::consts::PI * radius.powi(2) * height;
+        let spheres_volume = 4.0 / 3.0 * std::f64::consts::PI * radius.powi(3);
+        let cylinder_mass = mass * cylinder_volume / (cylinder_volume + spheres_volume);
+        let spheres_mass = mass - cylinder_mass;
+
+        // the hemispheres are offset from the centre, by their centre of mass, along the y-axis.
+        let axial = cylinder_mass * radius.powi(2) / 2.0 + spheres_mass * 0.4 * radius.powi(2);
+        let transverse = cylinder_mass * (height.powi(2) / 12.0 + radius.powi(2) / 4.0) +
+            spheres_mass * (0.4 * radius.powi(2) + height.powi(2) / 4.0 + 3.0 * height * radius / 8.0);
+
+        Self::new(DMat3::from_diagonal(DVec3::new(transverse, axial, transverse)))
+    }
+
+    /// Instantiates an inertia tensor for a solid cylinder, aligned with the y-axis, with the
+    /// given mass, radius and half-height.
+    pub fn cylinder(mass: f64, radius: f64, half_height: f64) -> Self {
+        let height = 2.0 * half_height;
+
+        let axial = mass * radius.powi(2) / 2.0;
+        let transverse = mass * (3.0 * radius.powi(2) + height.powi(2)) / 12.0;
+
+        Self::new(DMat3::from_diagonal(DVec3::new(transverse, axial, transverse)))
+    }
+
     /// Instantiates an inertia tensor for a solid sphere with the given mass and radius.
     pub fn fixed_sphere() -> Self {
         let element = f64::INFINITY;
@@ -52,8 +83,32 @@ impl InertiaTensor {
         }
     }
 
+    /// Instantiates an inertia tensor for a body made up of several parts, given the mass,
+    /// transform relative to the body and inertia tensor of each part. The tensors are combined
+    /// about the body origin using the parallel-axis theorem, so the origin should be the centre
+    /// of mass of the parts.
+    pub fn compound(parts: &[(f64, &PhysTransform, &InertiaTensor)]) -> Self {
+        let tensor = parts.iter()
+            .fold(DMat3::ZERO, |tensor, (mass, transform, part)| {
+                // rotate the part's tensor into the body's basis, then offset it from the origin.
+                let rotation = DMat3::from_quat(transform.rotation());
+                let d = transform.translation();
+                let offset = DMat3::from_diagonal(DVec3::splat(d.length_squared()))
+                    - DMat3::from_cols(d * d.x, d * d.y, d * d.z);
+
+                tensor + rotation * part.tensor * rotation.transpose() + offset * *mass
+            });
+
+        Self::new(tensor)
+    }
+
+    /// Returns the inertia tensor with respect to local body coords.
+    pub fn tensor(&self) -> DMat3 {
+        self.tensor
+    }
+
     // TODO inertia tensors for other standard shapes.
-    // Ellipsoid, shell-sphere, cylinder, cone, hemisphere...
+    // Ellipsoid, shell-sphere, cone, hemisphere...
 
     /// Returns the inverse inertia tensor with respect to local body coords.
     pub fn inverse(&self) -> DMat3 {
@@ -94,3 +149,38 @@ impl Default for InertiaTensor {
         Self::sphere(constants::DEFAULT_MASS, constants::DEFAULT_RADIUS)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EPSILON: f64 = 0.000001;
+
+    #[test]
+    fn test_compound() {
+        // two unit spheres, each of unit mass, either side of the origin on the x-axis.
+        let sphere = InertiaTensor::sphere(1.0, 1.0);
+        let left = PhysTransform::from_xyz(-2.0, 0.0, 0.0);
+        let right = PhysTransform::from_xyz(2.0, 0.0, 0.0);
+
+        let tensor = InertiaTensor::compound(&[(1.0, &left, &sphere), (1.0, &right, &sphere)]).tensor();
+        let expected = DMat3::from_diagonal(DVec3::new(0.8, 8.8, 8.8));
+
+        for i in 0..3 {
+            assert!((tensor.col(i) - expected.col(i)).length() < EPSILON);
+        }
+
+        // a rotated cuboid part has its tensor rotated into the body's basis.
+        let cuboid = InertiaTensor::cuboid(12.0, 1.0, 2.0, 3.0);
+        let rotated = PhysTransform::from_rotation(
+            bevy::math::DQuat::from_rotation_z(std::f64::consts::PI * 0.5),
+        );
+
+        let tensor = InertiaTensor::compound(&[(12.0, &rotated, &cuboid)]).tensor();
+        let expected = DMat3::from_diagonal(DVec3::new(10.0, 13.0, 5.0));
+
+        for i in 0..3 {
+            assert!((tensor.col(i) - expected.col(i)).length() < EPSILON);
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use bevy::{
+    prelude::*,
+    math::DVec3,
+};
+
+/// A component that constrains the relative motion of two bodies, e.g. to hang a door on a hinge
+/// or link the bodies of a chain. A body can take part in any number of joints, so a Joint is
+/// usually added to an Entity of its own.
+///
+/// Each body is attached at an anchor point and has a joint axis, both in its local coords. The
+/// axes of a Hinge or Slider are held in line, and the bodies' local frames are considered aligned
+/// when their axes and reference directions (a direction perpendicular to each local axis)
+/// coincide. Hinge angles are measured from, and Fixed joints hold, that alignment. Unless set
+/// with with_references, the references are captured from the relative orientation of the bodies
+/// when the joint is first resolved, so that a Hinge starts at an angle of zero.
+///
+/// Joints are resolved alongside contacts, in the collision response stage. Contacts between the
+/// connected bodies are ignored unless collide_connected is set.
+///
+/// Both entities must be bodies, with a Mass and InertiaTensor, or the Joint is skipped with a
+/// warning. To fix a body to the world, join it to a Static body.
+#[derive(Debug, Clone)]
+pub struct Joint {
+    /// The bodies connected by the joint.
+    pub entities: [Entity; 2],
+    /// The point at which the joint is attached to each body, in the body's local coords.
+    pub local_anchors: [DVec3; 2],
+    /// The axis of the joint in each body's local coords. A zero axis is taken to be the y-axis.
+    pub local_axes: [DVec3; 2],
+    /// The reference direction, perpendicular to the axis, in each body's local coords. None
+    /// until captured when the joint is first resolved, unless set.
+    pub local_references: Option<[DVec3; 2]>,
+    pub kind: JointKind,
+    /// When true, contacts between the connected bodies are resolved as normal.
+    pub collide_connected: bool,
+    // the angle of a Hinge, tracked through whole turns.
+    angle: f64,
+}
+
+/// The degrees of freedom removed by a Joint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JointKind {
+    /// Holds the anchors together and the bodies' local frames aligned.
+    Fixed,
+    /// Holds the anchors together, leaving the bodies free to rotate.
+    BallAndSocket,
+    /// Holds the anchors together and the axes in line, leaving the bodies free to rotate about
+    /// the axis. The angle may be limited to a range, in radians, and driven by a motor.
+    Hinge {
+        limits: Option<(f64, f64)>,
+        motor: Option<JointMotor>,
+    },
+    /// Holds the bodies' local frames aligned and the second anchor on the first body's axis,
+    /// leaving the bodies free to slide along it. The displacement of the second anchor along the
+    /// axis may be limited to a range.
+    Slider {
+        limits: Option<(f64, f64)>,
+    },
+    /// Holds the anchors at the given distance apart, as though joined by a rigid rod.
+    Distance {
+        length: f64,
+    },
+}
+
+/// Drives a Hinge towards a target relative angular speed, in radians per second, using no more
+/// than the given torque.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointMotor {
+    pub target_speed: f64,
+    pub max_torque: f64,
+}
+
+impl Joint {
+    /// Creates a new Fixed Joint between the given bodies at the given local anchors. Both local
+    /// axes are the y-axis; use with_axes to hold the bodies in a different relative orientation.
+    pub fn fixed(entities: [Entity; 2], local_anchors: [DVec3; 2]) -> Self {
+        Self::new(entities, local_anchors, [DVec3::Y; 2], JointKind::Fixed)
+    }
+
+    /// Creates a new BallAndSocket Joint between the given bodies at the given local anchors.
+    pub fn ball_and_socket(entities: [Entity; 2], local_anchors: [DVec3; 2]) -> Self {
+        Self::new(entities, local_anchors, [DVec3::Y; 2], JointKind::BallAndSocket)
+    }
+
+    /// Creates a new Hinge Joint, with no limits or motor, between the given bodies at the given
+    /// local anchors and about the given local axes.
+    pub fn hinge(entities: [Entity; 2], local_anchors: [DVec3; 2], local_axes: [DVec3; 2]) -> Self {
+        Self::new(entities, local_anchors, local_axes, JointKind::Hinge { limits: None, motor: None })
+    }
+
+    /// Creates a new Slider Joint, with no limits, between the given bodies at the given local
+    /// anchors and along the given local axes.
+    pub fn slider(entities: [Entity; 2], local_anchors: [DVec3; 2], local_axes: [DVec3; 2]) -> Self {
+        Self::new(entities, local_anchors, local_axes, JointKind::Slider { limits: None })
+    }
+
+    /// Creates a new Distance Joint, holding the given local anchors at the given distance apart.
+    pub fn distance(entities: [Entity; 2], local_anchors: [DVec3; 2], length: f64) -> Self {
+        Self::new(entities, local_anchors, [DVec3::Y; 2], JointKind::Distance { length })
+    }
+
+    /// Returns the Joint with the given local axes.
+    pub fn with_axes(mut self, local_axes: [DVec3; 2]) -> Self {
+        self.local_axes = local_axes;
+        self
+    }
+
+    /// Returns the Joint with the given local reference directions, which are made perpendicular
+    /// to the local axes.
+    pub fn with_references(mut self, local_references: [DVec3; 2]) -> Self {
+        self.local_references = Some(local_references);
+        self
+    }
+
+    /// Returns the Joint limited to the given range of angles (for a Hinge) or displacements (for
+    /// a Slider). Has no effect on other kinds of Joint.
+    pub fn with_limits(mut self, min: f64, max: f64) -> Self {
+        match &mut self.kind {
+            JointKind::Hinge { limits, .. } | JointKind::Slider { limits } => {
+                *limits = Some((min, max));
+            },
+            _ => {},
+        }
+        self
+    }
+
+    /// Returns the Joint driven by a motor with the given target speed and maximum torque. Has no
+    /// effect on joints other than a Hinge.
+    pub fn with_motor(mut self, target_speed: f64, max_torque: f64) -> Self {
+        if let JointKind::Hinge { motor, .. } = &mut self.kind {
+            *motor = Some(JointMotor { target_speed, max_torque });
+        }
+        self
+    }
+
+    /// Returns the Joint with contacts between the connected bodies resolved as normal, or not.
+    pub fn with_collide_connected(mut self, collide_connected: bool) -> Self {
+        self.collide_connected = collide_connected;
+        self
+    }
+
+    /// Returns the angle of a Hinge, in radians, as of the latest step. The angle is not wrapped,
+    /// so it keeps counting through whole turns, and limits beyond a half turn either way apply.
+    pub fn angle(&self) -> f64 {
+        self.angle
+    }
+
+    pub(crate) fn set_angle(&mut self, angle: f64) {
+        self.angle = angle;
+    }
+
+    // --- Helper methods
+
+    fn new(
+        entities: [Entity; 2],
+        local_anchors: [DVec3; 2],
+        local_axes: [DVec3; 2],
+        kind: JointKind,
+    ) -> Self {
+        Self {
+            entities,
+            local_anchors,
+            local_axes,
+            local_references: None,
+            kind,
+            collide_connected: false,
+            angle: 0.0,
+        }
+    }
+}
@@ -1,28 +1,71 @@
 mod angular_velocity;
 mod boundary_collider;
+mod ccd;
 mod collider;
+mod collision_groups;
 mod contact;
+mod external_force;
+mod external_impulse;
 mod force;
 mod force_and_torque_generators;
+mod gravity_scale;
 mod inertia;
+mod joint;
 mod mass;
 mod phys_transform;
+mod physics_material;
+mod previous_phys_transform;
+mod rigid_body_type;
+mod sensor;
+mod sleeping;
 mod torque;
 mod velocity;
 
 pub use angular_velocity::AngularVelocity;
 pub use boundary_collider::BoundaryCollider;
+pub use ccd::Ccd;
 pub use collider::Collider;
+pub use collision_groups::CollisionGroups;
 pub use contact::Contact;
+pub use external_force::ExternalForce;
+pub use external_impulse::ExternalImpulse;
 pub use force::Force;
 pub use force_and_torque_generators::{
     Drag,
+    FluidVolume,
+    ForceGenerator,
+    ForceGeneratorBody,
     Gravity,
+    GravityZone,
+    GravityZoneMode,
+    PointAttractor,
     Rotator,
+    Spring,
+    SpringAnchor,
     Thrust,
 };
+pub use gravity_scale::GravityScale;
 pub use inertia::InertiaTensor;
+pub use joint::{
+    Joint,
+    JointKind,
+    JointMotor,
+};
 pub use mass::Mass;
 pub use phys_transform::PhysTransform;
+pub use physics_material::{
+    CombineRule,
+    PhysicsMaterial,
+};
+pub use previous_phys_transform::PreviousPhysTransform;
+pub use rigid_body_type::{
+    KinematicTarget,
+    RigidBodyType,
+};
+pub use sensor::Sensor;
+pub use sleeping::{
+    SleepTimer,
+    Sleeping,
+};
 pub use torque::Torque;
 pub use velocity::Velocity;
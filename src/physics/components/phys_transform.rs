@@ -4,7 +4,7 @@ use bevy::{
 
 /// Duplication of the built-in Bevy Transform component with a higher float precision (64 bit),
 /// for use internally within the physics engine's calculations.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PhysTransform {
     pub rotation: DQuat,
     pub translation: DVec3,
@@ -92,6 +92,15 @@ impl PhysTransform {
         self.matrix.transform_vector3(direction)
     }
 
+    /// Returns the transform formed by applying the given transform, relative to this one, e.g. to
+    /// find the global transform of a shape placed in the local body space of an entity.
+    pub fn mul_transform(&self, transform: &PhysTransform) -> PhysTransform {
+        Self::from_rotation_translation(
+            self.rotation * transform.rotation,
+            self.get_point_in_global_space(transform.translation),
+        )
+    }
+
     /// Updates the cached transform matrix based on the current rotation and translation.
     pub fn update(&mut self) {
         self.matrix = DMat4::from_rotation_translation(self.rotation, self.translation);
@@ -0,0 +1,127 @@
+use crate::constants;
+
+/// Describes how the material properties of two bodies in contact are combined into a single
+/// value for the contact.
+///
+/// When the two bodies specify different rules, the rule that appears latest in this list takes
+/// precedence.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CombineRule {
+    #[default]
+    Average,
+    Min,
+    Multiply,
+    Max,
+}
+
+impl CombineRule {
+    /// Combines the two given values according to the rule.
+    pub fn combine(&self, a: f64, b: f64) -> f64 {
+        match self {
+            Self::Average => (a + b) * 0.5,
+            Self::Min => a.min(b),
+            Self::Multiply => a * b,
+            Self::Max => a.max(b),
+        }
+    }
+}
+
+/// A component that describes the surface properties of a body used in collision response.
+///
+/// Bodies without a PhysicsMaterial use the default material held in the PhysicsConfig resource.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhysicsMaterial {
+    /// The coefficient of restitution. 0.0 is perfectly inelastic and 1.0 perfectly elastic.
+    pub restitution: f64,
+    /// The coefficient of friction that must be overcome for a resting contact to start sliding.
+    pub static_friction: f64,
+    /// The coefficient of friction applied to a sliding contact.
+    pub dynamic_friction: f64,
+    pub restitution_combine: CombineRule,
+    pub friction_combine: CombineRule,
+}
+
+impl PhysicsMaterial {
+    /// Creates a new PhysicsMaterial with the given coefficients of restitution, static friction and
+    /// dynamic friction, that combines with other materials by averaging.
+    pub fn new(restitution: f64, static_friction: f64, dynamic_friction: f64) -> Self {
+        Self {
+            restitution,
+            static_friction,
+            dynamic_friction,
+            restitution_combine: CombineRule::default(),
+            friction_combine: CombineRule::default(),
+        }
+    }
+
+    /// Returns the material with the given rule for combining its restitution with other
+    /// materials.
+    pub fn with_restitution_combine(mut self, rule: CombineRule) -> Self {
+        self.restitution_combine = rule;
+        self
+    }
+
+    /// Returns the material with the given rule for combining its friction coefficients with
+    /// other materials.
+    pub fn with_friction_combine(mut self, rule: CombineRule) -> Self {
+        self.friction_combine = rule;
+        self
+    }
+
+    /// Returns the material resulting from the contact of this material with another.
+    pub fn combine(&self, other: &Self) -> Self {
+        let restitution_combine = self.restitution_combine.max(other.restitution_combine);
+        let friction_combine = self.friction_combine.max(other.friction_combine);
+
+        Self {
+            restitution: restitution_combine.combine(self.restitution, other.restitution),
+            static_friction: friction_combine.combine(self.static_friction, other.static_friction),
+            dynamic_friction: friction_combine.combine(self.dynamic_friction,
+                                                       other.dynamic_friction),
+            restitution_combine,
+            friction_combine,
+        }
+    }
+}
+
+impl Default for PhysicsMaterial {
+    fn default() -> Self {
+        Self::new(
+            constants::RESTITUTION_COEFF,
+            constants::STATIC_FRICTION_COEFF,
+            constants::DYNAMIC_FRICTION_COEFF,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_combine() {
+        let a = PhysicsMaterial::new(0.2, 0.8, 0.4);
+        let b = PhysicsMaterial::new(0.6, 0.4, 0.2);
+
+        // both averaging.
+        let c = a.combine(&b);
+        assert!((c.restitution - 0.4).abs() < 0.000001);
+        assert!((c.static_friction - 0.6).abs() < 0.000001);
+        assert!((c.dynamic_friction - 0.3).abs() < 0.000001);
+
+        // the rule with the highest precedence is used, regardless of order.
+        let b = b.with_restitution_combine(CombineRule::Max)
+            .with_friction_combine(CombineRule::Min);
+        for c in [a.combine(&b), b.combine(&a)].iter() {
+            assert_eq!(CombineRule::Max, c.restitution_combine);
+            assert_eq!(CombineRule::Min, c.friction_combine);
+            assert!((c.restitution - 0.6).abs() < 0.000001);
+            assert!((c.static_friction - 0.4).abs() < 0.000001);
+            assert!((c.dynamic_friction - 0.2).abs() < 0.000001);
+        }
+
+        let a = a.with_friction_combine(CombineRule::Multiply);
+        let c = a.combine(&b);
+        assert!((c.static_friction - 0.32).abs() < 0.000001);
+    }
+}
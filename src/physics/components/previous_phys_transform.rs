@@ -0,0 +1,38 @@
+use bevy::math::{DQuat, DVec3};
+
+use crate::physics::components::PhysTransform;
+
+/// A component that records the rotation and translation of a body at the start of the most
+/// recent physics step. Used to interpolate the rendered Transform between physics steps.
+///
+/// It is added automatically to any Entity with a PhysTransform.
+#[derive(Debug, Clone, Copy)]
+pub struct PreviousPhysTransform {
+    pub rotation: DQuat,
+    pub translation: DVec3,
+}
+
+impl PreviousPhysTransform {
+    /// Creates a new component from the current state of the given PhysTransform.
+    pub fn new(transform: &PhysTransform) -> Self {
+        Self {
+            rotation: transform.rotation(),
+            translation: transform.translation(),
+        }
+    }
+
+    /// Records the current state of the given PhysTransform.
+    pub fn record(&mut self, transform: &PhysTransform) {
+        self.rotation = transform.rotation();
+        self.translation = transform.translation();
+    }
+
+    /// Returns the rotation and translation interpolated between this previous state and the
+    /// given current PhysTransform. An alpha of 0.0 gives the previous state and 1.0 the current.
+    pub fn interpolate(&self, transform: &PhysTransform, alpha: f64) -> (DQuat, DVec3) {
+        (
+            self.rotation.slerp(transform.rotation(), alpha),
+            self.translation.lerp(transform.translation(), alpha),
+        )
+    }
+}
@@ -0,0 +1,74 @@
+use crate::physics::components::{
+    Mass,
+    PhysTransform,
+};
+
+/// A component that sets how a body is moved by the simulation.
+///
+/// Bodies without a RigidBodyType are Dynamic, unless they have an infinite Mass, in which case
+/// they are Static.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RigidBodyType {
+    /// Moved by forces, torques and contacts, according to its Mass and InertiaTensor.
+    #[default]
+    Dynamic,
+    /// Never moves.
+    Static,
+    /// Moved only by its Velocity and AngularVelocity, or towards its KinematicTarget, and never
+    /// by forces or contacts. A Kinematic body pushes Dynamic bodies as though it had an infinite
+    /// Mass, and carries them with it through friction, e.g. as a moving platform.
+    Kinematic,
+}
+
+impl RigidBodyType {
+    /// Returns the effective type of a body with the given RigidBodyType (if any) and Mass. A
+    /// Dynamic body with an infinite Mass cannot move, so is Static.
+    pub fn of(body_type: Option<&RigidBodyType>, mass: &Mass) -> Self {
+        match body_type {
+            Some(RigidBodyType::Kinematic) => RigidBodyType::Kinematic,
+            Some(RigidBodyType::Static) => RigidBodyType::Static,
+            _ if mass.is_infinite() => RigidBodyType::Static,
+            _ => RigidBodyType::Dynamic,
+        }
+    }
+
+    /// Returns true if the body is Dynamic.
+    pub fn is_dynamic(&self) -> bool {
+        *self == RigidBodyType::Dynamic
+    }
+
+    /// Returns true if the body is Kinematic.
+    pub fn is_kinematic(&self) -> bool {
+        *self == RigidBodyType::Kinematic
+    }
+
+    /// Returns true if the body is Static.
+    pub fn is_static(&self) -> bool {
+        *self == RigidBodyType::Static
+    }
+}
+
+/// A component that moves a Kinematic body to the given transform over the next physics step.
+///
+/// The body's Velocity and AngularVelocity are set to those needed to reach the target, so that
+/// bodies in contact with it respond to its motion. Once the target is reached the body remains
+/// there, at rest, until the target is changed or removed.
+#[derive(Debug, Clone)]
+pub struct KinematicTarget(pub PhysTransform);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_of() {
+        let mass = Mass::new(1.0);
+        let infinite = Mass::from_inverse(0.0);
+
+        assert_eq!(RigidBodyType::Dynamic, RigidBodyType::of(None, &mass));
+        assert_eq!(RigidBodyType::Static, RigidBodyType::of(None, &infinite));
+        assert_eq!(RigidBodyType::Static, RigidBodyType::of(Some(&RigidBodyType::Dynamic), &infinite));
+        assert_eq!(RigidBodyType::Static, RigidBodyType::of(Some(&RigidBodyType::Static), &mass));
+        assert_eq!(RigidBodyType::Kinematic, RigidBodyType::of(Some(&RigidBodyType::Kinematic), &infinite));
+    }
+}
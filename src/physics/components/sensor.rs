@@ -0,0 +1,7 @@
+/// A marker component that turns an entity with a Collider into a sensor (or trigger) volume.
+///
+/// Sensors detect overlaps with other bodies, reported through the SensorEntered and SensorExited
+/// events, but take no part in collision response. Neither the sensor nor the bodies it overlaps
+/// are moved by the overlap. A sensor does not require a Mass, in which case it remains in place.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Sensor;
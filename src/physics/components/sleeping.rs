@@ -0,0 +1,33 @@
+/// A marker component present on bodies that are asleep.
+///
+/// Bodies that remain below the sleep thresholds in the PhysicsConfig for long enough, along with
+/// every body in contact with them (their island), are put to sleep. Sleeping bodies are skipped by
+/// force accumulation, integration and contact generation until they are woken, either by an
+/// impact, by the loss of a contact, or by a change to their Velocity, AngularVelocity, Force,
+/// Torque or Thrust.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Sleeping;
+
+/// A component that records how long a body has been at rest. It is added automatically to any
+/// body with a finite Mass.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SleepTimer {
+    seconds: f64,
+}
+
+impl SleepTimer {
+    /// Returns the time, in seconds, that the body has been at rest.
+    pub fn seconds(&self) -> f64 {
+        self.seconds
+    }
+
+    /// Adds the given time, in seconds, to the timer.
+    pub fn tick(&mut self, seconds: f64) {
+        self.seconds += seconds;
+    }
+
+    /// Resets the timer to zero.
+    pub fn reset(&mut self) {
+        self.seconds = 0.0;
+    }
+}
@@ -17,15 +17,25 @@ use crate::{
         Thrust,
         Torque,
         PhysTransform,
+        Sensor,
         Velocity,
     },
     physics::shapes::{
+        Capsule,
+        CollisionPrimative,
+        Compound,
+        ConvexHull,
         Cuboid,
+        Cylinder,
+        HeightField,
         Sphere,
+        TriMesh,
     },
 };
 
-/// A component bundle that adds rigid-body physics to an entity. Supports cuboids and spheres.
+/// A component bundle that adds rigid-body physics to an entity. Supports cuboids, spheres,
+/// capsules, cylinders, convex hulls and compounds of these, as well as fixed triangle meshes and
+/// height fields.
 #[derive(Bundle)]
 pub struct PhysicsColliderBundle {
     pub angular_velocity: AngularVelocity,
@@ -64,6 +74,89 @@ impl PhysicsColliderBundle {
         }
     }
 
+    /// Creates a new PhysicsColliderBundle for a capsule body, aligned with its local y-axis, with
+    /// the given mass, transform, radius and half-length of its central segment.
+    pub fn capsule(mass: f64, radius: f64, half_height: f64, transform: PhysTransform) -> Self {
+        Self {
+            collider: Collider::new(Capsule::new(radius, half_height)),
+            inertia_tensor: InertiaTensor::capsule(mass, radius, half_height),
+            mass: Mass::new(mass),
+            transform,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a new PhysicsColliderBundle for a cylindrical body, aligned with its local y-axis,
+    /// with the given mass, transform, radius and half-height.
+    pub fn cylinder(mass: f64, radius: f64, half_height: f64, transform: PhysTransform) -> Self {
+        Self {
+            collider: Collider::new(Cylinder::new(radius, half_height)),
+            inertia_tensor: InertiaTensor::cylinder(mass, radius, half_height),
+            mass: Mass::new(mass),
+            transform,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a new PhysicsColliderBundle for a body with the given mass and transform, whose shape
+    /// is the convex hull of the given points in local body coords. The inertia tensor is
+    /// approximated by that of the hull's local bounding box.
+    pub fn convex_hull(mass: f64, points: Vec<DVec3>, transform: PhysTransform) -> Self {
+        let extents = calc_points_extents(&points);
+
+        Self {
+            collider: Collider::new(ConvexHull::new(points)),
+            inertia_tensor: InertiaTensor::cuboid(mass, extents.x, extents.y, extents.z),
+            mass: Mass::new(mass),
+            transform,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a new PhysicsColliderBundle for a body made up of the children of the given
+    /// Compound, with the given total mass and transform. The mass is shared between the children
+    /// by volume, i.e. the body is of constant density, and their inertia tensors are combined.
+    ///
+    /// The children are moved so that the centre of mass of the body lies at its origin, and the
+    /// body is moved to compensate so that the children stay in place.
+    pub fn compound(mass: f64, mut compound: Compound, transform: PhysTransform) -> Self {
+        let volumes: Vec<f64> = compound.children().iter()
+            .map(|(_, shape)| calc_volume(shape.as_ref()))
+            .collect();
+        let total_volume: f64 = volumes.iter().sum();
+        let masses: Vec<f64> = volumes.iter()
+            .map(|v| if total_volume > 0.0 { mass * v / total_volume } else { 0.0 })
+            .collect();
+
+        let centre_of_mass = compound.children().iter()
+            .zip(masses.iter())
+            .fold(DVec3::ZERO, |sum, ((t, _), m)| sum + t.translation() * *m) / mass;
+        compound.offset_children(-centre_of_mass);
+
+        let tensors: Vec<InertiaTensor> = compound.children().iter()
+            .zip(masses.iter())
+            .map(|((_, shape), m)| calc_inertia_tensor(*m, shape.as_ref()))
+            .collect();
+        let parts: Vec<(f64, &PhysTransform, &InertiaTensor)> = compound.children().iter()
+            .zip(masses.iter().zip(tensors.iter()))
+            .map(|((t, _), (m, tensor))| (*m, t, tensor))
+            .collect();
+        let inertia_tensor = InertiaTensor::compound(&parts);
+
+        let transform = PhysTransform::from_rotation_translation(
+            transform.rotation(),
+            transform.get_point_in_global_space(centre_of_mass),
+        );
+
+        Self {
+            collider: Collider::new(compound),
+            inertia_tensor,
+            mass: Mass::new(mass),
+            transform,
+            ..Default::default()
+        }
+    }
+
     /// Creates a new PhysicsColliderBundle for a fixed (infinite mass) spherical body with transform and extents.
     pub fn fixed_sphere(radius: f64, transform: PhysTransform) -> Self {
         Self {
@@ -74,6 +167,30 @@ impl PhysicsColliderBundle {
             ..Default::default()
         }
     }
+
+    /// Creates a new PhysicsColliderBundle for a fixed (infinite mass) triangle mesh body, such as
+    /// level geometry, with the given transform.
+    pub fn fixed_tri_mesh(mesh: TriMesh, transform: PhysTransform) -> Self {
+        Self {
+            collider: Collider::new(mesh),
+            inertia_tensor: InertiaTensor::fixed_sphere(),
+            mass: Mass::from_inverse(0.0),
+            transform,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a new PhysicsColliderBundle for a fixed (infinite mass) height field body, such as
+    /// terrain, with the given transform.
+    pub fn fixed_height_field(field: HeightField, transform: PhysTransform) -> Self {
+        Self {
+            collider: Collider::new(field),
+            inertia_tensor: InertiaTensor::fixed_sphere(),
+            mass: Mass::from_inverse(0.0),
+            transform,
+            ..Default::default()
+        }
+    }
 }
 
 impl Default for PhysicsColliderBundle {
@@ -94,6 +211,35 @@ impl Default for PhysicsColliderBundle {
     }
 }
 
+/// A component bundle that adds a Sensor volume to an entity, which reports overlapping bodies
+/// without a physical response. Supports cuboids and spheres.
+#[derive(Bundle)]
+pub struct PhysicsSensorBundle {
+    pub collider: Collider,
+    pub sensor: Sensor,
+    pub transform: PhysTransform,
+}
+
+impl PhysicsSensorBundle {
+    /// Creates a new PhysicsSensorBundle for a cuboid volume with the given transform and extents.
+    pub fn cuboid(extents: DVec3, transform: PhysTransform) -> Self {
+        Self {
+            collider: Collider::new(Cuboid::new(extents)),
+            sensor: Sensor,
+            transform,
+        }
+    }
+
+    /// Creates a new PhysicsSensorBundle for a spherical volume with the given transform and radius.
+    pub fn sphere(radius: f64, transform: PhysTransform) -> Self {
+        Self {
+            collider: Collider::new(Sphere::new(radius)),
+            sensor: Sensor,
+            transform,
+        }
+    }
+}
+
 /// A component bundle that adds rigid-body physics to an entity. Supports boundary planes as
 /// half-spaces.
 #[derive(Bundle)]
@@ -124,3 +270,53 @@ impl Default for PhysicsBoundaryBundle {
         }
     }
 }
+
+// --- Helper methods
+
+/// Returns the volume of the given primative. Convex hulls are approximated by their bounding box,
+/// and shapes with no volume, such as triangle meshes, return zero.
+fn calc_volume(primative: &dyn CollisionPrimative) -> f64 {
+    if let Some(sphere) = primative.downcast_ref::<Sphere>() {
+        4.0 / 3.0 * std::f64::consts::PI * sphere.radius().powi(3)
+    } else if let Some(cuboid) = primative.downcast_ref::<Cuboid>() {
+        let extents = cuboid.extents();
+        8.0 * extents.x * extents.y * extents.z
+    } else if let Some(capsule) = primative.downcast_ref::<Capsule>() {
+        std::f64::consts::PI * capsule.radius().powi(2)
+            * (2.0 * capsule.half_height() + 4.0 / 3.0 * capsule.radius())
+    } else if let Some(cylinder) = primative.downcast_ref::<Cylinder>() {
+        std::f64::consts::PI * cylinder.radius().powi(2) * 2.0 * cylinder.half_height()
+    } else if let Some(hull) = primative.downcast_ref::<ConvexHull>() {
+        let extents = calc_points_extents(hull.points());
+        8.0 * extents.x * extents.y * extents.z
+    } else if let Some(compound) = primative.downcast_ref::<Compound>() {
+        compound.children().iter().map(|(_, shape)| calc_volume(shape.as_ref())).sum()
+    } else {
+        0.0
+    }
+}
+
+/// Returns the inertia tensor of the given primative with the given mass, about its own origin.
+/// Shapes with no specific tensor are treated as a solid sphere filling their bounding sphere.
+fn calc_inertia_tensor(mass: f64, primative: &dyn CollisionPrimative) -> InertiaTensor {
+    if let Some(sphere) = primative.downcast_ref::<Sphere>() {
+        InertiaTensor::sphere(mass, sphere.radius())
+    } else if let Some(cuboid) = primative.downcast_ref::<Cuboid>() {
+        let extents = cuboid.extents();
+        InertiaTensor::cuboid(mass, extents.x, extents.y, extents.z)
+    } else if let Some(capsule) = primative.downcast_ref::<Capsule>() {
+        InertiaTensor::capsule(mass, capsule.radius(), capsule.half_height())
+    } else if let Some(cylinder) = primative.downcast_ref::<Cylinder>() {
+        InertiaTensor::cylinder(mass, cylinder.radius(), cylinder.half_height())
+    } else if let Some(hull) = primative.downcast_ref::<ConvexHull>() {
+        let extents = calc_points_extents(hull.points());
+        InertiaTensor::cuboid(mass, extents.x, extents.y, extents.z)
+    } else {
+        InertiaTensor::sphere(mass, primative.bounding_sphere().radius())
+    }
+}
+
+/// Returns the extents of the local bounding box of the given points, centred on the origin.
+fn calc_points_extents(points: &[DVec3]) -> DVec3 {
+    points.iter().fold(DVec3::ZERO, |extents, p| extents.max(p.abs()))
+}
@@ -0,0 +1,13 @@
+use bevy::{
+    prelude::Entity,
+    math::DVec3,
+};
+
+/// An event sent when a body's bounding sphere leaves the play area covered by the broad-phase
+/// OctTree. It is sent once, when the body first leaves, and again only if it returns and then
+/// leaves once more.
+#[derive(Debug, Clone, Copy)]
+pub struct BodyEscaped {
+    pub entity: Entity,
+    pub translation: DVec3,
+}
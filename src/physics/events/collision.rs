@@ -0,0 +1,42 @@
+use bevy::{
+    prelude::Entity,
+    math::DVec3,
+};
+
+/// An event sent in the first physics step in which two Entitys (or an Entity and a boundary) are
+/// in contact.
+///
+/// The normal points from the first Entity towards the second. The impulse is the total magnitude
+/// of the impulses applied at the contact points.
+#[derive(Debug, Clone)]
+pub struct CollisionStarted {
+    pub entities: (Entity, Entity),
+    pub normal: DVec3,
+    pub points: Vec<DVec3>,
+    pub impulse: f64,
+}
+
+/// An event sent in each physics step, after the first, in which two Entitys (or an Entity and a
+/// boundary) remain in contact.
+///
+/// The normal points from the first Entity towards the second. The impulse is the total magnitude
+/// of the impulses applied at the contact points.
+#[derive(Debug, Clone)]
+pub struct CollisionPersisted {
+    pub entities: (Entity, Entity),
+    pub normal: DVec3,
+    pub points: Vec<DVec3>,
+    pub impulse: f64,
+}
+
+/// An event sent in the first physics step in which two Entitys (or an Entity and a boundary) are
+/// no longer in contact.
+///
+/// The normal, points and impulse are those of the last step in which the Entitys were in contact.
+#[derive(Debug, Clone)]
+pub struct CollisionEnded {
+    pub entities: (Entity, Entity),
+    pub normal: DVec3,
+    pub points: Vec<DVec3>,
+    pub impulse: f64,
+}
@@ -0,0 +1,14 @@
+mod body_escaped;
+mod collision;
+mod sensor;
+
+pub use body_escaped::BodyEscaped;
+pub use collision::{
+    CollisionEnded,
+    CollisionPersisted,
+    CollisionStarted,
+};
+pub use sensor::{
+    SensorEntered,
+    SensorExited,
+};
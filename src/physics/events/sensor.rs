@@ -0,0 +1,16 @@
+use bevy::prelude::Entity;
+
+/// An event sent in the first physics step in which an entity overlaps a Sensor.
+#[derive(Debug, Clone, Copy)]
+pub struct SensorEntered {
+    pub sensor: Entity,
+    pub entity: Entity,
+}
+
+/// An event sent in the first physics step in which an entity no longer overlaps a Sensor that it
+/// previously overlapped.
+#[derive(Debug, Clone, Copy)]
+pub struct SensorExited {
+    pub sensor: Entity,
+    pub entity: Entity,
+}
@@ -1,11 +1,23 @@
 pub mod components;
 mod entity;
+pub mod events;
 mod oct_tree;
+pub mod query;
+pub mod resources;
 pub mod shapes;
 mod systems;
 
 // Re-exports
-pub use entity::PhysicsColliderBundle;
+pub use entity::{
+    PhysicsColliderBundle,
+    PhysicsSensorBundle,
+};
+pub use systems::force_and_torque::{
+    add_force,
+    add_force_at_point,
+    add_force_at_body_point,
+    add_body_force_at_body_point,
+};
 
 /// 'use physics::prelude::*;' to import common components, shapes, bundles and plugins.
 pub mod prelude {
@@ -13,40 +25,126 @@ pub mod prelude {
     pub use super::components::{
         AngularVelocity,
         BoundaryCollider,
+        Ccd,
         Collider,
+        CollisionGroups,
         Drag,
+        ExternalForce,
+        ExternalImpulse,
+        FluidVolume,
+        ForceGenerator,
         Gravity,
+        GravityScale,
+        GravityZone,
         InertiaTensor,
+        Joint,
+        JointKind,
+        KinematicTarget,
         Mass,
         PhysTransform,
+        PhysicsMaterial,
+        PointAttractor,
+        RigidBodyType,
         Rotator,
+        Sensor,
+        Sleeping,
+        Spring,
+        SpringAnchor,
         Thrust,
         Velocity,
     };
     pub use super::entity::{
         PhysicsBoundaryBundle,
         PhysicsColliderBundle,
+        PhysicsSensorBundle,
+    };
+    pub use super::events::{
+        BodyEscaped,
+        CollisionEnded,
+        CollisionPersisted,
+        CollisionStarted,
+        SensorEntered,
+        SensorExited,
+    };
+    pub use super::query::{
+        PhysicsQuery,
+        PointProjection,
+        QueryFilter,
+        RayHit,
+        ShapeHit,
+    };
+    pub use super::resources::{
+        CollisionFilter,
+        ContactManifolds,
+        PhysicsConfig,
+        PhysicsTime,
+        SensorOverlaps,
     };
     pub use super::shapes::{
+        Capsule,
         CollisionPrimative,
+        Compound,
+        ConvexHull,
         Cuboid,
+        Cylinder,
+        HeightField,
         Plane,
+        RayCast,
         Sphere,
+        SupportMap,
+        TriMesh,
+    };
+    pub use super::{
+        AddForceGenerator,
+        PhysicsPlugin,
     };
-    pub use super::PhysicsPlugin;
 }
 
 use bevy::prelude::*;
 
+use components::ForceGenerator;
+use events::{
+    BodyEscaped,
+    CollisionEnded,
+    CollisionPersisted,
+    CollisionStarted,
+    SensorEntered,
+    SensorExited,
+};
+use resources::{
+    CollisionFilter,
+    PhysicsConfig,
+    PhysicsTime,
+};
 use systems::{
     cache_update,
+    ccd,
     collision_detection,
+    collision_events,
     collision_response,
     force_and_torque,
     integrator,
+    sleep,
+    timestep,
     transform_sync,
 };
 
+/// Labels for the stages added by the PhysicsPlugin.
+///
+/// The Primary and Secondary stages are nested within the Physics stage, which runs them zero or
+/// more times per frame at a fixed timestep. Systems can be added to them through
+/// 'AppBuilder::stage', e.g. to order them after a BpmPhysicsSystems label.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, StageLabel)]
+pub enum BpmPhysicsStages {
+    Physics,
+    /// Up to and including contact generation, but nothing that depends on those contacts.
+    Primary,
+    /// Systems that utilise contacts generated during the current step.
+    Secondary,
+    /// Runs once per frame to present the (interpolated) results of the physics steps.
+    Sync,
+}
+
 /// System label covering all physics systems.
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
 pub struct BpmPhysics;
@@ -54,78 +152,162 @@ pub struct BpmPhysics;
 /// System labels covering physics sub-systems.
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
 pub enum BpmPhysicsSystems {
+    TransformRecord,
     ForceAndTorque,
     Integrator,
     CollisionDetection,
     CollisionResponse,
+    /// Sends the CollisionStarted, CollisionPersisted, CollisionEnded, SensorEntered and
+    /// SensorExited events for the current step, in the Secondary stage.
+    CollisionEvents,
+    /// Puts islands of resting bodies to sleep and wakes them, in the Secondary stage.
+    Sleep,
     TransformSync,
     CacheUpdatePrimary,
     CacheUpdateSecondary,
+    /// Clamps the motion of bodies with a Ccd component to their first time of impact, in the
+    /// Primary stage before collision detection.
+    Ccd,
 }
 
 /// A Bevy plugin that adds systems to support rigid-body physics, including; force/torque
 /// accumulation, integration, collision detection and collision resolution.
+///
+/// The simulation is stepped at a fixed rate, independent of the frame rate, as configured by the
+/// PhysicsTime resource. The simulation parameters are held in the PhysicsConfig resource. Insert
+/// either resource before adding the plugin to override the defaults.
 pub struct PhysicsPlugin;
 
 impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        // Up to and including contact generation, but nothing that depends on those contacts.
-        static PRIMARY: &str = "Primary";
-        // Systems that utilise contact entities generated during the current frame.
-        static SECONDARY: &str = "Secondary";
-
         app
-            .add_stage_after(CoreStage::Update, PRIMARY, SystemStage::parallel())
-            .add_stage_after(PRIMARY, SECONDARY, SystemStage::parallel())
-            .add_startup_system(
-                collision_detection::initialize.system()
-            )
-            .add_system_set_to_stage(
-                PRIMARY,
-                force_and_torque::get_system_set()
-                    .label(BpmPhysicsSystems::ForceAndTorque)
-                    .label(BpmPhysics)
-            )
-            .add_system_set_to_stage(
-                PRIMARY,
-                integrator::get_system_set()
-                    .label(BpmPhysicsSystems::Integrator)
-                    .label(BpmPhysics)
-                    .after(BpmPhysicsSystems::ForceAndTorque)
+            .init_resource::<PhysicsConfig>()
+            .init_resource::<PhysicsTime>()
+            .init_resource::<CollisionFilter>()
+            .add_event::<BodyEscaped>()
+            .add_event::<CollisionStarted>()
+            .add_event::<CollisionPersisted>()
+            .add_event::<CollisionEnded>()
+            .add_event::<SensorEntered>()
+            .add_event::<SensorExited>()
+            .add_stage_after(
+                CoreStage::Update,
+                BpmPhysicsStages::Physics,
+                Schedule::default()
+                    .with_run_criteria(timestep::fixed_timestep.system())
+                    .with_stage(BpmPhysicsStages::Primary, SystemStage::parallel())
+                    .with_stage_after(
+                        BpmPhysicsStages::Primary,
+                        BpmPhysicsStages::Secondary,
+                        SystemStage::parallel(),
+                    )
             )
-            .add_system_set_to_stage(
-                PRIMARY,
-                cache_update::get_system_set()
-                    .label(BpmPhysicsSystems::CacheUpdatePrimary)
-                    .label(BpmPhysics)
-                    .after(BpmPhysicsSystems::Integrator)
-            )
-            .add_system_set_to_stage(
-                PRIMARY,
-                collision_detection::get_system_set()
-                    .label(BpmPhysicsSystems::CollisionDetection)
-                    .label(BpmPhysics)
-                    .after(BpmPhysicsSystems::CacheUpdatePrimary)
+            .add_stage_after(
+                BpmPhysicsStages::Physics,
+                BpmPhysicsStages::Sync,
+                SystemStage::parallel(),
             )
-            .add_system_set_to_stage(
-                SECONDARY,
-                collision_response::get_system_set()
-                    .label(BpmPhysicsSystems::CollisionResponse)
-                    .label(BpmPhysics)
-            )
-            .add_system_set_to_stage(
-                SECONDARY,
-                cache_update::get_system_set()
-                    .label(BpmPhysicsSystems::CacheUpdateSecondary)
-                    .label(BpmPhysics)
-                    .after(BpmPhysicsSystems::CollisionResponse)
+            .add_startup_system(
+                collision_detection::initialize.system()
             )
+            .stage(BpmPhysicsStages::Physics, |schedule: &mut Schedule| {
+                schedule
+                    .add_system_set_to_stage(
+                        BpmPhysicsStages::Primary,
+                        transform_sync::get_record_system_set()
+                            .label(BpmPhysicsSystems::TransformRecord)
+                            .label(BpmPhysics)
+                    )
+                    .add_system_set_to_stage(
+                        BpmPhysicsStages::Primary,
+                        force_and_torque::get_system_set()
+                            .label(BpmPhysicsSystems::ForceAndTorque)
+                            .label(BpmPhysics)
+                            .after(BpmPhysicsSystems::TransformRecord)
+                    )
+                    .add_system_set_to_stage(
+                        BpmPhysicsStages::Primary,
+                        integrator::get_system_set()
+                            .label(BpmPhysicsSystems::Integrator)
+                            .label(BpmPhysics)
+                            .after(BpmPhysicsSystems::ForceAndTorque)
+                    )
+                    .add_system_set_to_stage(
+                        BpmPhysicsStages::Primary,
+                        cache_update::get_system_set()
+                            .label(BpmPhysicsSystems::CacheUpdatePrimary)
+                            .label(BpmPhysics)
+                            .after(BpmPhysicsSystems::Integrator)
+                    )
+                    .add_system_set_to_stage(
+                        BpmPhysicsStages::Primary,
+                        ccd::get_system_set()
+                            .label(BpmPhysicsSystems::Ccd)
+                            .label(BpmPhysics)
+                            .after(BpmPhysicsSystems::CacheUpdatePrimary)
+                    )
+                    .add_system_set_to_stage(
+                        BpmPhysicsStages::Primary,
+                        collision_detection::get_system_set()
+                            .label(BpmPhysicsSystems::CollisionDetection)
+                            .label(BpmPhysics)
+                            .after(BpmPhysicsSystems::Ccd)
+                    )
+                    .add_system_set_to_stage(
+                        BpmPhysicsStages::Secondary,
+                        collision_response::get_system_set()
+                            .label(BpmPhysicsSystems::CollisionResponse)
+                            .label(BpmPhysics)
+                    )
+                    .add_system_set_to_stage(
+                        BpmPhysicsStages::Secondary,
+                        collision_events::get_system_set()
+                            .label(BpmPhysicsSystems::CollisionEvents)
+                            .label(BpmPhysics)
+                            .after(BpmPhysicsSystems::CollisionResponse)
+                    )
+                    .add_system_set_to_stage(
+                        BpmPhysicsStages::Secondary,
+                        sleep::get_system_set()
+                            .label(BpmPhysicsSystems::Sleep)
+                            .label(BpmPhysics)
+                            .after(BpmPhysicsSystems::CollisionResponse)
+                    )
+                    .add_system_set_to_stage(
+                        BpmPhysicsStages::Secondary,
+                        cache_update::get_system_set()
+                            .label(BpmPhysicsSystems::CacheUpdateSecondary)
+                            .label(BpmPhysics)
+                            .after(BpmPhysicsSystems::CollisionResponse)
+                    )
+            })
             .add_system_set_to_stage(
-                SECONDARY,
+                BpmPhysicsStages::Sync,
                 transform_sync::get_system_set()
                     .label(BpmPhysicsSystems::TransformSync)
                     .label(BpmPhysics)
-                    .after(BpmPhysicsSystems::CollisionResponse)
             );
     }
 }
+
+/// Registers user-defined ForceGenerators with an app that has the PhysicsPlugin.
+pub trait AddForceGenerator {
+    /// Adds a system that updates the ForceGenerator of the given type on each body it is added
+    /// to, in the BpmPhysicsSystems::ForceAndTorque systems. Must be called after the
+    /// PhysicsPlugin has been added, and only once per type.
+    fn add_force_generator<T: ForceGenerator>(&mut self) -> &mut Self;
+}
+
+impl AddForceGenerator for AppBuilder {
+    fn add_force_generator<T: ForceGenerator>(&mut self) -> &mut Self {
+        self.stage(BpmPhysicsStages::Physics, |schedule: &mut Schedule| {
+            schedule.add_system_set_to_stage(
+                BpmPhysicsStages::Primary,
+                force_and_torque::get_generator_system_set::<T>()
+                    .label(BpmPhysicsSystems::ForceAndTorque)
+                    .label(BpmPhysics)
+                    .after(BpmPhysicsSystems::TransformRecord)
+            )
+        })
+    }
+}
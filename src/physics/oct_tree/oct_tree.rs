@@ -1,7 +1,7 @@
 use bevy::math::DVec3;
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     hash::Hash,
 };
 
@@ -16,6 +16,7 @@ use crate::{
         OctTreeNode,
     },
     physics::shapes::{
+        calc_ray_and_aabb,
         Aabb3D,
         Sphere,
     },
@@ -27,6 +28,8 @@ use crate::{
 pub struct OctTree<T: Copy + Hash + Eq> {
     arena: Vec<OctTreeNode<T>>,
     data_node_map: HashMap<T, OctIndex>,
+    // data entries whose shapes are not wholly within the bounds of the tree.
+    escaped: HashSet<T>,
     max_depth: i32,
     root: OctIndex,
 }
@@ -37,6 +40,7 @@ impl<T: Copy + Hash + Eq> OctTree<T> {
         Self {
             arena: vec![],
             data_node_map: HashMap::new(),
+            escaped: HashSet::new(),
             max_depth,
             root: 0,
         }
@@ -88,6 +92,11 @@ impl<T: Copy + Hash + Eq> OctTree<T> {
         }
     }
 
+    /// Returns the maximum depth of the tree.
+    pub fn max_depth(&self) -> i32 {
+        self.max_depth
+    }
+
     /// Returns an option; either Some() containing a reference to the root node, or None if there
     /// is no root node (i.e. the tree has not been initialised).
     pub fn get_root_node(&self) -> Option<&OctTreeNode<T>> {
@@ -102,14 +111,16 @@ impl<T: Copy + Hash + Eq> OctTree<T> {
 
     // INSERT, REMOVE & UPDATE
 
-    // TODO return error if shape outside the bounds of the tree?
     /// Inserts the given data into the tree according to its associated shape and position.
-    pub fn insert(&mut self, collider: &Collider, transform: &PhysTransform, data: T) {
+    ///
+    /// Returns false if the shape is not wholly within the bounds of the tree, in which case the
+    /// data is recorded as having escaped.
+    pub fn insert(&mut self, collider: &Collider, transform: &PhysTransform, data: T) -> bool {
         self.insert_sphere(
             collider.0.bounding_sphere(),
             transform.translation(),
             data
-        );
+        )
     }
 
     /// Removes the given data entry from the tree, if present.
@@ -122,15 +133,30 @@ impl<T: Copy + Hash + Eq> OctTree<T> {
         // remove from node and map.
         self.arena[*node_idx].data.remove(&data);
         self.data_node_map.remove(&data);
+        self.escaped.remove(&data);
     }
 
     // TODO could be optimised to make better use of the knowledge of where the data is stored in
     // the tree. Assuming objects aren't moving quickly, it should be moved to a nearby node.
     /// Updates the location of the data point in the tree based on its current associated
     /// geometric position and spherical shape.
-    pub fn update(&mut self, collider: &Collider, transform: &PhysTransform, data: T) {
+    ///
+    /// Returns false if the shape is not wholly within the bounds of the tree.
+    pub fn update(&mut self, collider: &Collider, transform: &PhysTransform, data: T) -> bool {
         self.remove(data);
-        self.insert(collider, transform, data);
+        self.insert(collider, transform, data)
+    }
+
+    /// Returns true if the given data entry is held in the tree but its shape is not wholly within
+    /// the bounds of the tree.
+    pub fn is_escaped(&self, data: T) -> bool {
+        self.escaped.contains(&data)
+    }
+
+    /// Returns an iterator over the data entries whose shapes are not wholly within the bounds of
+    /// the tree.
+    pub fn escaped(&self) -> impl Iterator<Item = &T> {
+        self.escaped.iter()
     }
 
     // -- helper functions
@@ -179,10 +205,14 @@ impl<T: Copy + Hash + Eq> OctTree<T> {
         Some(idx)
     }
 
-    /// Specific insert method for sphere primatives.
-    fn insert_sphere(&mut self, shape: &Sphere, shape_pos: DVec3, data: T) {
+    /// Specific insert method for sphere primatives. Returns false if the sphere is not wholly
+    /// within the bounds of the tree.
+    fn insert_sphere(&mut self, shape: &Sphere, shape_pos: DVec3, data: T) -> bool {
         let mut node_idx = self.root;  // start at root
 
+        let root = &self.arena[node_idx];
+        let enclosed = root.boundary.holds_sphere(*shape, shape_pos, root.centre);
+
         // Traverse down the branch, stopping if the shape will not fit within a child node, or a
         // leaf has been reached.
         while let Some(child_octant) = self.calc_child_octant_idx(node_idx, shape, shape_pos) {
@@ -202,10 +232,120 @@ impl<T: Copy + Hash + Eq> OctTree<T> {
 
         // Record the node where this data entry is stored so it can be updated easily.
         self.data_node_map.insert(data, node_idx);
+
+        if !enclosed {
+            self.escaped.insert(data);
+        }
+
+        enclosed
     }
 
     // QUERIES
 
+    /// Returns all data entries in the oct-tree that reside in nodes intersected by the ray with
+    /// the given origin and direction, up to the given maximum time of impact, along with any
+    /// escaped entries, which may lie anywhere.
+    pub fn query_by_ray(&self, origin: DVec3, direction: DVec3, max_toi: f64) -> Vec<T> {
+        self.query_by_swept_sphere(origin, direction, max_toi, 0.0)
+    }
+
+    /// Returns all data entries in the oct-tree that reside in nodes overlapped by the sphere with
+    /// the given centre and radius, along with any escaped entries.
+    pub fn query_by_sphere(&self, centre: DVec3, radius: f64) -> Vec<T> {
+        self.query_by_swept_sphere(centre, DVec3::ZERO, 0.0, radius)
+    }
+
+    /// Returns all data entries in the oct-tree that reside in nodes touched by a sphere with the
+    /// given radius as it is swept from the origin along the given direction, up to the given
+    /// maximum time of impact, along with any escaped entries.
+    pub fn query_by_swept_sphere(
+        &self,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+        radius: f64,
+    ) -> Vec<T> {
+        let mut result: Vec<T> = self.escaped.iter().copied().collect();
+        if self.arena.is_empty() { return result; }
+
+        let mut stack = vec![self.root];
+        while let Some(node_idx) = stack.pop() {
+            let node = &self.arena[node_idx];
+
+            // the sphere touches the node if its centre passes through the node's boundary
+            // expanded by the radius.
+            let min = node.boundary.min(node.centre) - DVec3::splat(radius);
+            let max = node.boundary.max(node.centre) + DVec3::splat(radius);
+
+            if calc_ray_and_aabb(origin, direction, min, max, max_toi).is_none() { continue; }
+
+            result.extend(node.data.iter().filter(|d| !self.escaped.contains(d)));
+            stack.extend(node.children.iter().flatten());
+        }
+
+        result
+    }
+
+    /// Returns up to k data entries nearest to the given point, nearest first, along with their
+    /// distances, where the given function measures the distance from the point to an entry or
+    /// returns None if the entry is to be ignored. Nodes further from the point than the k-th
+    /// nearest entry found so far are not searched.
+    pub fn query_nearest(
+        &self,
+        point: DVec3,
+        k: usize,
+        mut distance: impl FnMut(T) -> Option<f64>,
+    ) -> Vec<(T, f64)> {
+        let mut result: Vec<(T, f64)> = vec![];
+        if k == 0 { return result; }
+
+        let mut consider = |data: T, result: &mut Vec<(T, f64)>| {
+            if let Some(d) = distance(data) {
+                let i = result.iter().position(|(_, other)| d < *other).unwrap_or(result.len());
+                if i < k {
+                    result.insert(i, (data, d));
+                    result.truncate(k);
+                }
+            }
+        };
+
+        // escaped entries may lie anywhere, so are always considered.
+        for data in self.escaped.iter() {
+            consider(*data, &mut result);
+        }
+
+        if self.arena.is_empty() { return result; }
+
+        let node_distance = |node_idx: OctIndex| {
+            let node = &self.arena[node_idx];
+            let min = node.boundary.min(node.centre);
+            let max = node.boundary.max(node.centre);
+
+            (point - point.clamp(min, max)).length()
+        };
+
+        let mut stack = vec![self.root];
+        while let Some(node_idx) = stack.pop() {
+            // the entries in a node lie wholly within its boundary, so can be no nearer.
+            if result.len() == k && node_distance(node_idx) >= result[k - 1].1 { continue; }
+
+            let node = &self.arena[node_idx];
+            for data in node.data.iter().filter(|d| !self.escaped.contains(d)) {
+                consider(*data, &mut result);
+            }
+
+            // search the nearest children first.
+            let mut children: Vec<(OctIndex, f64)> = node.children.iter()
+                .flatten()
+                .map(|c| (*c, node_distance(*c)))
+                .collect();
+            children.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+            stack.extend(children.into_iter().map(|(c, _)| c));
+        }
+
+        result
+    }
+
 //    /// Returns all data entries in the oct-tree that reside in nodes intersected by the given
 //    /// plane.
 //    pub fn query_by_plane(&self, plane: &Plane, plane_pos: DVec3) -> Vec<T> {
@@ -427,6 +567,76 @@ mod test {
 //        }
 //    }
 
+    #[test]
+    fn test_query_by_ray() {
+        // 100.0 x 100.0 bounding box.
+        let bounding_box = Aabb3D::from_xyz(50.0, 50.0, 50.0);
+        let centre = DVec3::new(50.0, 50.0, 50.0);
+
+        let mut qt = OctTree::new(constants::MAX_OCT_TREE_DEPTH);
+        qt.initialize(centre, bounding_box);
+
+        qt.insert_sphere(&Sphere::new(1.0), DVec3::new(10.0, 10.0, 10.0), 1);
+        qt.insert_sphere(&Sphere::new(1.0), DVec3::new(90.0, 10.0, 10.0), 2);
+        qt.insert_sphere(&Sphere::new(1.0), DVec3::new(10.0, 90.0, 90.0), 3);
+        qt.insert_sphere(&Sphere::new(1.0), DVec3::new(-10.0, 90.0, 90.0), 4);  // escaped.
+
+        // along the bottom of the tree in the x-axis.
+        let mut found = qt.query_by_ray(DVec3::new(-5.0, 10.0, 10.0), DVec3::X, 200.0);
+        found.sort_unstable();
+        assert_eq!(vec![1, 2, 4], found);
+
+        // stopping short of the far octants.
+        let mut found = qt.query_by_ray(DVec3::new(-5.0, 10.0, 10.0), DVec3::X, 20.0);
+        found.sort_unstable();
+        assert_eq!(vec![1, 4], found);
+
+        // a sphere overlapping the first entry's octants, and a swept sphere that clips them.
+        let mut found = qt.query_by_sphere(DVec3::new(10.0, 14.0, 10.0), 2.0);
+        found.sort_unstable();
+        assert_eq!(vec![1, 4], found);
+
+        let mut found = qt.query_by_swept_sphere(DVec3::new(-5.0, 20.0, 10.0), DVec3::X, 20.0, 8.0);
+        found.sort_unstable();
+        assert_eq!(vec![1, 4], found);
+        let found = qt.query_by_swept_sphere(DVec3::new(-5.0, 60.0, 10.0), DVec3::X, 20.0, 8.0);
+        assert_eq!(vec![4], found);
+    }
+
+    #[test]
+    fn test_query_nearest() {
+        // 100.0 x 100.0 bounding box.
+        let bounding_box = Aabb3D::from_xyz(50.0, 50.0, 50.0);
+        let centre = DVec3::new(50.0, 50.0, 50.0);
+
+        let mut qt = OctTree::new(constants::MAX_OCT_TREE_DEPTH);
+        qt.initialize(centre, bounding_box);
+
+        // a row of spheres along the x-axis, with an escaped one at the far end.
+        let positions: Vec<DVec3> = (0..10)
+            .map(|i| DVec3::new(5.0 + i as f64 * 10.0, 10.0, 10.0))
+            .chain(std::iter::once(DVec3::new(110.0, 10.0, 10.0)))
+            .collect();
+        for (i, p) in positions.iter().enumerate() {
+            qt.insert_sphere(&Sphere::new(1.0), *p, i);
+        }
+
+        let point = DVec3::new(42.0, 10.0, 10.0);
+        let distance = |i: usize| Some((positions[i] - point).length() - 1.0);
+
+        let found: Vec<usize> = qt.query_nearest(point, 3, distance).iter().map(|(i, _)| *i).collect();
+        assert_eq!(vec![4, 3, 5], found);
+
+        let found = qt.query_nearest(DVec3::new(120.0, 10.0, 10.0), 1, |i| {
+            Some((positions[i] - DVec3::new(120.0, 10.0, 10.0)).length() - 1.0)
+        });
+        assert_eq!(vec![(10, 9.0)], found);
+
+        // entries can be ignored.
+        let found = qt.query_nearest(point, 1, |i| if i == 4 { None } else { distance(i) });
+        assert_eq!(3, found[0].0);
+    }
+
     #[test]
     fn test_calc_child_octant_idx() {
         // 100.0 x 100.0 bounding box.
@@ -524,6 +734,32 @@ mod test {
         assert!(child_octant_span.is_none());
     }
 
+    #[test]
+    fn test_insert_outside_bounds() {
+        // 100.0 x 100.0 bounding box.
+        let bounding_box = Aabb3D::from_xyz(50.0, 50.0, 50.0);
+        let centre = DVec3::new(50.0, 50.0, 50.0);
+
+        let mut qt = OctTree::new(constants::MAX_OCT_TREE_DEPTH);
+        qt.initialize(centre, bounding_box);
+
+        // wholly within the bounds.
+        assert!(qt.insert_sphere(&Sphere::new(1.0), DVec3::new(10.0, 10.0, 10.0), 1));
+        assert!(!qt.is_escaped(1));
+
+        // straddling the boundary and wholly outside.
+        assert!(!qt.insert_sphere(&Sphere::new(1.0), DVec3::new(99.5, 10.0, 10.0), 2));
+        assert!(!qt.insert_sphere(&Sphere::new(1.0), DVec3::new(10.0, -50.0, 10.0), 3));
+
+        let mut escaped: Vec<_> = qt.escaped().copied().collect();
+        escaped.sort_unstable();
+        assert_eq!(vec![2, 3], escaped);
+
+        // removal clears the escaped record.
+        qt.remove(3);
+        assert!(!qt.is_escaped(3));
+    }
+
     #[test]
     fn test_octtree_construction() {
         // 100.0 x 100.0 bounding box.
@@ -0,0 +1,12 @@
+mod physics_query;
+pub(crate) mod point_projection;
+mod query_filter;
+mod shape_cast;
+
+pub use physics_query::{
+    PhysicsQuery,
+    PointProjection,
+    RayHit,
+    ShapeHit,
+};
+pub use query_filter::QueryFilter;
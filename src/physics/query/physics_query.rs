@@ -0,0 +1,339 @@
+use bevy::{
+    ecs::system::SystemParam,
+    math::DVec3,
+    prelude::*,
+};
+use std::collections::HashMap;
+
+use crate::{
+    physics::components::{
+        BoundaryCollider,
+        Collider,
+        CollisionGroups,
+        PhysTransform,
+        Sensor,
+    },
+    physics::oct_tree::OctTree,
+    physics::query::{
+        point_projection,
+        shape_cast::{self, Piece, Proximity},
+        QueryFilter,
+    },
+    physics::shapes::{
+        CollisionPrimative,
+        RayCast,
+    },
+    constants,
+};
+
+/// The first point at which a ray hits an Entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub entity: Entity,
+    /// The time of impact, in multiples of the ray's direction.
+    pub toi: f64,
+    /// The point hit, in global coords.
+    pub point: DVec3,
+    /// The unit surface normal at the point hit, in global coords.
+    pub normal: DVec3,
+}
+
+/// The first point at which a shape swept along a direction touches an Entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapeHit {
+    pub entity: Entity,
+    /// The time of impact, in multiples of the direction of the sweep.
+    pub toi: f64,
+    /// The point touched on the Entity, in global coords.
+    pub point: DVec3,
+    /// The unit surface normal of the Entity at the point touched, in global coords.
+    pub normal: DVec3,
+}
+
+/// The projection of a point onto the surface of an Entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointProjection {
+    pub entity: Entity,
+    /// The closest point on the surface of the Entity, in global coords.
+    pub point: DVec3,
+    /// The distance from the point projected to the surface.
+    pub distance: f64,
+    /// True if the point projected is inside the Entity.
+    pub is_inside: bool,
+}
+
+/// A SystemParam for querying the shapes in the physics world, e.g. to find what a ray hits.
+///
+/// Candidate bodies are found using the OctTree, which is updated during the physics step, so
+/// queries reflect the positions of bodies at the end of the latest step. Rays can hit any shape,
+/// with TriMeshes and HeightFields hit as surfaces rather than solids. Shape casts and overlap
+/// tests can be made against any shape, but the shape cast must be convex.
+#[derive(SystemParam)]
+pub struct PhysicsQuery<'a> {
+    tree: Res<'a, OctTree<Entity>>,
+    colliders: Query<'a, (
+        &'static Collider,
+        &'static PhysTransform,
+        Option<&'static CollisionGroups>,
+        Option<&'static Sensor>,
+    )>,
+    boundaries: Query<'a, (
+        Entity,
+        &'static BoundaryCollider,
+        &'static PhysTransform,
+        Option<&'static CollisionGroups>,
+    )>,
+}
+
+impl<'a> PhysicsQuery<'a> {
+    /// Returns the first Entity allowed by the filter that is hit by the ray with the given origin
+    /// and direction, if any is hit within the given maximum time of impact. The time of impact is
+    /// measured in multiples of the direction, so is a distance if the direction is normalised.
+    pub fn cast_ray(
+        &self,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+        filter: &QueryFilter,
+    ) -> Option<RayHit> {
+        self.calc_ray_hits(origin, direction, max_toi, filter)
+            .min_by(|a, b| a.toi.partial_cmp(&b.toi).unwrap())
+    }
+
+    /// Returns every Entity allowed by the filter that is hit by the ray with the given origin
+    /// and direction within the given maximum time of impact, ordered by time of impact.
+    pub fn cast_ray_all(
+        &self,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+        filter: &QueryFilter,
+    ) -> Vec<RayHit> {
+        let mut result: Vec<RayHit> = self.calc_ray_hits(origin, direction, max_toi, filter)
+            .collect();
+        result.sort_by(|a, b| a.toi.partial_cmp(&b.toi).unwrap());
+
+        result
+    }
+
+    /// Returns the first Entity allowed by the filter that is touched by the given shape as it is
+    /// swept, without rotating, from the given transform along the given direction, if any is
+    /// touched within the given maximum time of impact. The time of impact is measured in multiples
+    /// of the direction.
+    ///
+    /// Entitys already overlapping the shape are touched at a time of impact of zero. Returns None
+    /// if the shape is not convex, i.e. is not a SupportMap.
+    pub fn cast_shape(
+        &self,
+        shape: &dyn CollisionPrimative,
+        transform: &PhysTransform,
+        direction: DVec3,
+        max_toi: f64,
+        filter: &QueryFilter,
+    ) -> Option<ShapeHit> {
+        let support_map = shape.as_support_map()?;
+        let radius = shape.bounding_sphere().radius();
+
+        // the sweep is made in terms of distance along the unit direction.
+        let speed = direction.length();
+        let unit_direction = direction.normalize_or_zero();
+        let max_distance = max_toi * speed;
+        let start = transform.translation();
+        let end = start + direction * max_toi;
+
+        let sweep = |entity: Entity, proximity: &mut dyn FnMut(&PhysTransform) -> Option<Proximity>| {
+            shape_cast::calc_time_of_impact(transform, unit_direction, max_distance, proximity)
+                .map(|(distance, proximity)| ShapeHit {
+                    entity,
+                    toi: if speed > 0.0 { distance / speed } else { 0.0 },
+                    point: proximity.point,
+                    normal: proximity.normal,
+                })
+        };
+
+        let body_hits = self.tree.query_by_swept_sphere(start, direction, max_toi, radius)
+            .into_iter()
+            .filter_map(|ent| {
+                let pieces = self.collect_pieces(ent, start, end, radius, filter)?;
+
+                sweep(ent, &mut |t| shape_cast::calc_pieces_proximity(support_map, t, &pieces))
+            });
+
+        let boundary_hits = self.boundaries.iter()
+            .filter(|(ent, _, _, groups)| !filter.exclude_boundaries && filter.allows(*ent, *groups))
+            .filter_map(|(ent, boundary, bnd_transform, _)| {
+                sweep(ent, &mut |t| {
+                    Some(shape_cast::calc_plane_proximity(support_map, t, &boundary.0, bnd_transform))
+                })
+            });
+
+        body_hits.chain(boundary_hits)
+            .min_by(|a, b| a.toi.partial_cmp(&b.toi).unwrap())
+    }
+
+    /// Returns every Entity allowed by the filter that overlaps, or touches, the given shape with
+    /// the given transform. Returns an empty list if the shape is not convex, i.e. is not a
+    /// SupportMap.
+    pub fn intersections_with_shape(
+        &self,
+        shape: &dyn CollisionPrimative,
+        transform: &PhysTransform,
+        filter: &QueryFilter,
+    ) -> Vec<Entity> {
+        let support_map = match shape.as_support_map() {
+            Some(support_map) => support_map,
+            None => return vec![],
+        };
+        let radius = shape.bounding_sphere().radius();
+        let centre = transform.translation();
+
+        let overlaps = |proximity: Option<Proximity>| {
+            matches!(proximity, Some(p) if p.distance < constants::SHAPE_CAST_TOLERANCE)
+        };
+
+        let body_overlaps = self.tree.query_by_sphere(centre, radius)
+            .into_iter()
+            .filter(|ent| match self.collect_pieces(*ent, centre, centre, radius, filter) {
+                Some(pieces) => {
+                    overlaps(shape_cast::calc_pieces_proximity(support_map, transform, &pieces))
+                },
+                None => false,
+            });
+
+        let boundary_overlaps = self.boundaries.iter()
+            .filter(|(ent, _, _, groups)| !filter.exclude_boundaries && filter.allows(*ent, *groups))
+            .filter(|(_, boundary, bnd_transform, _)| {
+                overlaps(Some(shape_cast::calc_plane_proximity(
+                    support_map, transform, &boundary.0, bnd_transform,
+                )))
+            })
+            .map(|(ent, _, _, _)| ent);
+
+        body_overlaps.chain(boundary_overlaps).collect()
+    }
+
+    /// Returns the projection of the given point onto the nearest Entity allowed by the filter, if
+    /// any. Entitys containing the point are nearest, with their surfaces at zero distance.
+    pub fn project_point(&self, point: DVec3, filter: &QueryFilter) -> Option<PointProjection> {
+        self.nearest_entities(point, 1, filter).pop()
+    }
+
+    /// Returns true if the given point is inside any Entity allowed by the filter.
+    pub fn contains_point(&self, point: DVec3, filter: &QueryFilter) -> bool {
+        matches!(self.project_point(point, filter), Some(p) if p.is_inside)
+    }
+
+    /// Returns the projections of the given point onto the k nearest Entitys allowed by the
+    /// filter, nearest first. Entitys containing the point are nearest, with their surfaces at
+    /// zero distance.
+    pub fn nearest_entities(&self, point: DVec3, k: usize, filter: &QueryFilter) -> Vec<PointProjection> {
+        let project = |entity: Entity, (surface, is_inside): (DVec3, bool)| PointProjection {
+            entity,
+            point: surface,
+            distance: if is_inside { 0.0 } else { (point - surface).length() },
+            is_inside,
+        };
+
+        // keep the projections made while searching the tree, rather than repeating them.
+        let mut projections = HashMap::new();
+
+        let nearest = self.tree.query_nearest(point, k, |ent| {
+            let (collider, transform, groups, sensor) = self.colliders.get(ent).ok()?;
+
+            if !filter.allows(ent, groups) || (filter.exclude_sensors && sensor.is_some()) {
+                return None;
+            }
+
+            let projection = project(
+                ent,
+                point_projection::calc_projection(collider.0.as_ref(), transform, point),
+            );
+            projections.insert(ent, projection);
+
+            Some(projection.distance)
+        });
+
+        let mut result: Vec<PointProjection> = nearest.iter()
+            .map(|(ent, _)| projections[ent])
+            .collect();
+
+        if !filter.exclude_boundaries {
+            result.extend(self.boundaries.iter()
+                .filter(|(ent, _, _, groups)| filter.allows(*ent, *groups))
+                .map(|(ent, boundary, transform, _)| {
+                    project(ent, point_projection::calc_plane_projection(&boundary.0, transform, point))
+                }));
+
+            result.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap());
+            result.truncate(k);
+        }
+
+        result
+    }
+
+    // --- Helper methods
+
+    /// Returns the convex pieces of the given Entity's Collider that may be touched by a sphere
+    /// with the given radius moving from start to end, or None if the Entity is not allowed by the
+    /// filter.
+    fn collect_pieces(
+        &self,
+        entity: Entity,
+        start: DVec3,
+        end: DVec3,
+        radius: f64,
+        filter: &QueryFilter,
+    ) -> Option<Vec<Piece<'_>>> {
+        let (collider, transform, groups, sensor) = self.colliders.get(entity).ok()?;
+
+        if !filter.allows(entity, groups) || (filter.exclude_sensors && sensor.is_some()) {
+            return None;
+        }
+
+        let mut pieces = vec![];
+        shape_cast::collect_pieces(collider.0.as_ref(), transform, start, end, radius, &mut pieces);
+
+        Some(pieces)
+    }
+
+
+    /// Returns the hits on all bodies, found using the OctTree, and boundaries allowed by the
+    /// filter.
+    fn calc_ray_hits<'b>(
+        &'b self,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+        filter: &'b QueryFilter,
+    ) -> impl Iterator<Item = RayHit> + 'b {
+        let hit = move |entity: Entity, shape: &dyn RayCast, transform: &PhysTransform| {
+            shape.cast_ray(transform, origin, direction, max_toi)
+                .map(|(toi, normal)| RayHit {
+                    entity,
+                    toi,
+                    point: origin + direction * toi,
+                    normal,
+                })
+        };
+
+        let body_hits = self.tree.query_by_ray(origin, direction, max_toi)
+            .into_iter()
+            .filter_map(move |ent| {
+                let (collider, transform, groups, sensor) = self.colliders.get(ent).ok()?;
+
+                if !filter.allows(ent, groups) || (filter.exclude_sensors && sensor.is_some()) {
+                    return None;
+                }
+
+                hit(ent, collider.0.as_ray_cast()?, transform)
+            });
+
+        let boundary_hits = self.boundaries.iter()
+            .filter(move |(ent, _, _, groups)| {
+                !filter.exclude_boundaries && filter.allows(*ent, *groups)
+            })
+            .filter_map(move |(ent, boundary, transform, _)| hit(ent, &boundary.0, transform));
+
+        body_hits.chain(boundary_hits)
+    }
+}
@@ -0,0 +1,202 @@
+use bevy::math::DVec3;
+
+use crate::{
+    physics::components::PhysTransform,
+    physics::shapes::{
+        gjk,
+        triangle::TriangleSet,
+        Capsule,
+        Collidable,
+        CollisionPrimative,
+        Compound,
+        Cuboid,
+        Cylinder,
+        HeightField,
+        Plane,
+        Sphere,
+        TriMesh,
+    },
+};
+
+/// Returns the closest point on the surface of the given primative, with the given transform, to
+/// the given point in global coords, along with whether the point is inside the primative.
+///
+/// A point is inside a TriMesh or HeightField if it lies behind the triangle nearest to it. Points
+/// are never inside a Compound's surface, but inside the surface of one of its children.
+/// Primatives with no projection are represented by their bounding sphere.
+pub(crate) fn calc_projection(
+    primative: &dyn CollisionPrimative,
+    transform: &PhysTransform,
+    point: DVec3,
+) -> (DVec3, bool) {
+    if let Some(sphere) = primative.downcast_ref::<Sphere>() {
+        calc_collidable_projection(sphere, transform, point)
+    } else if let Some(cuboid) = primative.downcast_ref::<Cuboid>() {
+        calc_cuboid_projection(cuboid, transform, point)
+    } else if let Some(capsule) = primative.downcast_ref::<Capsule>() {
+        calc_collidable_projection(capsule, transform, point)
+    } else if let Some(cylinder) = primative.downcast_ref::<Cylinder>() {
+        calc_cylinder_projection(cylinder, transform, point)
+    } else if let Some(mesh) = primative.downcast_ref::<TriMesh>() {
+        calc_triangles_projection(mesh, transform, point)
+    } else if let Some(field) = primative.downcast_ref::<HeightField>() {
+        calc_triangles_projection(field, transform, point)
+    } else if let Some(compound) = primative.downcast_ref::<Compound>() {
+        calc_compound_projection(compound, transform, point)
+    } else if let Some((surface, inside)) = primative.as_support_map()
+        .and_then(|shape| gjk::calc_surface_projection(shape, transform, point))
+    {
+        (surface, inside)
+    } else {
+        calc_collidable_projection(primative.bounding_sphere(), transform, point)
+    }
+}
+
+/// Returns the closest point on the given boundary, with the given transform, to the given point
+/// in global coords, along with whether the point is behind the boundary.
+pub(crate) fn calc_plane_projection(
+    plane: &Plane,
+    transform: &PhysTransform,
+    point: DVec3,
+) -> (DVec3, bool) {
+    calc_collidable_projection(plane, transform, point)
+}
+
+// --- Helper methods
+
+/// Projects the point onto a shape whose Collidable implementation always returns a point on its
+/// surface and a negative distance to points inside it.
+fn calc_collidable_projection(
+    shape: &dyn Collidable,
+    transform: &PhysTransform,
+    point: DVec3,
+) -> (DVec3, bool) {
+    (shape.closest_point_to(transform, point), shape.shortest_distance_to(transform, point) < 0.0)
+}
+
+fn calc_cuboid_projection(cuboid: &Cuboid, transform: &PhysTransform, point: DVec3) -> (DVec3, bool) {
+    let point_local = transform.get_point_in_local_space(point);
+    let extents = cuboid.extents();
+    let gaps = extents - point_local.abs();
+
+    if gaps.min_element() < 0.0 {
+        return (cuboid.closest_point_to(transform, point), false);
+    }
+
+    // move the point out through the nearest face.
+    let axis = if gaps.x <= gaps.y && gaps.x <= gaps.z {
+        0
+    } else if gaps.y <= gaps.z {
+        1
+    } else {
+        2
+    };
+    let mut surface = point_local;
+    surface[axis] = extents[axis].copysign(point_local[axis]);
+
+    (transform.get_point_in_global_space(surface), true)
+}
+
+fn calc_cylinder_projection(
+    cylinder: &Cylinder,
+    transform: &PhysTransform,
+    point: DVec3,
+) -> (DVec3, bool) {
+    let point_local = transform.get_point_in_local_space(point);
+    let radial = DVec3::new(point_local.x, 0.0, point_local.z);
+    let radial_gap = cylinder.radius() - radial.length();
+    let height_gap = cylinder.half_height() - point_local.y.abs();
+
+    if radial_gap < 0.0 || height_gap < 0.0 {
+        return (cylinder.closest_point_to(transform, point), false);
+    }
+
+    // move the point out through the nearest of the curved face and the caps.
+    let mut surface = point_local;
+    if radial_gap < height_gap {
+        let direction = if radial.length_squared() > 0.0 { radial.normalize() } else { DVec3::X };
+        surface = direction * cylinder.radius() + DVec3::Y * point_local.y;
+    } else {
+        surface.y = cylinder.half_height().copysign(point_local.y);
+    }
+
+    (transform.get_point_in_global_space(surface), true)
+}
+
+fn calc_triangles_projection(
+    triangles: &dyn TriangleSet,
+    transform: &PhysTransform,
+    point: DVec3,
+) -> (DVec3, bool) {
+    let point_local = transform.get_point_in_local_space(point);
+    let (triangle, closest) = triangles.closest_triangle_to(point_local);
+
+    (transform.get_point_in_global_space(closest), (point_local - closest).dot(triangle.normal()) < 0.0)
+}
+
+fn calc_compound_projection(
+    compound: &Compound,
+    transform: &PhysTransform,
+    point: DVec3,
+) -> (DVec3, bool) {
+    let mut result = (transform.translation(), false);
+    let mut nearest = f64::INFINITY;
+
+    for (child_transform, child) in compound.children() {
+        let child_global = transform.mul_transform(child_transform);
+        let (surface, inside) = calc_projection(child.as_ref(), &child_global, point);
+        let distance = (point - surface).length();
+
+        // a child containing the point takes precedence over any the point is outside.
+        if (inside && !result.1) || ((inside == result.1) && distance < nearest) {
+            result = (surface, inside);
+            nearest = distance;
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::physics::shapes::ConvexHull;
+
+    const EPSILON: f64 = 0.001;
+
+    #[test]
+    fn test_calc_projection() {
+        let transform = PhysTransform::from_xyz(0.0, 1.0, 0.0);
+
+        // a cuboid, from outside and inside.
+        let cuboid = Cuboid::new(DVec3::new(2.0, 1.0, 1.0));
+        assert_eq!((DVec3::new(2.0, 1.5, 0.0), false), calc_projection(&cuboid, &transform, DVec3::new(4.0, 1.5, 0.0)));
+        assert_eq!((DVec3::new(1.5, 2.0, 0.0), true), calc_projection(&cuboid, &transform, DVec3::new(1.5, 1.8, 0.0)));
+
+        // a cylinder, from inside near the curved face and near a cap.
+        let cylinder = Cylinder::new(1.0, 2.0);
+        assert_eq!((DVec3::new(0.0, 1.5, 1.0), true), calc_projection(&cylinder, &transform, DVec3::new(0.0, 1.5, 0.8)));
+        assert_eq!((DVec3::new(0.2, -1.0, 0.0), true), calc_projection(&cylinder, &transform, DVec3::new(0.2, -0.5, 0.0)));
+
+        // a convex shape with no specific projection.
+        let hull = ConvexHull::new(Cuboid::new(DVec3::ONE).vertices(&PhysTransform::IDENTITY).to_vec());
+        let (surface, inside) = calc_projection(&hull, &transform, DVec3::new(0.5, 1.5, 0.8));
+        assert!((surface - DVec3::new(0.5, 1.5, 1.0)).length() < EPSILON);
+        assert!(inside);
+
+        // a compound, inside one child but nearer to the surface of another.
+        let compound = Compound::new()
+            .with_child(PhysTransform::IDENTITY, Sphere::new(1.0))
+            .with_child(PhysTransform::from_xyz(1.8, 0.0, 0.0), Sphere::new(1.0));
+        let (surface, inside) = calc_projection(&compound, &PhysTransform::IDENTITY, DVec3::new(0.7, 0.0, 0.0));
+        assert!((surface - DVec3::new(1.0, 0.0, 0.0)).length() < EPSILON);
+        assert!(inside);
+
+        // beneath a floor.
+        let floor = HeightField::new(vec![0.0; 4], 2, 2, 10.0, 10.0);
+        let (surface, inside) = calc_projection(&floor, &transform, DVec3::new(1.0, 0.5, 1.0));
+        assert!((surface - DVec3::new(1.0, 1.0, 1.0)).length() < EPSILON);
+        assert!(inside);
+    }
+}
@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+
+use crate::physics::components::CollisionGroups;
+
+/// Restricts the Entitys that can be found by a PhysicsQuery.
+#[derive(Debug, Clone, Default)]
+pub struct QueryFilter {
+    /// Only Entitys whose CollisionGroups interact with these groups are found. Entitys without
+    /// CollisionGroups belong to, and collide with, all groups.
+    pub groups: CollisionGroups,
+    /// Entitys that are never found, e.g. the body casting the query.
+    pub excluded: Vec<Entity>,
+    /// When true, Entitys with a Sensor component are not found.
+    pub exclude_sensors: bool,
+    /// When true, boundaries are not found.
+    pub exclude_boundaries: bool,
+}
+
+impl QueryFilter {
+    /// Creates a new QueryFilter that excludes the given Entity, but is otherwise unrestricted.
+    pub fn excluding(entity: Entity) -> Self {
+        Self { excluded: vec![entity], ..Default::default() }
+    }
+
+    /// Returns true if the given Entity, with the given CollisionGroups (if any), may be found.
+    pub fn allows(&self, entity: Entity, groups: Option<&CollisionGroups>) -> bool {
+        !self.excluded.contains(&entity)
+            && self.groups.interacts_with(groups.unwrap_or(&CollisionGroups::ALL))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_allows() {
+        let a = Entity::new(1);
+        let b = Entity::new(2);
+
+        let filter = QueryFilter::excluding(a);
+        assert!(!filter.allows(a, None));
+        assert!(filter.allows(b, None));
+
+        let filter = QueryFilter { groups: CollisionGroups::new(0b01, 0b10), ..Default::default() };
+        assert!(filter.allows(a, Some(&CollisionGroups::new(0b10, 0b01))));
+        assert!(!filter.allows(a, Some(&CollisionGroups::new(0b01, 0b01))));
+        assert!(filter.allows(b, None));
+    }
+}
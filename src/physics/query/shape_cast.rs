@@ -0,0 +1,276 @@
+use bevy::math::DVec3;
+
+use crate::{
+    constants,
+    physics::components::PhysTransform,
+    physics::shapes::{
+        gjk,
+        triangle::{Triangle, TriangleSet},
+        Collidable,
+        CollisionPrimative,
+        Compound,
+        HeightField,
+        Plane,
+        SupportMap,
+        TriMesh,
+    },
+};
+
+/// The separation between a convex query shape and a target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Proximity {
+    /// The distance between the shape and the target, or zero if they intersect.
+    pub distance: f64,
+    /// The point on the target closest to, or deepest within, the shape in global coords.
+    pub point: DVec3,
+    /// The unit normal at the point, pointing from the target towards the shape. Zero if it could
+    /// not be found.
+    pub normal: DVec3,
+}
+
+/// A convex part of a target shape, placed by its own transform in global coords.
+pub(crate) struct Piece<'a> {
+    shape: PieceShape<'a>,
+    transform: PhysTransform,
+}
+
+enum PieceShape<'a> {
+    Convex(&'a dyn SupportMap),
+    Triangle(Triangle),
+}
+
+impl<'a> Piece<'a> {
+    fn support_map(&self) -> &dyn SupportMap {
+        match &self.shape {
+            PieceShape::Convex(shape) => *shape,
+            PieceShape::Triangle(triangle) => triangle,
+        }
+    }
+}
+
+/// Breaks the given target down into the convex pieces that may be touched by a sphere with the
+/// given radius moving from start to end in global coords. Compounds are broken down into their
+/// children and TriMeshes and HeightFields into their nearby triangles. Primatives that are not
+/// convex and cannot be broken down are ignored.
+pub(crate) fn collect_pieces<'a>(
+    target: &'a dyn CollisionPrimative,
+    transform: &PhysTransform,
+    start: DVec3,
+    end: DVec3,
+    radius: f64,
+    pieces: &mut Vec<Piece<'a>>,
+) {
+    if let Some(compound) = target.downcast_ref::<Compound>() {
+        for (child_transform, child) in compound.children() {
+            let child_global = transform.mul_transform(child_transform);
+            collect_pieces(child.as_ref(), &child_global, start, end, radius, pieces);
+        }
+        return;
+    }
+
+    if let Some(shape) = target.as_support_map() {
+        pieces.push(Piece { shape: PieceShape::Convex(shape), transform: transform.clone() });
+        return;
+    }
+
+    let triangles: &dyn TriangleSet = if let Some(mesh) = target.downcast_ref::<TriMesh>() {
+        mesh
+    } else if let Some(field) = target.downcast_ref::<HeightField>() {
+        field
+    } else {
+        return;
+    };
+
+    // the sphere's path is bounded by the same box in any orientation.
+    let start_local = transform.get_point_in_local_space(start);
+    let end_local = transform.get_point_in_local_space(end);
+    let min = start_local.min(end_local) - DVec3::splat(radius);
+    let max = start_local.max(end_local) + DVec3::splat(radius);
+
+    for (_, triangle) in triangles.triangles_in_aabb(min, max) {
+        pieces.push(Piece { shape: PieceShape::Triangle(triangle), transform: transform.clone() });
+    }
+}
+
+/// Returns the Proximity of the given shape to the nearest of the given pieces, or None if there
+/// are no pieces.
+pub(crate) fn calc_pieces_proximity(
+    shape: &dyn SupportMap,
+    transform: &PhysTransform,
+    pieces: &[Piece],
+) -> Option<Proximity> {
+    pieces.iter()
+        .map(|piece| calc_convex_proximity(shape, transform, piece.support_map(), &piece.transform))
+        .min_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap())
+}
+
+/// Returns the Proximity of the given shape to the solid half-space behind the given Plane.
+pub(crate) fn calc_plane_proximity(
+    shape: &dyn SupportMap,
+    transform: &PhysTransform,
+    plane: &Plane,
+    plane_transform: &PhysTransform,
+) -> Proximity {
+    let normal = plane_transform.get_direction_in_global_space(plane.normal_in_body_space());
+    let deepest = shape.support_point(transform, -normal);
+
+    Proximity {
+        distance: plane.shortest_distance_to(plane_transform, deepest).max(0.0),
+        point: plane.closest_point_to(plane_transform, deepest),
+        normal,
+    }
+}
+
+/// Returns the distance travelled by a shape with the given transform, moving in the given unit
+/// direction, before it first touches a target, along with its Proximity to the target at that
+/// time. Returns None if the shape does not touch the target within the given maximum distance.
+/// The given function returns the Proximity of the shape to the target at a given transform, or
+/// None if there is nothing to touch.
+///
+/// The time of impact is found by conservative advancement; the shape is repeatedly advanced by
+/// the gap between it and the target, which can never carry it past the surface.
+pub(crate) fn calc_time_of_impact(
+    transform: &PhysTransform,
+    direction: DVec3,
+    max_distance: f64,
+    mut proximity: impl FnMut(&PhysTransform) -> Option<Proximity>,
+) -> Option<(f64, Proximity)> {
+    let mut moved = transform.clone();
+    let mut distance = 0.0;
+
+    for _ in 0..constants::SHAPE_CAST_MAX_ITERATIONS {
+        let current = proximity(&moved)?;
+
+        if current.distance < constants::SHAPE_CAST_TOLERANCE {
+            return Some((distance, current));
+        }
+
+        distance += current.distance;
+        if distance > max_distance { return None; }
+
+        moved.translation = transform.translation + direction * distance;
+        moved.update();
+    }
+
+    None
+}
+
+// --- Helper methods
+
+/// Returns the Proximity of the given shape to the given convex target.
+fn calc_convex_proximity(
+    shape: &dyn SupportMap,
+    transform: &PhysTransform,
+    target: &dyn SupportMap,
+    target_transform: &PhysTransform,
+) -> Proximity {
+    match gjk::gjk(shape, transform, target, target_transform) {
+        gjk::GjkResult::Separated { distance, point_a, point_b } => Proximity {
+            distance,
+            point: point_b,
+            normal: (point_a - point_b).normalize_or_zero(),
+        },
+        gjk::GjkResult::Intersecting(simplex) => {
+            match gjk::epa(shape, transform, target, target_transform, simplex) {
+                Some(penetration) => Proximity {
+                    distance: 0.0,
+                    point: penetration.point_b,
+                    normal: -penetration.normal,
+                },
+                None => Proximity {
+                    distance: 0.0,
+                    point: transform.translation,
+                    normal: DVec3::ZERO,
+                },
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::physics::shapes::{
+        Cuboid,
+        Sphere,
+    };
+
+    const EPSILON: f64 = 0.001;
+
+    #[test]
+    fn test_calc_time_of_impact() {
+        // a unit cube swept along the x-axis towards a sphere.
+        let cube = Cuboid::new(DVec3::splat(0.5));
+        let transform = PhysTransform::from_xyz(-5.0, 0.0, 0.0);
+        let sphere = Sphere::new(1.0);
+        let sphere_transform = PhysTransform::IDENTITY;
+
+        let mut pieces = vec![];
+        collect_pieces(&sphere, &sphere_transform, DVec3::ZERO, DVec3::ZERO, 0.0, &mut pieces);
+
+        let (distance, proximity) = calc_time_of_impact(&transform, DVec3::X, 10.0, |t| {
+            calc_pieces_proximity(&cube, t, &pieces)
+        }).unwrap();
+        assert!((distance - 3.5).abs() < EPSILON);
+        assert!((proximity.point - DVec3::new(-1.0, 0.0, 0.0)).length() < EPSILON);
+        assert!((proximity.normal - -DVec3::X).length() < EPSILON);
+
+        // falling short, and passing by.
+        assert!(calc_time_of_impact(&transform, DVec3::X, 3.0, |t| {
+            calc_pieces_proximity(&cube, t, &pieces)
+        }).is_none());
+        let transform = PhysTransform::from_xyz(-5.0, 1.6, 0.0);
+        assert!(calc_time_of_impact(&transform, DVec3::X, 10.0, |t| {
+            calc_pieces_proximity(&cube, t, &pieces)
+        }).is_none());
+    }
+
+    #[test]
+    fn test_collect_pieces() {
+        let floor = TriMesh::from_buffers(
+            &[
+                DVec3::new(-10.0, 0.0, -10.0),
+                DVec3::new(-10.0, 0.0, 10.0),
+                DVec3::new(10.0, 0.0, 10.0),
+                DVec3::new(10.0, 0.0, -10.0),
+                DVec3::new(20.0, 0.0, 10.0),
+                DVec3::new(20.0, 0.0, -10.0),
+            ],
+            &[0, 1, 2, 0, 2, 3, 3, 2, 4, 3, 4, 5],
+        ).unwrap();
+        let compound = Compound::new()
+            .with_child(PhysTransform::IDENTITY, floor)
+            .with_child(PhysTransform::from_xyz(0.0, 5.0, 0.0), Sphere::new(1.0));
+
+        // a sphere dropped onto the left of the floor, missing the far triangles.
+        let mut pieces = vec![];
+        let start = DVec3::new(-5.0, 3.0, 0.0);
+        let end = DVec3::new(-5.0, -3.0, 0.0);
+        collect_pieces(&compound, &PhysTransform::IDENTITY, start, end, 1.0, &mut pieces);
+        assert_eq!(3, pieces.len());
+
+        let sphere = Sphere::new(1.0);
+        let transform = PhysTransform::from_xyz(-5.0, 3.0, 0.0);
+        let (distance, proximity) = calc_time_of_impact(&transform, -DVec3::Y, 6.0, |t| {
+            calc_pieces_proximity(&sphere, t, &pieces)
+        }).unwrap();
+        assert!((distance - 2.0).abs() < EPSILON);
+        assert!((proximity.normal - DVec3::Y).length() < EPSILON);
+    }
+
+    #[test]
+    fn test_calc_plane_proximity() {
+        let plane_transform = PhysTransform::from_xyz(0.0, -1.0, 0.0);
+        let plane = Plane::new(&plane_transform);
+        let cube = Cuboid::new(DVec3::splat(0.5));
+
+        let proximity = calc_plane_proximity(&cube, &PhysTransform::from_xyz(2.0, 1.0, 0.0), &plane, &plane_transform);
+        assert_eq!(1.5, proximity.distance);
+        assert_eq!(DVec3::Y, proximity.normal);
+        assert_eq!(-1.0, proximity.point.y);
+
+        let proximity = calc_plane_proximity(&cube, &PhysTransform::from_xyz(2.0, -1.0, 0.0), &plane, &plane_transform);
+        assert_eq!(0.0, proximity.distance);
+    }
+}
@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use std::fmt;
+
+/// The signature of a custom pair filter. Returns true if the given pair of Entitys may collide.
+pub type PairFilterFn = dyn Fn(Entity, Entity) -> bool + Send + Sync;
+
+/// A resource holding an optional custom filter that decides whether a pair of Entitys may
+/// collide. The filter is applied, along with any CollisionGroups, before contact generation.
+///
+/// Pairs involving a boundary are given with the boundary second.
+#[derive(Default)]
+pub struct CollisionFilter {
+    pair_filter: Option<Box<PairFilterFn>>,
+}
+
+impl CollisionFilter {
+    /// Creates a new CollisionFilter with the given pair filter.
+    pub fn new<F>(pair_filter: F) -> Self
+    where
+        F: Fn(Entity, Entity) -> bool + Send + Sync + 'static,
+    {
+        Self { pair_filter: Some(Box::new(pair_filter)) }
+    }
+
+    /// Registers the given pair filter, replacing any existing one.
+    pub fn set_pair_filter<F>(&mut self, pair_filter: F)
+    where
+        F: Fn(Entity, Entity) -> bool + Send + Sync + 'static,
+    {
+        self.pair_filter = Some(Box::new(pair_filter));
+    }
+
+    /// Removes the pair filter, so that all pairs may collide.
+    pub fn clear_pair_filter(&mut self) {
+        self.pair_filter = None;
+    }
+
+    /// Returns true if the given pair of Entitys may collide according to the pair filter.
+    pub fn allows(&self, a: Entity, b: Entity) -> bool {
+        match &self.pair_filter {
+            Some(pair_filter) => pair_filter(a, b),
+            None => true,
+        }
+    }
+}
+
+impl fmt::Debug for CollisionFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CollisionFilter")
+            .field("pair_filter", &self.pair_filter.as_ref().map(|_| "Fn(Entity, Entity) -> bool"))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_allows() {
+        let a = Entity::new(1);
+        let b = Entity::new(2);
+        let c = Entity::new(3);
+
+        let mut filter = CollisionFilter::default();
+        assert!(filter.allows(a, b));
+
+        filter.set_pair_filter(move |x, y| x != c && y != c);
+        assert!(filter.allows(a, b));
+        assert!(!filter.allows(a, c));
+        assert!(!filter.allows(c, b));
+
+        filter.clear_pair_filter();
+        assert!(filter.allows(a, c));
+    }
+}
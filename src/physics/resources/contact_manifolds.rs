@@ -0,0 +1,332 @@
+use bevy::{
+    prelude::*,
+    math::DVec3,
+};
+use std::collections::HashMap;
+
+use crate::{
+    physics::components::{
+        Contact,
+        PhysTransform,
+    },
+};
+
+/// A contact point held in a ContactManifold, along with the data retained for it between physics
+/// steps.
+#[derive(Debug)]
+pub struct ManifoldPoint {
+    pub contact: Contact,
+    /// The contact point in the local space of the first Entity in the contact, used to match the
+    /// point with its successor in the next step.
+    pub local_point: DVec3,
+    /// The total impulse applied to the first Entity in the contact when it was last resolved, in
+    /// global coords.
+    pub accumulated_impulse: DVec3,
+}
+
+/// The set of contact points between a pair of Entitys (or an Entity and a boundary), persisted
+/// for as long as the pair remain in contact.
+#[derive(Debug)]
+pub struct ContactManifold {
+    pair: (Entity, Entity),
+    points: Vec<ManifoldPoint>,
+    /// The number of consecutive physics steps the pair have been in contact for, prior to the
+    /// current one.
+    age: u32,
+    updated: bool,
+}
+
+impl ContactManifold {
+    /// Returns the pair of Entitys in contact. The first is the first Entity in each contact and
+    /// the second is either the other body or the boundary.
+    pub fn pair(&self) -> (Entity, Entity) {
+        self.pair
+    }
+
+    /// Returns the current contact points.
+    pub fn points(&self) -> &[ManifoldPoint] {
+        &self.points
+    }
+
+    /// Returns a mutable reference to the current contact points.
+    pub fn points_mut(&mut self) -> &mut [ManifoldPoint] {
+        &mut self.points
+    }
+
+    /// Returns the number of consecutive physics steps the pair had already been in contact for
+    /// when the current contact points were generated. 0 for a new contact.
+    pub fn age(&self) -> u32 {
+        self.age
+    }
+
+    /// Returns the contact normal, pointing from the first Entity in the pair towards the second.
+    pub fn normal(&self) -> DVec3 {
+        self.points.first().map_or(DVec3::ZERO, |p| p.contact.normal)
+    }
+
+    /// Returns the total magnitude of the impulses applied at the contact points when they were
+    /// last resolved.
+    pub fn impulse(&self) -> f64 {
+        self.points.iter().map(|p| p.accumulated_impulse.length()).sum()
+    }
+
+    /// Returns true if the second Entity in the pair is a boundary.
+    pub fn is_boundary(&self) -> bool {
+        self.points.iter().any(|p| p.contact.boundary.is_some())
+    }
+
+    /// Replaces the contact points with those given, matching each with a point from the previous
+    /// step to carry over its accumulated impulse. Points are matched by feature id first, then
+    /// by proximity, within the given tolerance. The transform must be that of the first Entity
+    /// in the pair.
+    ///
+    /// If the manifold has already been updated in the current step the points are added to
+    /// those already present.
+    fn update(&mut self, contacts: Vec<Contact>, transform: &PhysTransform, tolerance: f64) {
+        let previous = if self.updated {
+            vec![]
+        } else {
+            std::mem::take(&mut self.points)
+        };
+        let mut taken = vec![false; previous.len()];
+
+        let local_points: Vec<DVec3> = contacts.iter()
+            .map(|c| transform.get_point_in_local_space(c.point))
+            .collect();
+
+        // Returns the index of the nearest available previous point within tolerance that satisfies
+        // the given predicate.
+        let find_match = |local_point: DVec3, taken: &[bool], predicate: &dyn Fn(&ManifoldPoint) -> bool| {
+            previous.iter()
+                .enumerate()
+                .filter(|(i, p)| !taken[*i] && predicate(p))
+                .map(|(i, p)| (i, p.local_point.distance_squared(local_point)))
+                .filter(|(_, distance_sq)| *distance_sq <= tolerance * tolerance)
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                .map(|(i, _)| i)
+        };
+
+        let mut matches = vec![None; contacts.len()];
+
+        // Match by feature id first, then by proximity alone.
+        for (i, contact) in contacts.iter().enumerate() {
+            matches[i] = find_match(local_points[i], &taken,
+                                    &|p| p.contact.feature_id == contact.feature_id);
+            if let Some(m) = matches[i] {
+                taken[m] = true;
+            }
+        }
+        for i in 0..contacts.len() {
+            if matches[i].is_none() {
+                matches[i] = find_match(local_points[i], &taken, &|_| true);
+                if let Some(m) = matches[i] {
+                    taken[m] = true;
+                }
+            }
+        }
+
+        for ((contact, local_point), matched) in contacts.into_iter().zip(local_points).zip(matches) {
+            let accumulated_impulse = matched
+                .map(|m| previous[m].accumulated_impulse)
+                .unwrap_or(DVec3::ZERO);
+
+            self.points.push(ManifoldPoint { contact, local_point, accumulated_impulse });
+        }
+
+        self.updated = true;
+    }
+}
+
+/// A resource holding the ContactManifold of every pair of Entitys currently in contact.
+///
+/// Manifolds are updated with new contact points during collision detection each physics step,
+/// and expire once the Entitys are no longer in contact. Expired manifolds are retained until the
+/// next step.
+#[derive(Debug, Default)]
+pub struct ContactManifolds {
+    manifolds: HashMap<(Entity, Entity), ContactManifold>,
+    expired: Vec<ContactManifold>,
+}
+
+impl ContactManifolds {
+    /// Returns the manifold between the given Entitys, if they are in contact, regardless of the
+    /// order they are given in.
+    pub fn get(&self, a: Entity, b: Entity) -> Option<&ContactManifold> {
+        self.manifolds.get(&(a, b)).or_else(|| self.manifolds.get(&(b, a)))
+    }
+
+    /// Returns an iterator over all current manifolds.
+    pub fn iter(&self) -> impl Iterator<Item = &ContactManifold> {
+        self.manifolds.values()
+    }
+
+    /// Returns a mutable iterator over all current manifolds.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut ContactManifold> {
+        self.manifolds.values_mut()
+    }
+
+    /// Returns an iterator over the manifolds that expired in the current step, in their final
+    /// state.
+    pub fn expired(&self) -> impl Iterator<Item = &ContactManifold> {
+        self.expired.iter()
+    }
+
+    /// Returns the number of current manifolds.
+    pub fn len(&self) -> usize {
+        self.manifolds.len()
+    }
+
+    /// Returns true if there are no current manifolds.
+    pub fn is_empty(&self) -> bool {
+        self.manifolds.is_empty()
+    }
+
+    /// Marks the start of contact generation for a new physics step.
+    pub(crate) fn begin_step(&mut self) {
+        self.expired.clear();
+
+        for manifold in self.manifolds.values_mut() {
+            if manifold.updated {
+                manifold.age += 1;
+            }
+            manifold.updated = false;
+        }
+    }
+
+    /// Adds the given contacts, all between the same pair of Entitys, to the manifold for that
+    /// pair, creating it if necessary. The transform must be that of the first Entity in the
+    /// contacts, and points are matched with those of the previous step within the given
+    /// tolerance.
+    pub(crate) fn update(
+        &mut self,
+        contacts: Vec<Contact>,
+        transform: &PhysTransform,
+        tolerance: f64,
+    ) {
+        let pair = match contacts.first().and_then(Self::calc_pair) {
+            Some(pair) => pair,
+            None => return,
+        };
+
+        self.manifolds.entry(pair)
+            .or_insert_with(|| ContactManifold { pair, points: vec![], age: 0, updated: false })
+            .update(contacts, transform, tolerance);
+    }
+
+    /// Keeps the manifold between the given Entitys, if any, unchanged for the current step, e.g.
+    /// while both are asleep. The Entitys may be given in either order.
+    pub(crate) fn retain(&mut self, a: Entity, b: Entity) {
+        let manifold = match self.manifolds.get_mut(&(a, b)) {
+            Some(manifold) => Some(manifold),
+            None => self.manifolds.get_mut(&(b, a)),
+        };
+
+        if let Some(manifold) = manifold {
+            manifold.updated = true;
+        }
+    }
+
+    /// Expires the manifolds that were not updated in the current step, i.e. those whose Entitys
+    /// are no longer in contact.
+    pub(crate) fn remove_expired(&mut self) {
+        let expired: Vec<(Entity, Entity)> = self.manifolds.iter()
+            .filter(|(_, m)| !m.updated)
+            .map(|(pair, _)| *pair)
+            .collect();
+
+        for pair in expired {
+            if let Some(manifold) = self.manifolds.remove(&pair) {
+                self.expired.push(manifold);
+            }
+        }
+    }
+
+    /// Returns the pair of Entitys involved in the given contact.
+    fn calc_pair(contact: &Contact) -> Option<(Entity, Entity)> {
+        let first = *contact.entities.first()?;
+        let second = contact.entities.get(1).or(contact.boundary.as_ref())?;
+
+        Some((first, *second))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TOLERANCE: f64 = 0.1;
+
+    fn contact(a: Entity, b: Entity, point: DVec3, feature_id: u32) -> Contact {
+        Contact {
+            entities: vec![a, b],
+            boundary: None,
+            feature_id,
+            normal: DVec3::Y,
+            penetration: 0.1,
+            point,
+            relative_points: vec![point, point],
+        }
+    }
+
+    #[test]
+    fn test_update() {
+        let a = Entity::new(1);
+        let b = Entity::new(2);
+        let transform = PhysTransform::from_xyz(0.0, 1.0, 0.0);
+
+        let mut manifolds = ContactManifolds::default();
+
+        manifolds.begin_step();
+        manifolds.update(vec![contact(a, b, DVec3::new(1.0, 0.0, 0.0), 0),
+                              contact(a, b, DVec3::new(-1.0, 0.0, 0.0), 1)], &transform,
+                         TOLERANCE);
+        manifolds.remove_expired();
+        assert_eq!(0, manifolds.expired().count());
+
+        let manifold = manifolds.get(b, a).unwrap();
+        assert_eq!((a, b), manifold.pair());
+        assert_eq!(0, manifold.age());
+        assert_eq!(DVec3::new(1.0, -1.0, 0.0), manifold.points()[0].local_point);
+
+        for (i, point) in manifolds.iter_mut().next().unwrap().points_mut().iter_mut().enumerate() {
+            point.accumulated_impulse = DVec3::new(0.0, i as f64 + 1.0, 0.0);
+        }
+
+        // points that persist keep their impulse, matching by feature id before proximity. New
+        // points start with none.
+        manifolds.begin_step();
+        manifolds.update(vec![contact(a, b, DVec3::new(-0.98, 0.0, 0.0), 0),
+                              contact(a, b, DVec3::new(-1.0, 0.0, 0.0), 1),
+                              contact(a, b, DVec3::new(0.0, 0.0, 0.0), 2)], &transform,
+                         TOLERANCE);
+        manifolds.remove_expired();
+        assert_eq!(0, manifolds.expired().count());
+
+        let manifold = manifolds.get(a, b).unwrap();
+        assert_eq!(1, manifold.age());
+        let impulses: Vec<DVec3> = manifold.points().iter().map(|p| p.accumulated_impulse).collect();
+        assert_eq!(vec![DVec3::ZERO, DVec3::new(0.0, 2.0, 0.0), DVec3::ZERO], impulses);
+        assert_eq!(2.0, manifold.impulse());
+        assert_eq!(DVec3::Y, manifold.normal());
+
+        // the manifold expires once the bodies separate.
+        manifolds.begin_step();
+        manifolds.remove_expired();
+        assert_eq!(1, manifolds.expired().count());
+        assert!(manifolds.is_empty());
+
+        // unless it is retained.
+        manifolds.update(vec![contact(a, b, DVec3::ZERO, 0)], &transform, TOLERANCE);
+        manifolds.begin_step();
+        manifolds.retain(b, a);
+        manifolds.remove_expired();
+        assert_eq!(0, manifolds.expired().count());
+        assert_eq!(1, manifolds.get(a, b).unwrap().points().len());
+        manifolds.begin_step();
+        manifolds.remove_expired();
+
+        // and is forgotten in the following step.
+        manifolds.begin_step();
+        assert_eq!(0, manifolds.expired().count());
+    }
+}
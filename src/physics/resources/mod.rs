@@ -0,0 +1,18 @@
+mod collision_filter;
+mod contact_manifolds;
+mod physics_config;
+mod physics_time;
+mod sensor_overlaps;
+
+pub use collision_filter::{
+    CollisionFilter,
+    PairFilterFn,
+};
+pub use contact_manifolds::{
+    ContactManifold,
+    ContactManifolds,
+    ManifoldPoint,
+};
+pub use physics_config::PhysicsConfig;
+pub use physics_time::PhysicsTime;
+pub use sensor_overlaps::SensorOverlaps;
@@ -0,0 +1,120 @@
+use bevy::math::DVec3;
+
+use crate::{
+    constants,
+    physics::components::PhysicsMaterial,
+};
+
+/// A resource holding the global parameters of the physics simulation. Any changes made at runtime
+/// take effect from the next physics step.
+///
+/// Insert a PhysicsConfig resource before adding the PhysicsPlugin to override the defaults.
+#[derive(Debug, Clone)]
+pub struct PhysicsConfig {
+    /// The proportion of linear velocity retained after one second, used to dampen motion.
+    pub damping_factor: f64,
+    /// The proportion of angular velocity retained after one second, used to dampen rotation.
+    pub angular_damping_factor: f64,
+    /// The material used in collision response for bodies that do not have a PhysicsMaterial.
+    pub default_material: PhysicsMaterial,
+    /// The value of 'g' used by Gravity components that do not specify their own.
+    pub gravity: DVec3,
+    /// The gravitational constant, scaling the attraction of every PointAttractor.
+    pub gravitational_constant: f64,
+    /// The maximum number of velocity iterations made by the contact solver in each step, per
+    /// contact.
+    pub velocity_iterations: u32,
+    /// The maximum number of position iterations made by the contact solver in each step, per
+    /// contact.
+    pub position_iterations: u32,
+    /// The contact solver stops iterating over velocities once no contact's normal velocity is
+    /// further than this from its target.
+    pub velocity_epsilon: f64,
+    /// The contact solver stops iterating over positions once no contact's penetration exceeds
+    /// this.
+    pub position_epsilon: f64,
+    /// The proportion of the impulse applied at a contact in the previous step that is applied up
+    /// front when the contact persists. The impulse is kept with the contact point in its
+    /// ContactManifold. 0.0 disables warm starting.
+    pub warm_start_factor: f64,
+    /// Contact points within this distance of a point from the previous step, in the local
+    /// space of the first body, are matched with it to carry over its accumulated impulse.
+    pub contact_match_tolerance: f64,
+    /// The number of times the solver sweeps over every joint, for both velocities and positions,
+    /// in each step.
+    pub joint_iterations: u32,
+    /// When false, bodies never go to sleep.
+    pub sleep_enabled: bool,
+    /// Bodies with a speed below this threshold are considered to be at rest, for the purpose of
+    /// sleeping.
+    pub sleep_linear_threshold: f64,
+    /// Bodies with an angular speed (in radians per second) below this threshold are considered
+    /// to be at rest, for the purpose of sleeping.
+    pub sleep_angular_threshold: f64,
+    /// The time, in seconds, that every body in an island must be at rest before the island is put
+    /// to sleep.
+    pub time_to_sleep: f64,
+    /// The depth to which a body with a Ccd component is allowed to move into the surface it hits
+    /// when its motion is clamped, so that a contact is generated at the point of impact.
+    pub ccd_allowed_penetration: f64,
+    /// Bodies with a squared speed below this threshold are brought to rest.
+    pub low_velocity_threshold: f64,
+    /// Angular inertia below this threshold is ignored when resolving interpenetration.
+    pub low_rotation_threshold: f64,
+    /// Limits the rotation applied when resolving interpenetration, as a proportion of the
+    /// distance between the contact point and the centre of the body.
+    pub angular_limit: f64,
+    /// The maximum depth of the OctTree used for broad-phase collision detection.
+    pub max_oct_tree_depth: i32,
+    /// The centre of the volume covered by the OctTree.
+    pub play_area_centre: DVec3,
+    /// The extents (half-widths) of the volume covered by the OctTree.
+    pub play_area_extents: DVec3,
+    /// When true, the play area is expanded to enclose any body that leaves it. The expanded
+    /// bounds are held by the OctTree; the play area fields above are left as they are, and
+    /// changing them rebuilds the OctTree with the new bounds.
+    pub auto_expand_play_area: bool,
+    /// The factor by which the play area's extents are scaled beyond the bodies it must enclose
+    /// when it is expanded, leaving room for further movement.
+    pub play_area_growth_factor: f64,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            damping_factor: constants::DAMPING_FACTOR,
+            angular_damping_factor: constants::ANGULAR_DAMPING_FACTOR,
+            default_material: PhysicsMaterial::default(),
+            gravity: *constants::DEFAULT_GRAVITY,
+            gravitational_constant: constants::GRAVITATIONAL_CONSTANT,
+            velocity_iterations: constants::VELOCITY_ITERATIONS,
+            position_iterations: constants::POSITION_ITERATIONS,
+            velocity_epsilon: constants::VELOCITY_EPSILON,
+            position_epsilon: constants::POSITION_EPSILON,
+            warm_start_factor: constants::WARM_START_FACTOR,
+            contact_match_tolerance: constants::CONTACT_MATCH_TOLERANCE,
+            joint_iterations: constants::JOINT_ITERATIONS,
+            sleep_enabled: true,
+            sleep_linear_threshold: constants::SLEEP_LINEAR_THRESHOLD,
+            sleep_angular_threshold: constants::SLEEP_ANGULAR_THRESHOLD,
+            time_to_sleep: constants::TIME_TO_SLEEP,
+            ccd_allowed_penetration: constants::CCD_ALLOWED_PENETRATION,
+            low_velocity_threshold: constants::LOW_VELOCITY_THRESHOLD,
+            low_rotation_threshold: constants::LOW_ROTATION_THRESHOLD,
+            angular_limit: constants::ANGULAR_LIMIT,
+            max_oct_tree_depth: constants::MAX_OCT_TREE_DEPTH,
+            play_area_centre: DVec3::new(
+                constants::PLAY_AREA_CENTRE_X,
+                constants::PLAY_AREA_CENTRE_Y,
+                constants::PLAY_AREA_CENTRE_Z,
+            ),
+            play_area_extents: DVec3::new(
+                constants::PLAY_AREA_EXTENT_X,
+                constants::PLAY_AREA_EXTENT_Y,
+                constants::PLAY_AREA_EXTENT_Z,
+            ),
+            auto_expand_play_area: false,
+            play_area_growth_factor: constants::PLAY_AREA_GROWTH_FACTOR,
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use bevy::ecs::schedule::ShouldRun;
+
+use crate::constants;
+
+/// A resource that drives the physics simulation at a fixed timestep, independent of the frame
+/// rate.
+///
+/// Frame time is added to an accumulator which is then consumed in fixed size steps. The number
+/// of steps taken in a single frame is capped to prevent a 'spiral of death' after a frame hitch,
+/// with any excess time being discarded. The fraction of a step left in the accumulator is exposed
+/// as an interpolation alpha that can be used to blend between the previous and current physics
+/// states when rendering.
+#[derive(Debug)]
+pub struct PhysicsTime {
+    step: f64,
+    max_substeps: u32,
+    accumulator: f64,
+    alpha: f64,
+    substeps: u32,
+    looping: bool,
+}
+
+impl PhysicsTime {
+    /// Creates a new PhysicsTime with the given timestep (in seconds) and a cap on the number of
+    /// steps that can be taken in a single frame.
+    pub fn new(step: f64, max_substeps: u32) -> Self {
+        Self {
+            step,
+            max_substeps,
+            accumulator: 0.0,
+            alpha: 0.0,
+            substeps: 0,
+            looping: false,
+        }
+    }
+
+    /// Creates a new PhysicsTime that steps the given number of times per second, with a cap on the
+    /// number of steps that can be taken in a single frame.
+    pub fn from_steps_per_second(rate: f64, max_substeps: u32) -> Self {
+        Self::new(1.0 / rate, max_substeps)
+    }
+
+    /// Returns the duration of a single physics step in seconds.
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    /// Sets the duration of a single physics step in seconds.
+    pub fn set_step(&mut self, step: f64) {
+        self.step = step;
+    }
+
+    /// Returns the maximum number of physics steps that can be taken in a single frame.
+    pub fn max_substeps(&self) -> u32 {
+        self.max_substeps
+    }
+
+    /// Sets the maximum number of physics steps that can be taken in a single frame.
+    pub fn set_max_substeps(&mut self, max_substeps: u32) {
+        self.max_substeps = max_substeps;
+    }
+
+    /// Returns the frame time that has not yet been consumed by a physics step.
+    pub fn accumulator(&self) -> f64 {
+        self.accumulator
+    }
+
+    /// Returns the fraction of a step (0.0 to 1.0) left in the accumulator after the most recent
+    /// physics steps. Used to interpolate between the previous and current physics states.
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    /// Returns the number of physics steps taken so far in the current frame.
+    pub fn substeps(&self) -> u32 {
+        self.substeps
+    }
+
+    /// Advances the accumulator by the given frame time (on the first call in a frame only) and
+    /// returns whether another physics step should be run.
+    pub(crate) fn update(&mut self, delta_seconds: f64) -> ShouldRun {
+        if !self.looping {
+            self.accumulator += delta_seconds;
+            self.substeps = 0;
+        }
+
+        if self.accumulator >= self.step && self.substeps < self.max_substeps {
+            self.accumulator -= self.step;
+            self.substeps += 1;
+            self.looping = true;
+            ShouldRun::YesAndCheckAgain
+        } else {
+            // Substep cap reached, discard the remaining whole steps so that the simulation does
+            // not fall further and further behind.
+            if self.accumulator >= self.step {
+                self.accumulator %= self.step;
+            }
+            self.alpha = self.accumulator / self.step;
+            self.looping = false;
+            ShouldRun::No
+        }
+    }
+}
+
+impl Default for PhysicsTime {
+    fn default() -> Self {
+        Self::new(constants::DEFAULT_TIMESTEP, constants::DEFAULT_MAX_SUBSTEPS)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EPSILON: f64 = 0.000001;
+
+    /// Runs the update loop as the physics schedule would, returning the number of steps taken.
+    fn run_frame(time: &mut PhysicsTime, delta_seconds: f64) -> u32 {
+        let mut steps = 0;
+        while let ShouldRun::YesAndCheckAgain = time.update(delta_seconds) {
+            steps += 1;
+        }
+        steps
+    }
+
+    #[test]
+    fn test_update() {
+        let mut time = PhysicsTime::new(0.1, 5);
+
+        // less than a step, no physics update but the alpha reflects the partial step.
+        assert_eq!(0, run_frame(&mut time, 0.05));
+        assert!((time.alpha() - 0.5).abs() < EPSILON);
+
+        // remainder carried over from the previous frame.
+        assert_eq!(1, run_frame(&mut time, 0.08));
+        assert!((time.accumulator() - 0.03).abs() < EPSILON);
+        assert!((time.alpha() - 0.3).abs() < EPSILON);
+
+        // multiple steps in a single frame.
+        assert_eq!(3, run_frame(&mut time, 0.3));
+        assert_eq!(3, time.substeps());
+    }
+
+    #[test]
+    fn test_update_substep_cap() {
+        let mut time = PhysicsTime::new(0.1, 5);
+
+        // a large frame hitch is capped and the excess time discarded.
+        assert_eq!(5, run_frame(&mut time, 2.05));
+        assert!(time.accumulator() < time.step());
+        assert!((time.alpha() - 0.5).abs() < EPSILON);
+
+        // the following frame is unaffected by the hitch.
+        assert_eq!(0, run_frame(&mut time, 0.01));
+    }
+}
@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+/// A resource holding the pairs of Sensor and entity that currently overlap, along with those that
+/// overlapped in the previous physics step.
+#[derive(Debug, Default)]
+pub struct SensorOverlaps {
+    current: HashSet<(Entity, Entity)>,
+    previous: HashSet<(Entity, Entity)>,
+}
+
+impl SensorOverlaps {
+    /// Returns true if the given entity currently overlaps the given Sensor.
+    pub fn contains(&self, sensor: Entity, entity: Entity) -> bool {
+        self.current.contains(&(sensor, entity))
+    }
+
+    /// Returns an iterator over the current (sensor, entity) overlaps.
+    pub fn iter(&self) -> impl Iterator<Item = &(Entity, Entity)> {
+        self.current.iter()
+    }
+
+    /// Returns an iterator over the (sensor, entity) overlaps that began in the current step.
+    pub fn entered(&self) -> impl Iterator<Item = &(Entity, Entity)> {
+        self.current.difference(&self.previous)
+    }
+
+    /// Returns an iterator over the (sensor, entity) overlaps that ended in the current step.
+    pub fn exited(&self) -> impl Iterator<Item = &(Entity, Entity)> {
+        self.previous.difference(&self.current)
+    }
+
+    /// Marks the start of overlap detection for a new physics step.
+    pub(crate) fn begin_step(&mut self) {
+        self.previous = std::mem::take(&mut self.current);
+    }
+
+    /// Records an overlap between the given Sensor and entity in the current step.
+    pub(crate) fn insert(&mut self, sensor: Entity, entity: Entity) {
+        self.current.insert((sensor, entity));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_entered_and_exited() {
+        let sensor = Entity::new(1);
+        let a = Entity::new(2);
+        let b = Entity::new(3);
+
+        let mut overlaps = SensorOverlaps::default();
+
+        overlaps.begin_step();
+        overlaps.insert(sensor, a);
+        assert!(overlaps.contains(sensor, a));
+        assert_eq!(vec![&(sensor, a)], overlaps.entered().collect::<Vec<_>>());
+        assert_eq!(0, overlaps.exited().count());
+
+        overlaps.begin_step();
+        overlaps.insert(sensor, a);
+        overlaps.insert(sensor, b);
+        assert_eq!(vec![&(sensor, b)], overlaps.entered().collect::<Vec<_>>());
+        assert_eq!(0, overlaps.exited().count());
+
+        overlaps.begin_step();
+        overlaps.insert(sensor, b);
+        assert!(!overlaps.contains(sensor, a));
+        assert_eq!(0, overlaps.entered().count());
+        assert_eq!(vec![&(sensor, a)], overlaps.exited().collect::<Vec<_>>());
+    }
+}
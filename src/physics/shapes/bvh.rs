@@ -0,0 +1,211 @@
+use bevy::math::DVec3;
+
+use crate::physics::shapes::ray_cast::calc_ray_and_aabb;
+
+// The maximum number of items held in a single leaf node.
+const MAX_LEAF_ITEMS: usize = 4;
+
+/// A bounding volume hierarchy of axis-aligned boxes, used to quickly find the items of a static
+/// shape, such as the triangles of a TriMesh, near to a region of space.
+#[derive(Debug, Clone)]
+pub(crate) struct Bvh {
+    nodes: Vec<BvhNode>,
+    bounds: Vec<(DVec3, DVec3)>,
+}
+
+#[derive(Debug, Clone)]
+struct BvhNode {
+    min: DVec3,
+    max: DVec3,
+    contents: BvhContents,
+}
+
+#[derive(Debug, Clone)]
+enum BvhContents {
+    /// The indices of the items within the node.
+    Leaf(Vec<usize>),
+    /// The indices of the two child nodes.
+    Branch(usize, usize),
+}
+
+impl Bvh {
+    /// Builds a new Bvh over items with the given bounding boxes, given as (min, max) corners. The
+    /// items are identified by their index in the slice.
+    pub fn new(bounds: &[(DVec3, DVec3)]) -> Self {
+        let mut bvh = Self { nodes: vec![], bounds: bounds.to_vec() };
+
+        if !bounds.is_empty() {
+            let mut items: Vec<usize> = (0..bounds.len()).collect();
+            bvh.build(bounds, &mut items);
+        }
+
+        bvh
+    }
+
+    /// Returns the indices of all items whose bounding boxes overlap the box with the given
+    /// minimum and maximum corners.
+    pub fn query(&self, min: DVec3, max: DVec3) -> Vec<usize> {
+        let mut result = vec![];
+        if self.nodes.is_empty() { return result; }
+
+        let mut stack = vec![0];
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+            if !overlaps(node.min, node.max, min, max) { continue; }
+
+            match &node.contents {
+                BvhContents::Leaf(items) => {
+                    result.extend(items.iter()
+                        .filter(|i| overlaps(self.bounds[**i].0, self.bounds[**i].1, min, max)));
+                },
+                BvhContents::Branch(left, right) => {
+                    stack.push(*left);
+                    stack.push(*right);
+                },
+            }
+        }
+
+        result
+    }
+
+    /// Returns the indices of all items whose bounding boxes are hit by the ray with the given
+    /// origin and direction, within the given maximum time of impact.
+    pub fn query_ray(&self, origin: DVec3, direction: DVec3, max_toi: f64) -> Vec<usize> {
+        let hits = |min: DVec3, max: DVec3| {
+            calc_ray_and_aabb(origin, direction, min, max, max_toi).is_some()
+        };
+
+        let mut result = vec![];
+        if self.nodes.is_empty() { return result; }
+
+        let mut stack = vec![0];
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+            if !hits(node.min, node.max) { continue; }
+
+            match &node.contents {
+                BvhContents::Leaf(items) => {
+                    result.extend(items.iter()
+                        .filter(|i| hits(self.bounds[**i].0, self.bounds[**i].1)));
+                },
+                BvhContents::Branch(left, right) => {
+                    stack.push(*left);
+                    stack.push(*right);
+                },
+            }
+        }
+
+        result
+    }
+
+    /// Returns the index of the item nearest to the given point, along with its distance, where
+    /// the given function measures the distance from the point to an item. Branches further away
+    /// than the nearest item found so far are not searched.
+    pub fn nearest(&self, point: DVec3, mut distance: impl FnMut(usize) -> f64) -> Option<(usize, f64)> {
+        let mut best: Option<(usize, f64)> = None;
+        if self.nodes.is_empty() { return best; }
+
+        let mut stack = vec![0];
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+            let node_distance = (point - point.clamp(node.min, node.max)).length();
+
+            match best {
+                Some((_, d)) if node_distance >= d => continue,
+                _ => (),
+            }
+
+            match &node.contents {
+                BvhContents::Leaf(items) => {
+                    for item in items {
+                        let d = distance(*item);
+                        match best {
+                            Some((_, best_d)) if best_d <= d => (),
+                            _ => best = Some((*item, d)),
+                        }
+                    }
+                },
+                BvhContents::Branch(left, right) => {
+                    stack.push(*left);
+                    stack.push(*right);
+                },
+            }
+        }
+
+        best
+    }
+
+    /// Recursively builds the nodes enclosing the given items, returning the index of the node at
+    /// the root of this part of the tree.
+    fn build(&mut self, bounds: &[(DVec3, DVec3)], items: &mut [usize]) -> usize {
+        let (min, max) = items.iter()
+            .fold((DVec3::splat(f64::INFINITY), DVec3::splat(f64::NEG_INFINITY)),
+                  |(min, max), i| (min.min(bounds[*i].0), max.max(bounds[*i].1)));
+
+        let index = self.nodes.len();
+
+        if items.len() <= MAX_LEAF_ITEMS {
+            self.nodes.push(BvhNode { min, max, contents: BvhContents::Leaf(items.to_vec()) });
+            return index;
+        }
+
+        // split the items at the median of their centres along the longest axis.
+        let size = max - min;
+        let axis = if size.x >= size.y && size.x >= size.z {
+            0
+        } else if size.y >= size.z {
+            1
+        } else {
+            2
+        };
+        let centre = |i: &usize| (bounds[*i].0 + bounds[*i].1)[axis];
+        items.sort_by(|a, b| centre(a).partial_cmp(&centre(b)).unwrap());
+
+        // reserve this node's place before its children are added.
+        self.nodes.push(BvhNode { min, max, contents: BvhContents::Leaf(vec![]) });
+
+        let (left_items, right_items) = items.split_at_mut(items.len() / 2);
+        let left = self.build(bounds, left_items);
+        let right = self.build(bounds, right_items);
+        self.nodes[index].contents = BvhContents::Branch(left, right);
+
+        index
+    }
+}
+
+/// Returns true if the two boxes, with the given minimum and maximum corners, overlap.
+fn overlaps(min1: DVec3, max1: DVec3, min2: DVec3, max2: DVec3) -> bool {
+    min1.x <= max2.x && max1.x >= min2.x
+        && min1.y <= max2.y && max1.y >= min2.y
+        && min1.z <= max2.z && max1.z >= min2.z
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_query_and_nearest() {
+        // a row of unit boxes along the x-axis.
+        let bounds: Vec<(DVec3, DVec3)> = (0..20)
+            .map(|i| {
+                let min = DVec3::new(i as f64 * 2.0, 0.0, 0.0);
+                (min, min + DVec3::ONE)
+            })
+            .collect();
+        let bvh = Bvh::new(&bounds);
+
+        let mut found = bvh.query(DVec3::new(4.5, 0.5, 0.5), DVec3::new(8.5, 0.5, 0.5));
+        found.sort_unstable();
+        assert_eq!(vec![2, 3, 4], found);
+
+        assert!(bvh.query(DVec3::new(1.5, 0.5, 0.5), DVec3::new(1.6, 0.5, 0.5)).is_empty());
+
+        let point = DVec3::new(13.0, 3.0, 0.5);
+        let (nearest, distance) = bvh.nearest(point, |i| {
+            (point - point.clamp(bounds[i].0, bounds[i].1)).length()
+        }).unwrap();
+        assert_eq!(6, nearest);
+        assert_eq!(2.0, distance);
+    }
+}
@@ -0,0 +1,141 @@
+use bevy::math::DVec3;
+
+use crate::{
+    physics::shapes::{
+        Collidable,
+        CollisionPrimative,
+        RayCast,
+        Sphere,
+        SupportMap,
+    },
+    physics::components::PhysTransform,
+};
+
+/// A capsule described by its radius and the half-length of the line segment between the centres
+/// of its hemispherical ends. The segment is aligned with the y-axis in local body space, with the
+/// origin at the centre of the capsule.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Capsule {
+    radius: f64,
+    half_height: f64,
+    bounding_sphere: Sphere,
+}
+
+impl Capsule {
+    /// Creates a new Capsule with the given radius and half-length of its central segment.
+    pub fn new(radius: f64, half_height: f64) -> Self {
+        Self {
+            radius,
+            half_height,
+            bounding_sphere: Sphere::new(radius + half_height),
+        }
+    }
+
+    /// Returns the radius of the Capsule.
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    /// Returns the half-length of the Capsule's central segment, excluding its hemispherical ends.
+    pub fn half_height(&self) -> f64 {
+        self.half_height
+    }
+
+    /// Returns the end points of the Capsule's central segment in global coords.
+    pub fn segment(&self, transform: &PhysTransform) -> (DVec3, DVec3) {
+        let half_segment = transform.axis(1) * self.half_height;
+        let centre = transform.translation();
+
+        (centre - half_segment, centre + half_segment)
+    }
+
+    /// Projects the half-size of the capsule with the given transform onto the given axis.
+    pub fn project_onto_axis(&self, transform: &PhysTransform, axis: DVec3) -> f64 {
+        self.half_height * axis.dot(transform.axis(1)).abs() + self.radius
+    }
+}
+
+impl CollisionPrimative for Capsule {
+    /// Returns the Sphere that shares a centre point with the Capsule and completely encloses it.
+    fn bounding_sphere(&self) -> &Sphere {
+        &self.bounding_sphere
+    }
+
+    fn as_support_map(&self) -> Option<&dyn SupportMap> {
+        Some(self)
+    }
+
+    fn as_ray_cast(&self) -> Option<&dyn RayCast> {
+        Some(self)
+    }
+}
+
+impl SupportMap for Capsule {
+    fn support_point(&self, transform: &PhysTransform, direction: DVec3) -> DVec3 {
+        let (start, end) = self.segment(transform);
+        let centre = if direction.dot(end - start) >= 0.0 { end } else { start };
+
+        centre + direction.normalize_or_zero() * self.radius
+    }
+}
+
+impl Collidable for Capsule {
+    /// Calculates and returns the closest point on the surface of the Capsule, with the given
+    /// transform, to the given target point.
+    fn closest_point_to(&self, transform: &PhysTransform, target: DVec3) -> DVec3 {
+        // The calculation is made by finding the closest point on the central segment, then moving
+        // out towards the target by the radius.
+        let (start, end) = self.segment(transform);
+        let segment_point = calc_closest_point_on_segment(start, end, target);
+
+        segment_point + (target - segment_point).normalize_or_zero() * self.radius
+    }
+
+    /// Calculates and returns the shortest distance between the Capsule, with the given transform,
+    /// and the target point in global coords. Will be negative if the target point is inside the
+    /// Capsule.
+    fn shortest_distance_to(&self, transform: &PhysTransform, target: DVec3) -> f64 {
+        let (start, end) = self.segment(transform);
+
+        (target - calc_closest_point_on_segment(start, end, target)).length() - self.radius
+    }
+}
+
+/// Returns the point on the line segment between start and end that is closest to the given point.
+pub(crate) fn calc_closest_point_on_segment(start: DVec3, end: DVec3, point: DVec3) -> DVec3 {
+    let d = end - start;
+    let length_sq = d.length_squared();
+
+    if length_sq <= 0.0 {
+        return start;
+    }
+
+    let t = ((point - start).dot(d) / length_sq).clamp(0.0, 1.0);
+
+    start + d * t
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_closest_point_to() {
+        let c = Capsule::new(1.0, 2.0);
+        let transform = PhysTransform::from_xyz(1.0, 0.0, 0.0);
+
+        // beside the central segment.
+        let target = DVec3::new(4.0, 1.0, 0.0);
+        assert_eq!(DVec3::new(2.0, 1.0, 0.0), c.closest_point_to(&transform, target));
+        assert_eq!(2.0, c.shortest_distance_to(&transform, target));
+
+        // beyond the end of the segment.
+        let target = DVec3::new(1.0, 5.0, 0.0);
+        assert_eq!(DVec3::new(1.0, 3.0, 0.0), c.closest_point_to(&transform, target));
+        assert_eq!(2.0, c.shortest_distance_to(&transform, target));
+
+        // inside.
+        let target = DVec3::new(1.5, 0.0, 0.0);
+        assert_eq!(-0.5, c.shortest_distance_to(&transform, target));
+    }
+}
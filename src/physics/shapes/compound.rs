@@ -0,0 +1,102 @@
+use bevy::math::DVec3;
+
+use crate::{
+    physics::shapes::{
+        CollisionPrimative,
+        RayCast,
+        Sphere,
+    },
+    physics::components::PhysTransform,
+};
+
+/// A shape made up of several child shapes, each placed in the local body space of the compound
+/// by its own PhysTransform. Allows bodies such as tables or dumbbells to be built from the other
+/// primatives. Contacts are generated separately for each child.
+#[derive(Debug)]
+pub struct Compound {
+    children: Vec<(PhysTransform, Box<dyn CollisionPrimative>)>,
+    bounding_sphere: Sphere,
+}
+
+impl Compound {
+    /// Creates a new Compound with no children.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the given child shape, placed by the given transform relative to the Compound, and
+    /// returns the Compound.
+    pub fn with_child<T: CollisionPrimative>(mut self, transform: PhysTransform, shape: T) -> Self {
+        self.children.push((transform, Box::new(shape)));
+        self.update_bounding_sphere();
+        self
+    }
+
+    /// Returns the child shapes along with their transforms relative to the Compound.
+    pub fn children(&self) -> &[(PhysTransform, Box<dyn CollisionPrimative>)] {
+        &self.children
+    }
+
+    /// Moves every child by the given offset relative to the Compound, e.g. to place the centre
+    /// of mass at the origin.
+    pub(crate) fn offset_children(&mut self, offset: DVec3) {
+        for (transform, _) in self.children.iter_mut() {
+            transform.translation += offset;
+            transform.update();
+        }
+        self.update_bounding_sphere();
+    }
+
+    /// Recalculates the Sphere, centred on the origin, that encloses the bounding spheres of all
+    /// of the children.
+    fn update_bounding_sphere(&mut self) {
+        let radius = self.children.iter()
+            .map(|(transform, shape)| transform.translation().length() + shape.bounding_sphere().radius())
+            .fold(0.0, f64::max);
+
+        self.bounding_sphere = Sphere::new(radius);
+    }
+}
+
+impl Default for Compound {
+    fn default() -> Self {
+        Self {
+            children: vec![],
+            bounding_sphere: Sphere::new(0.0),
+        }
+    }
+}
+
+impl CollisionPrimative for Compound {
+    /// Returns the Sphere that shares a centre point with the Compound and completely encloses it.
+    fn bounding_sphere(&self) -> &Sphere {
+        &self.bounding_sphere
+    }
+
+    fn as_ray_cast(&self) -> Option<&dyn RayCast> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::physics::shapes::Cuboid;
+
+    #[test]
+    fn test_bounding_sphere() {
+        // a dumbbell.
+        let mut c = Compound::new()
+            .with_child(PhysTransform::from_xyz(-2.0, 0.0, 0.0), Sphere::new(1.0))
+            .with_child(PhysTransform::IDENTITY, Cuboid::new(DVec3::new(2.0, 0.2, 0.2)))
+            .with_child(PhysTransform::from_xyz(2.0, 0.0, 0.0), Sphere::new(0.5));
+
+        assert_eq!(3, c.children().len());
+        assert_eq!(3.0, c.bounding_sphere().radius());
+
+        c.offset_children(DVec3::new(1.0, 0.0, 0.0));
+        assert_eq!(3.5, c.bounding_sphere().radius());
+        assert_eq!(DVec3::new(-1.0, 0.0, 0.0), c.children()[0].0.translation());
+    }
+}
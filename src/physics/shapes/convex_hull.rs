@@ -0,0 +1,143 @@
+use bevy::math::DVec3;
+
+use crate::{
+    physics::shapes::{
+        gjk,
+        Collidable,
+        CollisionPrimative,
+        RayCast,
+        Sphere,
+        SupportMap,
+    },
+    physics::components::PhysTransform,
+};
+
+/// The convex hull of a set of points given in local body coords. The points need not all be
+/// vertices of the hull; interior points are simply never chosen as support points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvexHull {
+    points: Vec<DVec3>,
+    bounding_sphere: Sphere,
+}
+
+impl ConvexHull {
+    /// Creates a new ConvexHull enclosing the given points, in local body coords.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if no points are given.
+    pub fn new(points: Vec<DVec3>) -> Self {
+        assert!(!points.is_empty(), "A ConvexHull requires at least one point!");
+
+        let radius = points.iter()
+            .map(|p| p.length())
+            .fold(0.0, f64::max);
+
+        Self {
+            points,
+            bounding_sphere: Sphere::new(radius),
+        }
+    }
+
+    /// Returns the points defining the hull in local body coords.
+    pub fn points(&self) -> &[DVec3] {
+        &self.points
+    }
+
+    /// Returns the points defining the hull in global coords.
+    pub fn vertices(&self, transform: &PhysTransform) -> Vec<DVec3> {
+        self.points.iter()
+            .map(|p| transform.get_point_in_global_space(*p))
+            .collect()
+    }
+}
+
+impl CollisionPrimative for ConvexHull {
+    /// Returns the Sphere that shares a centre point with the ConvexHull and completely encloses
+    /// it.
+    fn bounding_sphere(&self) -> &Sphere {
+        &self.bounding_sphere
+    }
+
+    fn as_support_map(&self) -> Option<&dyn SupportMap> {
+        Some(self)
+    }
+
+    fn as_ray_cast(&self) -> Option<&dyn RayCast> {
+        Some(self)
+    }
+}
+
+impl SupportMap for ConvexHull {
+    /// Returns the point of the ConvexHull that is furthest in the given direction.
+    fn support_point(&self, transform: &PhysTransform, direction: DVec3) -> DVec3 {
+        let local_direction = transform.get_direction_in_local_space(direction);
+
+        let point = self.points.iter()
+            .copied()
+            .max_by(|a, b| a.dot(local_direction).partial_cmp(&b.dot(local_direction)).unwrap())
+            .unwrap();
+
+        transform.get_point_in_global_space(point)
+    }
+}
+
+impl Collidable for ConvexHull {
+    /// Calculates and returns the closest point on the ConvexHull, with the given transform, to the
+    /// given target point. Targets inside the ConvexHull are returned unchanged.
+    fn closest_point_to(&self, transform: &PhysTransform, target: DVec3) -> DVec3 {
+        gjk::calc_closest_point(self, transform, target).unwrap_or(target)
+    }
+
+    /// Calculates and returns the shortest distance between the ConvexHull, with the given
+    /// transform, and the target point in global coords.
+    fn shortest_distance_to(&self, transform: &PhysTransform, target: DVec3) -> f64 {
+        (target - self.closest_point_to(transform, target)).length()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EPSILON: f64 = 0.0001;
+
+    fn tetrahedron() -> ConvexHull {
+        ConvexHull::new(vec![
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(2.0, 0.0, 0.0),
+            DVec3::new(0.0, 2.0, 0.0),
+            DVec3::new(0.0, 0.0, 2.0),
+        ])
+    }
+
+    #[test]
+    fn test_support_point() {
+        let hull = tetrahedron();
+        let transform = PhysTransform::from_xyz(1.0, 0.0, 0.0);
+
+        assert_eq!(DVec3::new(3.0, 0.0, 0.0), hull.support_point(&transform, DVec3::X));
+        assert_eq!(DVec3::new(1.0, 2.0, 0.0), hull.support_point(&transform, DVec3::Y));
+        assert_eq!(2.0, hull.bounding_sphere().radius());
+    }
+
+    #[test]
+    fn test_closest_point_to() {
+        let hull = tetrahedron();
+        let transform = PhysTransform::IDENTITY;
+
+        // below the base.
+        let target = DVec3::new(0.5, -2.0, 0.5);
+        assert!((DVec3::new(0.5, 0.0, 0.5) - hull.closest_point_to(&transform, target)).length() < EPSILON);
+        assert!((hull.shortest_distance_to(&transform, target) - 2.0).abs() < EPSILON);
+
+        // beyond the sloping face.
+        let target = DVec3::new(2.0, 2.0, 2.0);
+        let expected = DVec3::new(2.0, 2.0, 2.0) / 3.0;
+        assert!((expected - hull.closest_point_to(&transform, target)).length() < EPSILON);
+
+        // inside.
+        let target = DVec3::new(0.2, 0.2, 0.2);
+        assert_eq!(target, hull.closest_point_to(&transform, target));
+    }
+}
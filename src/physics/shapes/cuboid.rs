@@ -4,7 +4,9 @@ use crate::{
     physics::shapes::{
         Collidable,
         CollisionPrimative, 
+        RayCast,
         Sphere,
+        SupportMap,
     },
     physics::components::PhysTransform,
 };
@@ -65,6 +67,28 @@ impl CollisionPrimative for Cuboid {
     fn bounding_sphere(&self) -> &Sphere {
         &self.bounding_sphere
     }
+
+    fn as_support_map(&self) -> Option<&dyn SupportMap> {
+        Some(self)
+    }
+
+    fn as_ray_cast(&self) -> Option<&dyn RayCast> {
+        Some(self)
+    }
+}
+
+impl SupportMap for Cuboid {
+    /// Returns the vertex of the Cuboid that is furthest in the given direction.
+    fn support_point(&self, transform: &PhysTransform, direction: DVec3) -> DVec3 {
+        let mut vertex = self.extents;
+        for i in 0..3 {
+            if transform.axis(i).dot(direction) < 0.0 {
+                vertex[i] = -vertex[i];
+            }
+        }
+
+        transform.get_point_in_global_space(vertex)
+    }
 }
 
 impl Collidable for Cuboid {
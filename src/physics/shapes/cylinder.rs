@@ -0,0 +1,148 @@
+use bevy::math::DVec3;
+
+use crate::{
+    physics::shapes::{
+        Collidable,
+        CollisionPrimative,
+        RayCast,
+        Sphere,
+        SupportMap,
+    },
+    physics::components::PhysTransform,
+};
+
+/// A solid cylinder described by its radius and half-height. The cylinder is aligned with the
+/// y-axis in local body space, with the origin at the centre of the cylinder.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Cylinder {
+    radius: f64,
+    half_height: f64,
+    bounding_sphere: Sphere,
+}
+
+impl Cylinder {
+    /// Creates a new Cylinder with the given radius and half-height.
+    pub fn new(radius: f64, half_height: f64) -> Self {
+        Self {
+            radius,
+            half_height,
+            bounding_sphere: Sphere::new((radius * radius + half_height * half_height).sqrt()),
+        }
+    }
+
+    /// Returns the radius of the Cylinder.
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    /// Returns the half-height of the Cylinder.
+    pub fn half_height(&self) -> f64 {
+        self.half_height
+    }
+
+    /// Projects the half-size of the cylinder with the given transform onto the given axis.
+    pub fn project_onto_axis(&self, transform: &PhysTransform, axis: DVec3) -> f64 {
+        let axis = axis.normalize();
+        let cos = axis.dot(transform.axis(1)).abs();
+        let sin = (1.0 - cos * cos).max(0.0).sqrt();
+
+        self.half_height * cos + self.radius * sin
+    }
+}
+
+impl CollisionPrimative for Cylinder {
+    /// Returns the Sphere that shares a centre point with the Cylinder and completely encloses it.
+    fn bounding_sphere(&self) -> &Sphere {
+        &self.bounding_sphere
+    }
+
+    fn as_support_map(&self) -> Option<&dyn SupportMap> {
+        Some(self)
+    }
+
+    fn as_ray_cast(&self) -> Option<&dyn RayCast> {
+        Some(self)
+    }
+}
+
+impl SupportMap for Cylinder {
+    /// Where a whole cap or edge is furthest in the given direction, the point closest to its
+    /// centre is chosen.
+    fn support_point(&self, transform: &PhysTransform, direction: DVec3) -> DVec3 {
+        let axis = transform.axis(1);
+        let along = direction.dot(axis);
+        let radial = (direction - axis * along).normalize_or_zero();
+
+        let cap = if along > 0.0 {
+            self.half_height
+        } else if along < 0.0 {
+            -self.half_height
+        } else {
+            0.0
+        };
+
+        transform.translation() + axis * cap + radial * self.radius
+    }
+}
+
+impl Collidable for Cylinder {
+    /// Calculates and returns the closest point on the Cylinder, with the given transform, to the
+    /// given target point. Targets inside the Cylinder are returned unchanged.
+    fn closest_point_to(&self, transform: &PhysTransform, target: DVec3) -> DVec3 {
+        // The calculation is made by clamping the target to the radius and to the height of the
+        // Cylinder in local coords.
+        let target_local = transform.get_point_in_local_space(target);
+
+        let mut radial = DVec3::new(target_local.x, 0.0, target_local.z);
+        if radial.length_squared() > self.radius * self.radius {
+            radial = radial.normalize() * self.radius;
+        }
+        let y = target_local.y.clamp(-self.half_height, self.half_height);
+
+        transform.get_point_in_global_space(DVec3::new(radial.x, y, radial.z))
+    }
+
+    /// Calculates and returns the shortest distance between the Cylinder, with the given
+    /// transform, and the target point in global coords.
+    fn shortest_distance_to(&self, transform: &PhysTransform, target: DVec3) -> f64 {
+        (target - self.closest_point_to(transform, target)).length()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EPSILON: f64 = 0.000001;
+
+    #[test]
+    fn test_closest_point_to() {
+        let c = Cylinder::new(1.0, 2.0);
+        let transform = PhysTransform::from_xyz(0.0, 1.0, 0.0);
+
+        // beside the curved face.
+        let target = DVec3::new(0.0, 2.0, 3.0);
+        assert!((DVec3::new(0.0, 2.0, 1.0) - c.closest_point_to(&transform, target)).length() < EPSILON);
+
+        // above the rim.
+        let target = DVec3::new(3.0, 5.0, 0.0);
+        assert!((DVec3::new(1.0, 3.0, 0.0) - c.closest_point_to(&transform, target)).length() < EPSILON);
+        assert!((c.shortest_distance_to(&transform, target) - 8.0_f64.sqrt()).abs() < EPSILON);
+
+        // inside.
+        let target = DVec3::new(0.5, 0.0, 0.0);
+        assert!((target - c.closest_point_to(&transform, target)).length() < EPSILON);
+    }
+
+    #[test]
+    fn test_project_onto_axis() {
+        let c = Cylinder::new(1.0, 2.0);
+        let transform = PhysTransform::IDENTITY;
+
+        assert!((c.project_onto_axis(&transform, DVec3::Y) - 2.0).abs() < EPSILON);
+        assert!((c.project_onto_axis(&transform, DVec3::X) - 1.0).abs() < EPSILON);
+        let diagonal = DVec3::new(1.0, 1.0, 0.0);
+        let expected = (2.0 + 1.0) / 2.0_f64.sqrt();
+        assert!((c.project_onto_axis(&transform, diagonal) - expected).abs() < EPSILON);
+    }
+}
@@ -0,0 +1,583 @@
+use bevy::math::DVec3;
+
+use crate::{
+    constants,
+    physics::components::PhysTransform,
+    physics::shapes::SupportMap,
+};
+
+// Squared lengths below this are treated as zero when checking for degenerate simplices.
+const DEGENERATE_TOLERANCE: f64 = 1e-12;
+
+/// A vertex of the Minkowski difference of two shapes, A - B, along with the support points on
+/// each shape that it was formed from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SupportVertex {
+    pub point: DVec3,
+    pub a: DVec3,
+    pub b: DVec3,
+}
+
+/// The outcome of running the GJK algorithm on a pair of shapes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GjkResult {
+    /// The shapes are separated by the given distance, with the given closest points on each.
+    Separated {
+        distance: f64,
+        point_a: DVec3,
+        point_b: DVec3,
+    },
+    /// The shapes intersect. Holds the final simplex, which encloses (or touches) the origin.
+    Intersecting(Vec<SupportVertex>),
+}
+
+/// The penetration of two intersecting shapes found by the EPA algorithm.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Penetration {
+    /// The distance shape A must be moved against the normal to separate the shapes.
+    pub depth: f64,
+    /// The unit normal, pointing from shape A towards shape B.
+    pub normal: DVec3,
+    /// The deepest point of shape A within shape B.
+    pub point_a: DVec3,
+    /// The deepest point of shape B within shape A.
+    pub point_b: DVec3,
+}
+
+/// Runs the Gilbert-Johnson-Keerthi (GJK) algorithm on the given pair of convex shapes, returning
+/// either the distance and closest points between them or, if they intersect, a simplex enclosing
+/// the origin that can be passed to 'epa'.
+pub fn gjk(
+    a: &dyn SupportMap,
+    a_transform: &PhysTransform,
+    b: &dyn SupportMap,
+    b_transform: &PhysTransform,
+) -> GjkResult {
+    let support = |direction| calc_support_vertex(a, a_transform, b, b_transform, direction);
+
+    let mut direction = b_transform.translation() - a_transform.translation();
+    if direction.length_squared() < DEGENERATE_TOLERANCE {
+        direction = DVec3::X;
+    }
+
+    let mut simplex = vec![support(-direction)];
+    let mut weights = vec![1.0];
+
+    for _ in 0..constants::GJK_MAX_ITERATIONS {
+        let (closest, reduced, reduced_weights) = calc_closest_point_on_simplex(&simplex);
+        simplex = reduced;
+        weights = reduced_weights;
+
+        let distance_sq = closest.length_squared();
+        if simplex.len() == 4 || distance_sq <= constants::GJK_TOLERANCE * constants::GJK_TOLERANCE {
+            return GjkResult::Intersecting(simplex);
+        }
+
+        // stop once the new vertex makes no further progress towards the origin.
+        let vertex = support(-closest);
+        if distance_sq - closest.dot(vertex.point) <= constants::GJK_TOLERANCE * distance_sq
+            || simplex.contains(&vertex)
+        {
+            break;
+        }
+
+        simplex.push(vertex);
+        weights.push(0.0);
+    }
+
+    // the closest points on each shape are the weighted sums of the simplex's support points.
+    let (closest, simplex, weights) = if simplex.len() == weights.len() {
+        let closest = calc_weighted_sum(simplex.iter().map(|v| v.point), &weights);
+        (closest, simplex, weights)
+    } else {
+        calc_closest_point_on_simplex(&simplex)
+    };
+
+    GjkResult::Separated {
+        distance: closest.length(),
+        point_a: calc_weighted_sum(simplex.iter().map(|v| v.a), &weights),
+        point_b: calc_weighted_sum(simplex.iter().map(|v| v.b), &weights),
+    }
+}
+
+/// Runs the Expanding Polytope Algorithm (EPA) on the given pair of intersecting convex shapes,
+/// starting from the simplex found by 'gjk', to find the depth and direction of their
+/// penetration. Returns None if no penetration can be found, e.g. for degenerate shapes.
+pub fn epa(
+    a: &dyn SupportMap,
+    a_transform: &PhysTransform,
+    b: &dyn SupportMap,
+    b_transform: &PhysTransform,
+    simplex: Vec<SupportVertex>,
+) -> Option<Penetration> {
+    let support = |direction| calc_support_vertex(a, a_transform, b, b_transform, direction);
+
+    let mut vertices = simplex;
+    if !calc_tetrahedron(&mut vertices, &support) {
+        return None;
+    }
+
+    // start from the tetrahedron, with each face wound so that its normal points outwards.
+    let mut faces = vec![];
+    for (i, j, k, opposite) in [(0, 1, 2, 3), (0, 3, 1, 2), (0, 2, 3, 1), (1, 3, 2, 0)].iter() {
+        let mut face = EpaFace::new(&vertices, *i, *j, *k);
+        if face.normal.dot(vertices[*opposite].point - vertices[*i].point) > 0.0 {
+            face = EpaFace::new(&vertices, *i, *k, *j);
+        }
+        faces.push(face);
+    }
+
+    let mut closest = 0;
+    for _ in 0..constants::EPA_MAX_ITERATIONS {
+        closest = calc_closest_face(&faces)?;
+        let face = faces[closest];
+
+        // stop once the polytope can be expanded no further towards the closest face.
+        let vertex = support(face.normal);
+        if vertex.point.dot(face.normal) - face.distance < constants::EPA_TOLERANCE {
+            return Some(face.penetration(&vertices));
+        }
+
+        // remove every face that can be seen from the new vertex, and patch the hole left
+        // behind with new faces joining its boundary (the horizon) to the vertex.
+        let mut horizon: Vec<(usize, usize)> = vec![];
+        faces.retain(|f| {
+            let visible = f.normal.dot(vertex.point - vertices[f.indices[0]].point) > 0.0;
+            if visible {
+                for (i, j) in [(0, 1), (1, 2), (2, 0)].iter() {
+                    let edge = (f.indices[*i], f.indices[*j]);
+                    // edges shared by two removed faces are not on the horizon.
+                    match horizon.iter().position(|e| *e == (edge.1, edge.0)) {
+                        Some(p) => { horizon.swap_remove(p); },
+                        None => horizon.push(edge),
+                    }
+                }
+            }
+            !visible
+        });
+
+        let index = vertices.len();
+        vertices.push(vertex);
+        for (i, j) in horizon {
+            faces.push(EpaFace::new(&vertices, i, j, index));
+        }
+    }
+
+    // out of iterations, so settle for the best found so far.
+    closest = calc_closest_face(&faces).unwrap_or(closest);
+    faces.get(closest).map(|f| f.penetration(&vertices))
+}
+
+/// Returns the closest point on the given convex shape, with the given transform, to the given
+/// point, or None if the point is inside the shape.
+pub fn calc_closest_point(
+    shape: &dyn SupportMap,
+    transform: &PhysTransform,
+    point: DVec3,
+) -> Option<DVec3> {
+    let point_transform = PhysTransform::from_translation(point);
+
+    match gjk(shape, transform, &Point, &point_transform) {
+        GjkResult::Separated { point_a, .. } => Some(point_a),
+        GjkResult::Intersecting(_) => None,
+    }
+}
+
+/// Returns the closest point on the surface of the given convex shape, with the given transform,
+/// to the given point, along with whether the point is inside the shape. Returns None if the
+/// surface cannot be found from inside the shape, e.g. for degenerate shapes.
+pub fn calc_surface_projection(
+    shape: &dyn SupportMap,
+    transform: &PhysTransform,
+    point: DVec3,
+) -> Option<(DVec3, bool)> {
+    let point_transform = PhysTransform::from_translation(point);
+
+    match gjk(shape, transform, &Point, &point_transform) {
+        GjkResult::Separated { point_a, .. } => Some((point_a, false)),
+        GjkResult::Intersecting(simplex) => {
+            // the deepest point of the shape within the point lies on the surface nearest to it.
+            epa(shape, transform, &Point, &point_transform, simplex)
+                .map(|penetration| (penetration.point_a, true))
+        },
+    }
+}
+
+// --- Helper methods
+
+/// A single point, located at the translation of its transform.
+#[derive(Debug)]
+struct Point;
+
+impl SupportMap for Point {
+    fn support_point(&self, transform: &PhysTransform, _direction: DVec3) -> DVec3 {
+        transform.translation()
+    }
+}
+
+/// A triangular face of the polytope expanded by EPA.
+#[derive(Debug, Clone, Copy)]
+struct EpaFace {
+    indices: [usize; 3],
+    /// The outward unit normal.
+    normal: DVec3,
+    /// The distance of the face's plane from the origin.
+    distance: f64,
+}
+
+impl EpaFace {
+    /// Creates a new face from the vertices with the given indices, wound anticlockwise about its
+    /// normal.
+    fn new(vertices: &[SupportVertex], i: usize, j: usize, k: usize) -> Self {
+        let a = vertices[i].point;
+        let normal = (vertices[j].point - a).cross(vertices[k].point - a);
+
+        // degenerate faces are placed out of reach, so that they are never chosen.
+        let (normal, distance) = if normal.length_squared() < DEGENERATE_TOLERANCE {
+            (DVec3::ZERO, f64::INFINITY)
+        } else {
+            let normal = normal.normalize();
+            (normal, normal.dot(a))
+        };
+
+        Self { indices: [i, j, k], normal, distance }
+    }
+
+    /// Returns the penetration described by the face, given that it is the closest to the origin.
+    fn penetration(&self, vertices: &[SupportVertex]) -> Penetration {
+        let [i, j, k] = self.indices;
+        let (u, v, w) = calc_barycentric(self.normal * self.distance, vertices[i].point,
+                                         vertices[j].point, vertices[k].point);
+
+        Penetration {
+            depth: self.distance,
+            normal: self.normal,
+            point_a: vertices[i].a * u + vertices[j].a * v + vertices[k].a * w,
+            point_b: vertices[i].b * u + vertices[j].b * v + vertices[k].b * w,
+        }
+    }
+}
+
+/// Returns the index of the face closest to the origin, if any.
+fn calc_closest_face(faces: &[EpaFace]) -> Option<usize> {
+    faces.iter()
+        .enumerate()
+        .filter(|(_, f)| f.distance.is_finite())
+        .min_by(|a, b| a.1.distance.partial_cmp(&b.1.distance).unwrap())
+        .map(|(i, _)| i)
+}
+
+/// Returns the sum of the given points, each multiplied by the corresponding weight.
+fn calc_weighted_sum(points: impl Iterator<Item = DVec3>, weights: &[f64]) -> DVec3 {
+    points.zip(weights.iter()).fold(DVec3::ZERO, |sum, (p, w)| sum + p * *w)
+}
+
+/// Returns the vertex of the Minkowski difference of the given shapes, A - B, that is furthest in
+/// the given direction.
+fn calc_support_vertex(
+    a: &dyn SupportMap,
+    a_transform: &PhysTransform,
+    b: &dyn SupportMap,
+    b_transform: &PhysTransform,
+    direction: DVec3,
+) -> SupportVertex {
+    let point_a = a.support_point(a_transform, direction);
+    let point_b = b.support_point(b_transform, -direction);
+
+    SupportVertex { point: point_a - point_b, a: point_a, b: point_b }
+}
+
+/// Adds vertices to the given simplex, found with the given support function, until it forms a
+/// tetrahedron. Returns false if this is not possible, i.e. the Minkowski difference is flat.
+fn calc_tetrahedron(
+    vertices: &mut Vec<SupportVertex>,
+    support: &dyn Fn(DVec3) -> SupportVertex,
+) -> bool {
+    let axes = [DVec3::X, DVec3::Y, DVec3::Z];
+
+    if vertices.len() == 1 {
+        for axis in axes.iter() {
+            let vertex = [support(*axis), support(-*axis)].iter()
+                .copied()
+                .find(|v| (v.point - vertices[0].point).length_squared() > DEGENERATE_TOLERANCE);
+            if let Some(vertex) = vertex {
+                vertices.push(vertex);
+                break;
+            }
+        }
+    }
+
+    if vertices.len() == 2 {
+        let line = vertices[1].point - vertices[0].point;
+        for axis in axes.iter() {
+            let direction = line.cross(*axis);
+            if direction.length_squared() < DEGENERATE_TOLERANCE { continue; }
+
+            let vertex = [support(direction), support(-direction)].iter()
+                .copied()
+                .find(|v| line.cross(v.point - vertices[0].point).length_squared() > DEGENERATE_TOLERANCE);
+            if let Some(vertex) = vertex {
+                vertices.push(vertex);
+                break;
+            }
+        }
+    }
+
+    if vertices.len() == 3 {
+        let normal = (vertices[1].point - vertices[0].point)
+            .cross(vertices[2].point - vertices[0].point)
+            .normalize_or_zero();
+
+        let vertex = [support(normal), support(-normal)].iter()
+            .copied()
+            .find(|v| normal.dot(v.point - vertices[0].point).abs() > DEGENERATE_TOLERANCE);
+        if let Some(vertex) = vertex {
+            vertices.push(vertex);
+        }
+    }
+
+    vertices.len() == 4
+}
+
+/// Returns the closest point to the origin on the given simplex of 1 to 4 vertices, along with the
+/// smallest sub-simplex containing that point and the barycentric weights of the point within it.
+fn calc_closest_point_on_simplex(simplex: &[SupportVertex]) -> (DVec3, Vec<SupportVertex>, Vec<f64>) {
+    match simplex.len() {
+        1 => (simplex[0].point, simplex.to_vec(), vec![1.0]),
+        2 => calc_closest_point_on_segment(simplex[0], simplex[1]),
+        3 => calc_closest_point_on_triangle(simplex[0], simplex[1], simplex[2]),
+        _ => calc_closest_point_on_tetrahedron(simplex[0], simplex[1], simplex[2], simplex[3]),
+    }
+}
+
+/// Returns the closest point to the origin on the segment between the given vertices.
+fn calc_closest_point_on_segment(
+    a: SupportVertex,
+    b: SupportVertex,
+) -> (DVec3, Vec<SupportVertex>, Vec<f64>) {
+    let ab = b.point - a.point;
+    let length_sq = ab.length_squared();
+    let t = if length_sq > 0.0 { -a.point.dot(ab) / length_sq } else { 0.0 };
+
+    if t <= 0.0 {
+        (a.point, vec![a], vec![1.0])
+    } else if t >= 1.0 {
+        (b.point, vec![b], vec![1.0])
+    } else {
+        (a.point + ab * t, vec![a, b], vec![1.0 - t, t])
+    }
+}
+
+/// Returns the closest point to the origin on the triangle with the given vertices.
+fn calc_closest_point_on_triangle(
+    a: SupportVertex,
+    b: SupportVertex,
+    c: SupportVertex,
+) -> (DVec3, Vec<SupportVertex>, Vec<f64>) {
+    // Determine which of the triangle's Voronoi regions contains the origin, as in Ericson's
+    // Real-Time Collision Detection (5.1.5).
+    let ab = b.point - a.point;
+    let ac = c.point - a.point;
+
+    let d1 = ab.dot(-a.point);
+    let d2 = ac.dot(-a.point);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return (a.point, vec![a], vec![1.0]);
+    }
+
+    let d3 = ab.dot(-b.point);
+    let d4 = ac.dot(-b.point);
+    if d3 >= 0.0 && d4 <= d3 {
+        return (b.point, vec![b], vec![1.0]);
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let v = d1 / (d1 - d3);
+        return (a.point + ab * v, vec![a, b], vec![1.0 - v, v]);
+    }
+
+    let d5 = ab.dot(-c.point);
+    let d6 = ac.dot(-c.point);
+    if d6 >= 0.0 && d5 <= d6 {
+        return (c.point, vec![c], vec![1.0]);
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let w = d2 / (d2 - d6);
+        return (a.point + ac * w, vec![a, c], vec![1.0 - w, w]);
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return (b.point + (c.point - b.point) * w, vec![b, c], vec![1.0 - w, w]);
+    }
+
+    let total = va + vb + vc;
+    if total.abs() < DEGENERATE_TOLERANCE {
+        // a degenerate triangle, so use the closest of its edges.
+        return [
+            calc_closest_point_on_segment(a, b),
+            calc_closest_point_on_segment(b, c),
+            calc_closest_point_on_segment(a, c),
+        ].iter()
+            .cloned()
+            .min_by(|x, y| x.0.length_squared().partial_cmp(&y.0.length_squared()).unwrap())
+            .unwrap();
+    }
+
+    let v = vb / total;
+    let w = vc / total;
+    (a.point + ab * v + ac * w, vec![a, b, c], vec![1.0 - v - w, v, w])
+}
+
+/// Returns the closest point to the origin on the tetrahedron with the given vertices. If the
+/// origin is inside, it is returned along with the whole tetrahedron.
+fn calc_closest_point_on_tetrahedron(
+    a: SupportVertex,
+    b: SupportVertex,
+    c: SupportVertex,
+    d: SupportVertex,
+) -> (DVec3, Vec<SupportVertex>, Vec<f64>) {
+    let mut closest: Option<(DVec3, Vec<SupportVertex>, Vec<f64>)> = None;
+
+    // test each face that the origin lies outside of, i.e. on the opposite side to the remaining
+    // vertex.
+    for (p, q, r, opposite) in [(a, b, c, d), (a, c, d, b), (a, d, b, c), (b, d, c, a)].iter() {
+        let normal = (q.point - p.point).cross(r.point - p.point);
+        let origin_side = normal.dot(-p.point);
+        let opposite_side = normal.dot(opposite.point - p.point);
+
+        if origin_side * opposite_side > 0.0 { continue; }
+
+        let candidate = calc_closest_point_on_triangle(*p, *q, *r);
+        match &closest {
+            Some((point, ..)) if point.length_squared() <= candidate.0.length_squared() => (),
+            _ => closest = Some(candidate),
+        }
+    }
+
+    closest.unwrap_or((DVec3::ZERO, vec![a, b, c, d], vec![0.25; 4]))
+}
+
+/// Returns the barycentric coordinates of the given point, projected onto the plane of the
+/// triangle with the given vertices.
+fn calc_barycentric(point: DVec3, a: DVec3, b: DVec3, c: DVec3) -> (f64, f64, f64) {
+    let v0 = b - a;
+    let v1 = c - a;
+    let v2 = point - a;
+
+    let d00 = v0.dot(v0);
+    let d01 = v0.dot(v1);
+    let d11 = v1.dot(v1);
+    let d20 = v2.dot(v0);
+    let d21 = v2.dot(v1);
+    let denom = d00 * d11 - d01 * d01;
+
+    if denom.abs() < DEGENERATE_TOLERANCE {
+        return (1.0, 0.0, 0.0);
+    }
+
+    let v = (d11 * d20 - d01 * d21) / denom;
+    let w = (d00 * d21 - d01 * d20) / denom;
+
+    (1.0 - v - w, v, w)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use bevy::math::DQuat;
+
+    use crate::physics::shapes::{
+        Cuboid,
+        Sphere,
+    };
+
+    const EPSILON: f64 = 0.0001;
+
+    #[test]
+    fn test_gjk() {
+        let c1 = Cuboid::new(DVec3::new(1.0, 1.0, 1.0));
+        let c2 = Cuboid::new(DVec3::new(1.0, 2.0, 1.0));
+        let c1_transform = PhysTransform::IDENTITY;
+
+        // SEPARATED.
+        let c2_transform = PhysTransform::from_rotation_translation(
+            DQuat::from_rotation_y(0.3),
+            DVec3::new(0.5, 4.5, 0.0),
+        );
+        match gjk(&c1, &c1_transform, &c2, &c2_transform) {
+            GjkResult::Separated { distance, point_a, point_b } => {
+                assert!((distance - 1.5).abs() < EPSILON);
+                assert!((point_a.y - 1.0).abs() < EPSILON);
+                assert!((point_b.y - 2.5).abs() < EPSILON);
+            },
+            GjkResult::Intersecting(_) => panic!("expected separation"),
+        }
+
+        // INTERSECTING.
+        let c2_transform = PhysTransform::from_xyz(0.5, 2.5, 0.0);
+        assert!(matches!(gjk(&c1, &c1_transform, &c2, &c2_transform), GjkResult::Intersecting(_)));
+    }
+
+    #[test]
+    fn test_epa() {
+        let c = Cuboid::new(DVec3::new(1.0, 1.0, 1.0));
+        let s = Sphere::new(1.0);
+        let c_transform = PhysTransform::IDENTITY;
+
+        // sphere resting in the top face.
+        let s_transform = PhysTransform::from_xyz(0.2, 1.9, 0.1);
+        let simplex = match gjk(&c, &c_transform, &s, &s_transform) {
+            GjkResult::Intersecting(simplex) => simplex,
+            GjkResult::Separated { .. } => panic!("expected intersection"),
+        };
+        let penetration = epa(&c, &c_transform, &s, &s_transform, simplex).unwrap();
+
+        assert!((penetration.depth - 0.1).abs() < EPSILON);
+        assert!((penetration.normal - DVec3::Y).length() < EPSILON);
+        // the witness points on the curved surface of the sphere are only approximate.
+        assert!((penetration.point_a - DVec3::new(0.2, 1.0, 0.1)).length() < 0.001);
+        assert!((penetration.point_b - DVec3::new(0.2, 0.9, 0.1)).length() < 0.001);
+
+        // two cuboids overlapping deeply along the x-axis.
+        let c2_transform = PhysTransform::from_xyz(1.5, 0.2, 0.0);
+        let simplex = match gjk(&c, &c_transform, &c, &c2_transform) {
+            GjkResult::Intersecting(simplex) => simplex,
+            GjkResult::Separated { .. } => panic!("expected intersection"),
+        };
+        let penetration = epa(&c, &c_transform, &c, &c2_transform, simplex).unwrap();
+
+        assert!((penetration.depth - 0.5).abs() < EPSILON);
+        assert!((penetration.normal - DVec3::X).length() < EPSILON);
+    }
+
+    #[test]
+    fn test_calc_closest_point() {
+        let c = Cuboid::new(DVec3::new(1.0, 1.0, 1.0));
+        let transform = PhysTransform::from_xyz(0.0, 1.0, 0.0);
+
+        let closest = calc_closest_point(&c, &transform, DVec3::new(3.0, 1.5, 0.0)).unwrap();
+        assert!((closest - DVec3::new(1.0, 1.5, 0.0)).length() < EPSILON);
+
+        assert_eq!(None, calc_closest_point(&c, &transform, DVec3::new(0.5, 1.5, 0.0)));
+    }
+
+    #[test]
+    fn test_calc_surface_projection() {
+        let c = Cuboid::new(DVec3::new(1.0, 1.0, 1.0));
+        let transform = PhysTransform::from_xyz(0.0, 1.0, 0.0);
+
+        let (point, inside) = calc_surface_projection(&c, &transform, DVec3::new(3.0, 1.5, 0.0)).unwrap();
+        assert!((point - DVec3::new(1.0, 1.5, 0.0)).length() < EPSILON);
+        assert!(!inside);
+
+        let (point, inside) = calc_surface_projection(&c, &transform, DVec3::new(0.2, 1.8, 0.1)).unwrap();
+        assert!((point - DVec3::new(0.2, 2.0, 0.1)).length() < EPSILON);
+        assert!(inside);
+    }
+}
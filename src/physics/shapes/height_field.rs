@@ -0,0 +1,416 @@
+use bevy::{
+    math::DVec3,
+    render::mesh::Mesh,
+};
+
+use crate::{
+    physics::shapes::{
+        ray_cast::calc_ray_and_aabb,
+        tri_mesh::read_mesh_positions,
+        triangle::{Triangle, TriangleSet},
+        Collidable,
+        CollisionPrimative,
+        RayCast,
+        Sphere,
+    },
+    physics::components::PhysTransform,
+};
+
+// Coordinates closer than this are treated as lying on the same grid line.
+const GRID_TOLERANCE: f64 = 0.000001;
+
+/// A static terrain surface described by a regular grid of heights in local body coords. The grid
+/// lies in the x-z plane with the heights along the y-axis. Each cell of the grid is split into
+/// two triangles, which face upwards. Contacts are generated against any convex shape.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeightField {
+    heights: Vec<f64>,
+    rows: usize,
+    columns: usize,
+    // the corner of the grid with the minimum x and z coords.
+    origin: DVec3,
+    cell_width: f64,
+    cell_depth: f64,
+    bounding_sphere: Sphere,
+}
+
+impl HeightField {
+    /// Creates a new HeightField, centred on the origin in the x-z plane, from the given heights.
+    /// The heights are given row by row, with each row running along the x-axis and the rows
+    /// spaced along the z-axis. The distance between columns is the cell width and between rows
+    /// the cell depth.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if there are fewer than 2 rows or columns, or the number of heights does not
+    /// match.
+    pub fn new(heights: Vec<f64>, rows: usize, columns: usize, cell_width: f64, cell_depth: f64) -> Self {
+        let origin = DVec3::new(
+            -cell_width * (columns as f64 - 1.0) * 0.5,
+            0.0,
+            -cell_depth * (rows as f64 - 1.0) * 0.5,
+        );
+
+        Self::with_origin(heights, rows, columns, origin, cell_width, cell_depth)
+    }
+
+    /// Creates a new HeightField from a raw buffer of vertices, in local body coords, that lie on a
+    /// regular grid in the x-z plane, in any order. Returns None if the vertices do not form a
+    /// complete grid of at least 2 by 2.
+    pub fn from_vertices(vertices: &[DVec3]) -> Option<Self> {
+        let xs = calc_grid_lines(vertices.iter().map(|v| v.x))?;
+        let zs = calc_grid_lines(vertices.iter().map(|v| v.z))?;
+
+        let (columns, rows) = (xs.len(), zs.len());
+        let cell_width = xs[1] - xs[0];
+        let cell_depth = zs[1] - zs[0];
+
+        let mut heights = vec![None; rows * columns];
+        for v in vertices {
+            let column = ((v.x - xs[0]) / cell_width).round() as usize;
+            let row = ((v.z - zs[0]) / cell_depth).round() as usize;
+            heights[row * columns + column] = Some(v.y);
+        }
+
+        let heights = heights.into_iter().collect::<Option<Vec<f64>>>()?;
+        let origin = DVec3::new(xs[0], 0.0, zs[0]);
+
+        Some(Self::with_origin(heights, rows, columns, origin, cell_width, cell_depth))
+    }
+
+    /// Creates a new HeightField from the vertex positions of the given Bevy Mesh, which must lie
+    /// on a regular grid in the x-z plane. Returns None if they do not.
+    pub fn from_mesh(mesh: &Mesh) -> Option<Self> {
+        Self::from_vertices(&read_mesh_positions(mesh)?)
+    }
+
+    /// Returns the number of rows in the grid, spaced along the z-axis.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns in the grid, spaced along the x-axis.
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Returns the height at the given row and column of the grid.
+    pub fn height(&self, row: usize, column: usize) -> f64 {
+        self.heights[row * self.columns + column]
+    }
+
+    fn with_origin(
+        heights: Vec<f64>,
+        rows: usize,
+        columns: usize,
+        origin: DVec3,
+        cell_width: f64,
+        cell_depth: f64,
+    ) -> Self {
+        assert!(rows >= 2 && columns >= 2, "A HeightField requires at least 2 rows and columns!");
+        assert_eq!(rows * columns, heights.len(), "HeightField size does not match its heights!");
+
+        let mut result = Self {
+            heights,
+            rows,
+            columns,
+            origin,
+            cell_width,
+            cell_depth,
+            bounding_sphere: Sphere::new(0.0),
+        };
+
+        let radius = (0..rows)
+            .flat_map(|r| (0..columns).map(move |c| (r, c)))
+            .map(|(r, c)| result.vertex(r, c).length())
+            .fold(0.0, f64::max);
+        result.bounding_sphere = Sphere::new(radius);
+
+        result
+    }
+
+    /// Returns the grid vertex at the given row and column in local body coords.
+    fn vertex(&self, row: usize, column: usize) -> DVec3 {
+        self.origin + DVec3::new(
+            column as f64 * self.cell_width,
+            self.height(row, column),
+            row as f64 * self.cell_depth,
+        )
+    }
+
+    /// Returns the two triangles of the cell at the given row and column, with their indices.
+    fn cell_triangles(&self, row: usize, column: usize) -> [(usize, Triangle); 2] {
+        let v00 = self.vertex(row, column);
+        let v01 = self.vertex(row, column + 1);
+        let v10 = self.vertex(row + 1, column);
+        let v11 = self.vertex(row + 1, column + 1);
+        let index = (row * (self.columns - 1) + column) * 2;
+
+        [
+            (index, Triangle::new(v00, v10, v01)),
+            (index + 1, Triangle::new(v01, v10, v11)),
+        ]
+    }
+
+    /// Returns the range of cells spanning the given local coordinate range along one grid axis,
+    /// given the axis's origin, cell size and number of cells, or None if they do not overlap.
+    fn cell_range(min: f64, max: f64, origin: f64, size: f64, count: usize) -> Option<(usize, usize)> {
+        let first = ((min - origin) / size).floor();
+        let last = ((max - origin) / size).floor();
+
+        if last < 0.0 || first >= count as f64 {
+            return None;
+        }
+
+        Some((first.max(0.0) as usize, (last as usize).min(count - 1)))
+    }
+}
+
+impl TriangleSet for HeightField {
+    fn triangles_in_aabb(&self, min: DVec3, max: DVec3) -> Vec<(usize, Triangle)> {
+        let mut result = vec![];
+
+        let columns = Self::cell_range(
+            min.x, max.x, self.origin.x, self.cell_width, self.columns - 1,
+        );
+        let rows = Self::cell_range(
+            min.z, max.z, self.origin.z, self.cell_depth, self.rows - 1,
+        );
+
+        let ((c0, c1), (r0, r1)) = match (columns, rows) {
+            (Some(columns), Some(rows)) => (columns, rows),
+            _ => return result,
+        };
+
+        for row in r0..=r1 {
+            for column in c0..=c1 {
+                for (i, triangle) in self.cell_triangles(row, column).iter() {
+                    let (tri_min, tri_max) = triangle.bounds();
+                    if tri_max.y >= min.y && tri_min.y <= max.y {
+                        result.push((*i, *triangle));
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// The triangles are returned in the order the ray passes over their cells.
+    fn triangles_along_ray(
+        &self,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+    ) -> Vec<(usize, Triangle)> {
+        let mut result = vec![];
+
+        // clip the ray to the grid, which is unbounded in y.
+        let size = DVec3::new(
+            self.cell_width * (self.columns - 1) as f64,
+            0.0,
+            self.cell_depth * (self.rows - 1) as f64,
+        );
+        let min = DVec3::new(self.origin.x, f64::NEG_INFINITY, self.origin.z);
+        let max = DVec3::new(self.origin.x + size.x, f64::INFINITY, self.origin.z + size.z);
+        let (start, _) = match calc_ray_and_aabb(origin, direction, min, max, max_toi) {
+            Some(hit) => hit,
+            None => return result,
+        };
+
+        // Walk the cells beneath the ray in order, as in Amanatides and Woo's "A Fast Voxel
+        // Traversal Algorithm for Ray Tracing".
+        let entry = origin + direction * start - self.origin;
+        let mut column = (entry.x / self.cell_width).floor()
+            .clamp(0.0, (self.columns - 2) as f64) as isize;
+        let mut row = (entry.z / self.cell_depth).floor()
+            .clamp(0.0, (self.rows - 2) as f64) as isize;
+
+        // the step between cells, time of impact of the next grid line and time between grid
+        // lines along each axis.
+        let axis = |d: f64, o: f64, cell: isize, size: f64| {
+            if d == 0.0 {
+                return (0, f64::INFINITY, f64::INFINITY);
+            }
+            let step = d.signum() as isize;
+            let line = (cell + step.max(0)) as f64 * size;
+            (step, (line - o) / d, size / d.abs())
+        };
+        let local = origin - self.origin;
+        let (step_x, mut next_x, delta_x) = axis(direction.x, local.x, column, self.cell_width);
+        let (step_z, mut next_z, delta_z) = axis(direction.z, local.z, row, self.cell_depth);
+
+        loop {
+            result.extend(self.cell_triangles(row as usize, column as usize).iter());
+
+            // the ray leaves the cell at the nearest grid line, if it does so in time.
+            let toi = next_x.min(next_z);
+            if toi.is_infinite() || toi > max_toi {
+                break;
+            }
+
+            if next_x < next_z {
+                column += step_x;
+                next_x += delta_x;
+            } else {
+                row += step_z;
+                next_z += delta_z;
+            }
+
+            let outside = column < 0 || column > self.columns as isize - 2
+                || row < 0 || row > self.rows as isize - 2;
+            if outside {
+                break;
+            }
+        }
+
+        result
+    }
+
+    fn closest_triangle_to(&self, point: DVec3) -> (Triangle, DVec3) {
+        let closest_in = |triangles: &[(usize, Triangle)]| {
+            triangles.iter()
+                .map(|(_, t)| (*t, t.closest_point_to(point)))
+                .min_by(|(_, a), (_, b)| {
+                    (point - *a).length().partial_cmp(&(point - *b).length()).unwrap()
+                })
+        };
+
+        // the cell beneath the point gives an upper bound on the distance to the surface, which
+        // limits the cells that need to be searched.
+        let column = ((point.x - self.origin.x) / self.cell_width).floor()
+            .clamp(0.0, (self.columns - 2) as f64) as usize;
+        let row = ((point.z - self.origin.z) / self.cell_depth).floor()
+            .clamp(0.0, (self.rows - 2) as f64) as usize;
+        let bound = closest_in(&self.cell_triangles(row, column)).unwrap();
+        let distance = (point - bound.1).length();
+
+        let nearby = self.triangles_in_aabb(
+            point - DVec3::splat(distance),
+            point + DVec3::splat(distance),
+        );
+
+        closest_in(&nearby).unwrap_or(bound)
+    }
+}
+
+impl CollisionPrimative for HeightField {
+    /// Returns the Sphere that shares a centre point with the HeightField and completely encloses
+    /// it.
+    fn bounding_sphere(&self) -> &Sphere {
+        &self.bounding_sphere
+    }
+
+    fn as_ray_cast(&self) -> Option<&dyn RayCast> {
+        Some(self)
+    }
+}
+
+impl Collidable for HeightField {
+    /// Calculates and returns the closest point on the surface of the HeightField, with the given
+    /// transform, to the given target point.
+    fn closest_point_to(&self, transform: &PhysTransform, target: DVec3) -> DVec3 {
+        let target_local = transform.get_point_in_local_space(target);
+        let (_, closest) = self.closest_triangle_to(target_local);
+
+        transform.get_point_in_global_space(closest)
+    }
+
+    /// Calculates and returns the shortest distance between the surface of the HeightField, with
+    /// the given transform, and the target point in global coords.
+    fn shortest_distance_to(&self, transform: &PhysTransform, target: DVec3) -> f64 {
+        (target - self.closest_point_to(transform, target)).length()
+    }
+}
+
+/// Returns the sorted, evenly spaced coordinates of the grid lines that the given coordinates lie
+/// on, or None if there are fewer than 2 or they are unevenly spaced.
+fn calc_grid_lines(coords: impl Iterator<Item = f64>) -> Option<Vec<f64>> {
+    let mut coords: Vec<f64> = coords.collect();
+    coords.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    coords.dedup_by(|a, b| (*a - *b).abs() < GRID_TOLERANCE);
+
+    if coords.len() < 2 {
+        return None;
+    }
+
+    let spacing = coords[1] - coords[0];
+    let even = coords.iter()
+        .enumerate()
+        .all(|(i, c)| (coords[0] + spacing * i as f64 - c).abs() < GRID_TOLERANCE);
+
+    if even { Some(coords) } else { None }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EPSILON: f64 = 0.000001;
+
+    /// A 3 by 3 grid, 2 units wide and deep, flat but for a raised centre.
+    fn hill() -> HeightField {
+        HeightField::new(vec![
+            0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0,
+        ], 3, 3, 1.0, 1.0)
+    }
+
+    #[test]
+    fn test_triangles_in_aabb() {
+        let h = hill();
+
+        // one whole cell.
+        let found = h.triangles_in_aabb(DVec3::new(-0.9, -1.0, -0.9), DVec3::new(-0.1, 2.0, -0.1));
+        assert_eq!(2, found.len());
+        for (_, triangle) in found.iter() {
+            assert!(triangle.normal().y > 0.0);
+        }
+
+        // above the hill.
+        assert!(h.triangles_in_aabb(DVec3::new(-0.9, 1.5, -0.9), DVec3::new(0.9, 2.0, 0.9)).is_empty());
+
+        // beyond the grid.
+        assert!(h.triangles_in_aabb(DVec3::new(1.5, -1.0, 0.0), DVec3::new(2.0, 1.0, 0.5)).is_empty());
+    }
+
+    #[test]
+    fn test_closest_point_to() {
+        let h = hill();
+        let transform = PhysTransform::from_xyz(0.0, -1.0, 0.0);
+
+        // above the peak.
+        let target = DVec3::new(0.0, 1.0, 0.0);
+        assert!((DVec3::new(0.0, 0.0, 0.0) - h.closest_point_to(&transform, target)).length() < EPSILON);
+
+        // above the flat half of a cell.
+        let target = DVec3::new(0.9, 0.0, 0.9);
+        assert!((h.shortest_distance_to(&transform, target) - 1.0).abs() < EPSILON);
+
+        // beside the grid.
+        let target = DVec3::new(3.0, -1.0, 0.5);
+        assert!((DVec3::new(1.0, -1.0, 0.5) - h.closest_point_to(&transform, target)).length() < EPSILON);
+    }
+
+    #[test]
+    fn test_from_vertices() {
+        // the hill, offset and shuffled.
+        let vertices: Vec<DVec3> = [(1, 0), (0, 0), (2, 2), (1, 1), (0, 1), (2, 0), (0, 2), (1, 2), (2, 1)]
+            .iter()
+            .map(|(c, r)| {
+                let y = if (*c, *r) == (1, 1) { 1.0 } else { 0.0 };
+                DVec3::new(*c as f64 + 5.0, y, *r as f64 * 2.0)
+            })
+            .collect();
+        let h = HeightField::from_vertices(&vertices).unwrap();
+
+        assert_eq!(3, h.rows());
+        assert_eq!(3, h.columns());
+        assert_eq!(1.0, h.height(1, 1));
+        assert_eq!(0.0, h.height(2, 0));
+
+        // an incomplete grid.
+        assert!(HeightField::from_vertices(&vertices[1..]).is_none());
+    }
+}
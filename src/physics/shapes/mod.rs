@@ -1,13 +1,34 @@
 mod aabb;
+mod bvh;
+mod capsule;
 mod collidable;
+mod compound;
+mod convex_hull;
 mod cuboid;
+mod cylinder;
+mod height_field;
+pub(crate) mod gjk;
 mod plane;
 mod primative;
+mod ray_cast;
 mod sphere;
+mod support_map;
+mod tri_mesh;
+pub(crate) mod triangle;
 
 pub use aabb::Aabb3D;
+pub use capsule::Capsule;
+pub(crate) use capsule::calc_closest_point_on_segment;
 pub use primative::CollisionPrimative;
 pub use collidable::Collidable;
+pub use compound::Compound;
+pub use convex_hull::ConvexHull;
 pub use cuboid::Cuboid;
+pub use cylinder::Cylinder;
+pub use height_field::HeightField;
 pub use plane::Plane;
+pub use ray_cast::RayCast;
+pub(crate) use ray_cast::calc_ray_and_aabb;
 pub use sphere::Sphere;
+pub use support_map::SupportMap;
+pub use tri_mesh::TriMesh;
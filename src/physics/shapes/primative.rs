@@ -3,12 +3,28 @@ use downcast_rs::{
     impl_downcast,
 };
 
-use crate::physics::shapes::Sphere;
+use crate::physics::shapes::{
+    RayCast,
+    Sphere,
+    SupportMap,
+};
 
 /// Primative shapes that can take part in collision physics,
 pub trait CollisionPrimative: std::fmt::Debug + Downcast + Send + Sync {
     /// Returns a Sphere that contains the primative shape in its entirety.
     fn bounding_sphere(&self) -> &Sphere;
+
+    /// Returns the primative as a SupportMap if it is convex, allowing contacts to be generated
+    /// against other convex primatives for which there is no specialised routine.
+    fn as_support_map(&self) -> Option<&dyn SupportMap> {
+        None
+    }
+
+    /// Returns the primative as a RayCast if it can be intersected exactly by a ray. Primatives
+    /// that cannot are ignored by ray casts.
+    fn as_ray_cast(&self) -> Option<&dyn RayCast> {
+        None
+    }
 }
 
 // implement downcasting to the concrete type of the primative shape for dispatching to relevant
@@ -0,0 +1,564 @@
+use bevy::math::DVec3;
+
+use crate::{
+    constants,
+    physics::components::PhysTransform,
+    physics::shapes::{
+        gjk,
+        triangle::{Triangle, TriangleSet},
+        Capsule,
+        Compound,
+        ConvexHull,
+        Cuboid,
+        Cylinder,
+        HeightField,
+        Plane,
+        Sphere,
+        SupportMap,
+        TriMesh,
+    },
+};
+
+/// Shapes that can be intersected exactly by a ray.
+pub trait RayCast: std::fmt::Debug {
+    /// Returns the time of impact and the unit surface normal, in global coords, at which the ray
+    /// with the given origin and direction first hits the shape with the given transform, if it
+    /// does so within the given maximum time of impact. The time of impact is measured in multiples
+    /// of the direction, so the point hit is 'origin + direction * toi'.
+    ///
+    /// Shapes are treated as solid, so a ray starting inside the shape hits it at a time of impact
+    /// of zero, with the normal opposing the direction of the ray.
+    fn cast_ray(
+        &self,
+        transform: &PhysTransform,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+    ) -> Option<(f64, DVec3)>;
+}
+
+impl RayCast for Sphere {
+    fn cast_ray(
+        &self,
+        transform: &PhysTransform,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+    ) -> Option<(f64, DVec3)> {
+        // Solve |origin + t * direction - centre|^2 = r^2 for the smallest t.
+        let m = origin - transform.translation();
+        let c = m.dot(m) - self.radius() * self.radius();
+
+        if c <= 0.0 {
+            return Some((0.0, -direction.normalize_or_zero()));
+        }
+
+        let a = direction.dot(direction);
+        let b = m.dot(direction);
+
+        // the ray starts outside the sphere and points away from it, or misses it entirely.
+        let discriminant = b * b - a * c;
+        if b > 0.0 || a == 0.0 || discriminant < 0.0 {
+            return None;
+        }
+
+        let toi = (-b - discriminant.sqrt()) / a;
+        if toi > max_toi {
+            return None;
+        }
+
+        Some((toi, (m + direction * toi).normalize()))
+    }
+}
+
+impl RayCast for Cuboid {
+    fn cast_ray(
+        &self,
+        transform: &PhysTransform,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+    ) -> Option<(f64, DVec3)> {
+        // The test is made in local coords, where the Cuboid is axis aligned.
+        let origin_local = transform.get_point_in_local_space(origin);
+        let direction_local = transform.get_direction_in_local_space(direction);
+
+        let (toi, normal_local) = calc_ray_and_aabb(
+            origin_local, direction_local, -self.extents(), self.extents(), max_toi,
+        )?;
+
+        if toi == 0.0 {
+            return Some((toi, -direction.normalize_or_zero()));
+        }
+
+        Some((toi, transform.get_direction_in_global_space(normal_local)))
+    }
+}
+
+impl RayCast for Plane {
+    /// The Plane is treated as the boundary of a solid half-space, behind its normal.
+    fn cast_ray(
+        &self,
+        transform: &PhysTransform,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+    ) -> Option<(f64, DVec3)> {
+        let normal = transform.get_direction_in_global_space(self.normal_in_body_space());
+        let distance = normal.dot(origin - transform.translation());
+
+        if distance <= 0.0 {
+            return Some((0.0, -direction.normalize_or_zero()));
+        }
+
+        // the ray is parallel to, or points away from, the plane.
+        let approach = normal.dot(direction);
+        if approach >= 0.0 {
+            return None;
+        }
+
+        let toi = -distance / approach;
+        if toi > max_toi {
+            return None;
+        }
+
+        Some((toi, normal))
+    }
+}
+
+impl RayCast for Compound {
+    /// Returns the first hit on any child that can be intersected by a ray.
+    fn cast_ray(
+        &self,
+        transform: &PhysTransform,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+    ) -> Option<(f64, DVec3)> {
+        self.children().iter()
+            .filter_map(|(child_transform, child)| {
+                let child_global = transform.mul_transform(child_transform);
+                child.as_ray_cast()?.cast_ray(&child_global, origin, direction, max_toi)
+            })
+            .min_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap())
+    }
+}
+
+impl RayCast for Capsule {
+    fn cast_ray(
+        &self,
+        transform: &PhysTransform,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+    ) -> Option<(f64, DVec3)> {
+        calc_ray_and_support_map(self, transform, origin, direction, max_toi)
+    }
+}
+
+impl RayCast for Cylinder {
+    fn cast_ray(
+        &self,
+        transform: &PhysTransform,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+    ) -> Option<(f64, DVec3)> {
+        calc_ray_and_support_map(self, transform, origin, direction, max_toi)
+    }
+}
+
+impl RayCast for ConvexHull {
+    fn cast_ray(
+        &self,
+        transform: &PhysTransform,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+    ) -> Option<(f64, DVec3)> {
+        calc_ray_and_support_map(self, transform, origin, direction, max_toi)
+    }
+}
+
+impl RayCast for TriMesh {
+    /// The TriMesh is treated as a surface rather than a solid, so a ray hits the first triangle
+    /// it crosses from either side, with the normal facing back along the ray.
+    fn cast_ray(
+        &self,
+        transform: &PhysTransform,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+    ) -> Option<(f64, DVec3)> {
+        calc_ray_and_triangle_set(self, transform, origin, direction, max_toi)
+    }
+}
+
+impl RayCast for HeightField {
+    /// The HeightField is treated as a surface rather than a solid, so a ray hits the first
+    /// triangle it crosses from either side, with the normal facing back along the ray.
+    fn cast_ray(
+        &self,
+        transform: &PhysTransform,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+    ) -> Option<(f64, DVec3)> {
+        calc_ray_and_triangle_set(self, transform, origin, direction, max_toi)
+    }
+}
+
+/// Returns the time of impact and the outward normal of the face at which the ray with the given
+/// origin and direction enters the axis-aligned box with the given minimum and maximum corners, if
+/// it does so within the given maximum time of impact. A ray starting inside the box gives a time of
+/// impact of zero and a zero normal.
+pub(crate) fn calc_ray_and_aabb(
+    origin: DVec3,
+    direction: DVec3,
+    min: DVec3,
+    max: DVec3,
+    max_toi: f64,
+) -> Option<(f64, DVec3)> {
+    // Clip the ray against each pair of parallel faces (slabs) in turn, as in Ericson's Real-Time
+    // Collision Detection (5.3.3).
+    let mut t_min = 0.0;
+    let mut t_max = max_toi;
+    let mut normal = DVec3::ZERO;
+
+    for i in 0..3 {
+        if direction[i] == 0.0 {
+            // parallel to the slab, so the origin must lie within it.
+            if origin[i] < min[i] || origin[i] > max[i] {
+                return None;
+            }
+            continue;
+        }
+
+        let mut t1 = (min[i] - origin[i]) / direction[i];
+        let mut t2 = (max[i] - origin[i]) / direction[i];
+        let mut face_normal = DVec3::ZERO;
+        face_normal[i] = -1.0;
+
+        if t1 > t2 {
+            std::mem::swap(&mut t1, &mut t2);
+            face_normal[i] = 1.0;
+        }
+
+        if t1 > t_min {
+            t_min = t1;
+            normal = face_normal;
+        }
+        t_max = f64::min(t_max, t2);
+
+        if t_min > t_max {
+            return None;
+        }
+    }
+
+    Some((t_min, normal))
+}
+
+// --- Helper methods
+
+/// Returns the time of impact and unit surface normal, in global coords, at which the ray with
+/// the given origin and direction first hits the given convex shape, if it does so within the
+/// given maximum time of impact.
+fn calc_ray_and_support_map(
+    shape: &dyn SupportMap,
+    transform: &PhysTransform,
+    origin: DVec3,
+    direction: DVec3,
+    max_toi: f64,
+) -> Option<(f64, DVec3)> {
+    // Advance along the ray to the plane that separates its current point from the shape, which
+    // the ray cannot cross without first reaching the shape, until the point touches it.
+    let mut toi = 0.0;
+    let mut normal = -direction.normalize_or_zero();
+
+    for _ in 0..constants::SHAPE_CAST_MAX_ITERATIONS {
+        let point = origin + direction * toi;
+        let closest = match gjk::calc_closest_point(shape, transform, point) {
+            Some(closest) => closest,
+            None => return Some((toi, normal)),
+        };
+
+        let separation = point - closest;
+        let distance = separation.length();
+        if distance > 0.0 {
+            normal = separation / distance;
+        }
+
+        if distance < constants::SHAPE_CAST_TOLERANCE {
+            return Some((toi, normal));
+        }
+
+        // the ray is parallel to, or points away from, the separating plane.
+        let approach = -normal.dot(direction);
+        if approach <= 0.0 {
+            return None;
+        }
+
+        toi += distance / approach;
+        if toi > max_toi {
+            return None;
+        }
+    }
+
+    None
+}
+
+/// Returns the time of impact and unit surface normal, in global coords, at which the ray with
+/// the given origin and direction first crosses a triangle of the given set, if it does so within
+/// the given maximum time of impact. The normal faces back along the ray.
+fn calc_ray_and_triangle_set(
+    triangles: &dyn TriangleSet,
+    transform: &PhysTransform,
+    origin: DVec3,
+    direction: DVec3,
+    max_toi: f64,
+) -> Option<(f64, DVec3)> {
+    // The test is made in local coords, where the triangles are stored.
+    let origin_local = transform.get_point_in_local_space(origin);
+    let direction_local = transform.get_direction_in_local_space(direction);
+
+    let (toi, normal_local) = triangles.triangles_along_ray(origin_local, direction_local, max_toi)
+        .iter()
+        .filter_map(|(_, t)| calc_ray_and_triangle(origin_local, direction_local, t, max_toi))
+        .min_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap())?;
+
+    Some((toi, transform.get_direction_in_global_space(normal_local)))
+}
+
+/// Returns the time of impact at which the ray with the given origin and direction crosses the
+/// given triangle, if it does so within the given maximum time of impact, along with the unit
+/// normal of the triangle facing back along the ray.
+fn calc_ray_and_triangle(
+    origin: DVec3,
+    direction: DVec3,
+    triangle: &Triangle,
+    max_toi: f64,
+) -> Option<(f64, DVec3)> {
+    // Solve for the barycentric coords of the crossing point, as in Moller and Trumbore's "Fast,
+    // Minimum Storage Ray/Triangle Intersection".
+    let [a, b, c] = triangle.vertices;
+    let ab = b - a;
+    let ac = c - a;
+
+    let p = direction.cross(ac);
+    let determinant = ab.dot(p);
+
+    // the ray is parallel to the triangle, or the triangle is degenerate.
+    if determinant.abs() < f64::EPSILON {
+        return None;
+    }
+
+    let ap = origin - a;
+    let u = ap.dot(p) / determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = ap.cross(ab);
+    let v = direction.dot(q) / determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let toi = ac.dot(q) / determinant;
+    if toi < 0.0 || toi > max_toi {
+        return None;
+    }
+
+    let normal = triangle.normal();
+    Some((toi, if normal.dot(direction) > 0.0 { -normal } else { normal }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::physics::shapes::Collidable;
+
+    const EPSILON: f64 = 0.000001;
+    // The support map ray cast stops within this distance of the surface.
+    const TOLERANCE: f64 = 0.001;
+
+    #[test]
+    fn test_sphere() {
+        let s = Sphere::new(1.0);
+        let transform = PhysTransform::from_xyz(0.0, 0.0, 5.0);
+
+        let (toi, normal) = s.cast_ray(&transform, DVec3::ZERO, DVec3::Z * 2.0, 10.0).unwrap();
+        assert!((toi - 2.0).abs() < EPSILON);
+        assert!((normal - -DVec3::Z).length() < EPSILON);
+
+        // too short, passing by and pointing away.
+        assert!(s.cast_ray(&transform, DVec3::ZERO, DVec3::Z, 3.9).is_none());
+        assert!(s.cast_ray(&transform, DVec3::X * 1.1, DVec3::Z, 10.0).is_none());
+        assert!(s.cast_ray(&transform, DVec3::ZERO, -DVec3::Z, 10.0).is_none());
+
+        // from inside.
+        let (toi, normal) = s.cast_ray(&transform, DVec3::Z * 5.5, DVec3::X, 10.0).unwrap();
+        assert_eq!(0.0, toi);
+        assert_eq!(-DVec3::X, normal);
+    }
+
+    #[test]
+    fn test_cuboid() {
+        let c = Cuboid::new(DVec3::new(1.0, 2.0, 3.0));
+        let transform = PhysTransform::from_rotation_translation(
+            bevy::math::DQuat::from_rotation_y(std::f64::consts::FRAC_PI_2),
+            DVec3::new(10.0, 0.0, 0.0),
+        );
+
+        // the rotated cuboid's local z-axis lies along the global x-axis.
+        let (toi, normal) = c.cast_ray(&transform, DVec3::new(0.0, 1.0, 0.0), DVec3::X, 20.0).unwrap();
+        assert!((toi - 7.0).abs() < EPSILON);
+        assert!((normal - -DVec3::X).length() < EPSILON);
+
+        let (toi, normal) = c.cast_ray(&transform, DVec3::new(10.5, 5.0, 0.5), -DVec3::Y, 20.0).unwrap();
+        assert!((toi - 3.0).abs() < EPSILON);
+        assert!((normal - DVec3::Y).length() < EPSILON);
+
+        // passing by.
+        assert!(c.cast_ray(&transform, DVec3::new(0.0, 0.0, 1.1), DVec3::X, 20.0).is_none());
+    }
+
+    #[test]
+    fn test_plane() {
+        let transform = PhysTransform::from_xyz(0.0, -1.0, 0.0);
+        let p = Plane::new(&transform);
+
+        let direction = DVec3::new(1.0, -1.0, 0.0);
+        let (toi, normal) = p.cast_ray(&transform, DVec3::new(0.0, 2.0, 0.0), direction, 10.0).unwrap();
+        assert!((toi - 3.0).abs() < EPSILON);
+        assert_eq!(DVec3::Y, normal);
+
+        assert!(p.cast_ray(&transform, DVec3::ZERO, DVec3::X, 10.0).is_none());
+        assert!(p.cast_ray(&transform, DVec3::ZERO, direction, 0.5).is_none());
+
+        // from behind.
+        assert_eq!(0.0, p.cast_ray(&transform, -DVec3::Y * 2.0, DVec3::Y, 10.0).unwrap().0);
+    }
+
+    #[test]
+    fn test_capsule() {
+        let c = Capsule::new(1.0, 2.0);
+        let transform = PhysTransform::from_xyz(0.0, 0.0, 5.0);
+
+        let (toi, normal) = c.cast_ray(&transform, DVec3::ZERO, DVec3::Z * 2.0, 10.0).unwrap();
+        assert!((toi - 2.0).abs() < TOLERANCE);
+        assert!((normal - -DVec3::Z).length() < TOLERANCE);
+
+        // onto the end, at an angle.
+        let direction = DVec3::new(0.0, -1.0, 0.2);
+        let (toi, normal) = c.cast_ray(&transform, DVec3::new(0.0, 10.0, 3.6), direction, 20.0)
+            .unwrap();
+        let point = DVec3::new(0.0, 10.0, 3.6) + direction * toi;
+        assert!((c.shortest_distance_to(&transform, point)).abs() < TOLERANCE);
+        assert!((normal - (point - DVec3::new(0.0, 2.0, 5.0)).normalize()).length() < TOLERANCE);
+
+        // passing by and from inside.
+        assert!(c.cast_ray(&transform, DVec3::X * 1.1, DVec3::Z, 10.0).is_none());
+        let inside = DVec3::new(0.0, 2.5, 5.0);
+        assert_eq!(0.0, c.cast_ray(&transform, inside, DVec3::X, 10.0).unwrap().0);
+    }
+
+    #[test]
+    fn test_cylinder() {
+        let c = Cylinder::new(1.0, 1.0);
+        let transform = PhysTransform::from_rotation_translation(
+            bevy::math::DQuat::from_rotation_z(std::f64::consts::FRAC_PI_2),
+            DVec3::ZERO,
+        );
+
+        // the rotated cylinder's axis lies along the global x-axis.
+        let (toi, normal) = c.cast_ray(&transform, DVec3::new(-5.0, 0.5, 0.0), DVec3::X, 10.0)
+            .unwrap();
+        assert!((toi - 4.0).abs() < TOLERANCE);
+        assert!((normal - -DVec3::X).length() < TOLERANCE);
+
+        let (toi, normal) = c.cast_ray(&transform, DVec3::new(0.5, 5.0, 0.0), -DVec3::Y, 10.0)
+            .unwrap();
+        assert!((toi - 4.0).abs() < TOLERANCE);
+        assert!((normal - DVec3::Y).length() < TOLERANCE);
+
+        // passing by.
+        assert!(c.cast_ray(&transform, DVec3::new(0.0, 1.1, -5.0), DVec3::Z, 10.0).is_none());
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let points = vec![
+            DVec3::new(-1.0, -1.0, -1.0), DVec3::new(1.0, -1.0, -1.0),
+            DVec3::new(-1.0, 1.0, -1.0), DVec3::new(1.0, 1.0, -1.0),
+            DVec3::new(-1.0, -1.0, 1.0), DVec3::new(1.0, -1.0, 1.0),
+            DVec3::new(-1.0, 1.0, 1.0), DVec3::new(1.0, 1.0, 1.0),
+        ];
+        let h = ConvexHull::new(points);
+        let transform = PhysTransform::from_xyz(0.0, 0.0, 5.0);
+
+        let (toi, normal) = h.cast_ray(&transform, DVec3::new(0.5, 0.5, 0.0), DVec3::Z * 2.0, 10.0)
+            .unwrap();
+        assert!((toi - 2.0).abs() < TOLERANCE);
+        assert!((normal - -DVec3::Z).length() < TOLERANCE);
+
+        // too short, passing by and from inside.
+        assert!(h.cast_ray(&transform, DVec3::ZERO, DVec3::Z, 3.9).is_none());
+        assert!(h.cast_ray(&transform, DVec3::X * 1.1, DVec3::Z, 10.0).is_none());
+        assert_eq!(0.0, h.cast_ray(&transform, DVec3::Z * 5.0, DVec3::X, 10.0).unwrap().0);
+    }
+
+    #[test]
+    fn test_tri_mesh() {
+        // a strip of squares along the x-axis, in the x-z plane.
+        let vertices: Vec<DVec3> = (0..=10)
+            .flat_map(|i| vec![DVec3::new(i as f64, 0.0, 0.0), DVec3::new(i as f64, 0.0, 1.0)])
+            .collect();
+        let indices: Vec<[usize; 3]> = (0..10)
+            .flat_map(|i| vec![[i * 2, i * 2 + 1, i * 2 + 2], [i * 2 + 2, i * 2 + 1, i * 2 + 3]])
+            .collect();
+        let m = TriMesh::new(vertices, indices);
+        let transform = PhysTransform::from_xyz(0.0, 1.0, 0.0);
+
+        let (toi, normal) = m.cast_ray(&transform, DVec3::new(7.3, 4.0, 0.2), -DVec3::Y, 10.0)
+            .unwrap();
+        assert!((toi - 3.0).abs() < EPSILON);
+        assert!((normal - DVec3::Y).length() < EPSILON);
+
+        // the surface can be hit from either side.
+        let (toi, normal) = m.cast_ray(&transform, DVec3::new(2.5, -1.0, 0.5), DVec3::Y, 10.0)
+            .unwrap();
+        assert!((toi - 2.0).abs() < EPSILON);
+        assert!((normal - -DVec3::Y).length() < EPSILON);
+
+        // too short, passing by and along the surface.
+        assert!(m.cast_ray(&transform, DVec3::new(7.3, 4.0, 0.2), -DVec3::Y, 2.9).is_none());
+        assert!(m.cast_ray(&transform, DVec3::new(7.3, 4.0, 1.2), -DVec3::Y, 10.0).is_none());
+        assert!(m.cast_ray(&transform, DVec3::new(-1.0, 2.0, 0.5), DVec3::X, 20.0).is_none());
+    }
+
+    #[test]
+    fn test_height_field() {
+        // a 3 by 3 grid of unit cells, rising by one along each column.
+        let heights = [0.0, 1.0, 2.0, 3.0].repeat(4);
+        let f = HeightField::new(heights, 4, 4, 1.0, 1.0);
+        let transform = PhysTransform::from_xyz(0.0, 0.0, 0.0);
+        let slope = DVec3::new(-1.0, 1.0, 0.0).normalize();
+
+        // across several cells before meeting the slope at x = -0.3.
+        let (toi, normal) = f.cast_ray(&transform, DVec3::new(-5.0, 1.2, 0.2), DVec3::X, 10.0)
+            .unwrap();
+        assert!((toi - 4.7).abs() < EPSILON);
+        assert!((normal - slope).length() < EPSILON);
+
+        let (toi, normal) = f.cast_ray(&transform, DVec3::new(1.0, -5.0, 1.0), DVec3::Y, 10.0)
+            .unwrap();
+        assert!((toi - 7.5).abs() < EPSILON);
+        assert!((normal - -slope).length() < EPSILON);
+
+        // too short, beside the grid and above the surface.
+        assert!(f.cast_ray(&transform, DVec3::new(-5.0, 1.2, 0.2), DVec3::X, 4.6).is_none());
+        assert!(f.cast_ray(&transform, DVec3::new(-5.0, 1.2, 2.0), DVec3::X, 10.0).is_none());
+        assert!(f.cast_ray(&transform, DVec3::new(-5.0, 3.5, 0.2), DVec3::X, 10.0).is_none());
+    }
+}
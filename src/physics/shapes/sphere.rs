@@ -7,6 +7,8 @@ use crate::{
     physics::shapes::{
         Collidable,
         CollisionPrimative,
+        RayCast,
+        SupportMap,
     },
 };
 
@@ -35,17 +37,32 @@ impl CollisionPrimative for Sphere {
     fn bounding_sphere(&self) -> &Sphere {
         self
     }
+
+    fn as_support_map(&self) -> Option<&dyn SupportMap> {
+        Some(self)
+    }
+
+    fn as_ray_cast(&self) -> Option<&dyn RayCast> {
+        Some(self)
+    }
+}
+
+impl SupportMap for Sphere {
+    fn support_point(&self, transform: &PhysTransform, direction: DVec3) -> DVec3 {
+        transform.translation() + direction.normalize_or_zero() * self.radius
+    }
 }
 
 impl Collidable for Sphere {
-    /// Calculates and returns the closest point on the Sphere centred at the given position to the
-    /// given target point. The calculation is made by taking the normalised vector between the
-    /// circle's centre and the target point and scaling it by the circle's radius.
+    /// Calculates and returns the closest point on the surface of the Sphere centred at the given
+    /// position to the given target point. The calculation is made by taking the normalised vector
+    /// between the circle's centre and the target point, scaling it by the circle's radius and
+    /// offsetting it by the centre.
     fn closest_point_to(&self, transform: &PhysTransform, target: DVec3) -> DVec3 {
         // Perform calculation in global coords. Sphere centre is the transform translation.
         let centre = transform.translation();
 
-        (target - centre).normalize() * self.radius()
+        centre + (target - centre).normalize_or_zero() * self.radius()
     }
 
     /// Calculates and returns the shortest distance between the Sphere, with the given transform,
@@ -56,3 +73,18 @@ impl Collidable for Sphere {
         (target - centre).length() - self.radius()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_closest_point_to() {
+        let s = Sphere::new(2.0);
+        let transform = PhysTransform::from_xyz(1.0, 1.0, 0.0);
+
+        assert_eq!(DVec3::new(3.0, 1.0, 0.0), s.closest_point_to(&transform, DVec3::new(5.0, 1.0, 0.0)));
+        assert_eq!(DVec3::new(1.0, -1.0, 0.0), s.closest_point_to(&transform, DVec3::new(1.0, 0.5, 0.0)));
+        assert_eq!(-1.5, s.shortest_distance_to(&transform, DVec3::new(1.0, 0.5, 0.0)));
+    }
+}
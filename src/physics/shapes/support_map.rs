@@ -0,0 +1,11 @@
+use bevy::math::DVec3;
+
+use crate::physics::components::PhysTransform;
+
+/// Convex shapes that can be described by a support mapping, which allows them to be tested
+/// against any other such shape by the generic GJK and EPA algorithms.
+pub trait SupportMap: std::fmt::Debug {
+    /// Returns the point on the shape, with the given transform, that is furthest in the given
+    /// direction, in global coords.
+    fn support_point(&self, transform: &PhysTransform, direction: DVec3) -> DVec3;
+}
@@ -0,0 +1,240 @@
+use bevy::{
+    math::DVec3,
+    render::{
+        mesh::{Indices, Mesh, VertexAttributeValues},
+        pipeline::PrimitiveTopology,
+    },
+};
+
+use crate::{
+    physics::shapes::{
+        bvh::Bvh,
+        triangle::{Triangle, TriangleSet},
+        Collidable,
+        CollisionPrimative,
+        RayCast,
+        Sphere,
+    },
+    physics::components::PhysTransform,
+};
+
+/// A static mesh of triangles described by vertices in local body coords and the indices of the
+/// vertices of each triangle. Intended for level geometry such as ramps and floors, the mesh need
+/// not be convex nor closed. The triangles are held in a bounding volume hierarchy so that only
+/// those near a colliding shape are tested. Contacts are generated against any convex shape.
+#[derive(Debug, Clone)]
+pub struct TriMesh {
+    vertices: Vec<DVec3>,
+    indices: Vec<[usize; 3]>,
+    bvh: Bvh,
+    bounding_sphere: Sphere,
+}
+
+impl TriMesh {
+    /// Creates a new TriMesh from the given vertices, in local body coords, and triangles, each
+    /// given by the indices of its three vertices.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if there are no triangles or any index is out of range.
+    pub fn new(vertices: Vec<DVec3>, indices: Vec<[usize; 3]>) -> Self {
+        assert!(!indices.is_empty(), "A TriMesh requires at least one triangle!");
+        assert!(indices.iter().flatten().all(|i| *i < vertices.len()),
+                "TriMesh index out of range!");
+
+        let radius = vertices.iter()
+            .map(|v| v.length())
+            .fold(0.0, f64::max);
+
+        let bounds: Vec<(DVec3, DVec3)> = indices.iter()
+            .map(|[a, b, c]| Triangle::new(vertices[*a], vertices[*b], vertices[*c]).bounds())
+            .collect();
+
+        Self {
+            bvh: Bvh::new(&bounds),
+            vertices,
+            indices,
+            bounding_sphere: Sphere::new(radius),
+        }
+    }
+
+    /// Creates a new TriMesh from raw vertex and index buffers, where each consecutive group of
+    /// three indices forms a triangle. Returns None if the buffers do not describe at least one
+    /// complete triangle.
+    pub fn from_buffers(vertices: &[DVec3], indices: &[u32]) -> Option<Self> {
+        let chunks = indices.chunks_exact(3);
+        if indices.is_empty() || !chunks.remainder().is_empty()
+            || indices.iter().any(|i| *i as usize >= vertices.len())
+        {
+            return None;
+        }
+
+        let triangles = chunks
+            .map(|t| [t[0] as usize, t[1] as usize, t[2] as usize])
+            .collect();
+
+        Some(Self::new(vertices.to_vec(), triangles))
+    }
+
+    /// Creates a new TriMesh from the positions and indices of the given Bevy Mesh. Returns None if
+    /// the Mesh is not a list of triangles or has no positions.
+    pub fn from_mesh(mesh: &Mesh) -> Option<Self> {
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return None;
+        }
+
+        let vertices = read_mesh_positions(mesh)?;
+        let indices: Vec<u32> = match mesh.indices() {
+            Some(Indices::U16(indices)) => indices.iter().map(|i| *i as u32).collect(),
+            Some(Indices::U32(indices)) => indices.clone(),
+            None => (0..vertices.len() as u32).collect(),
+        };
+
+        Self::from_buffers(&vertices, &indices)
+    }
+
+    /// Returns the vertices of the TriMesh in local body coords.
+    pub fn vertices(&self) -> &[DVec3] {
+        &self.vertices
+    }
+
+    /// Returns the indices of the vertices of each triangle in the TriMesh.
+    pub fn indices(&self) -> &[[usize; 3]] {
+        &self.indices
+    }
+
+    /// Returns the triangle with the given index in local body coords.
+    pub(crate) fn triangle(&self, index: usize) -> Triangle {
+        let [a, b, c] = self.indices[index];
+
+        Triangle::new(self.vertices[a], self.vertices[b], self.vertices[c])
+    }
+}
+
+impl TriangleSet for TriMesh {
+    fn triangles_in_aabb(&self, min: DVec3, max: DVec3) -> Vec<(usize, Triangle)> {
+        self.bvh.query(min, max)
+            .into_iter()
+            .map(|i| (i, self.triangle(i)))
+            .collect()
+    }
+
+    fn triangles_along_ray(
+        &self,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+    ) -> Vec<(usize, Triangle)> {
+        self.bvh.query_ray(origin, direction, max_toi)
+            .into_iter()
+            .map(|i| (i, self.triangle(i)))
+            .collect()
+    }
+
+    fn closest_triangle_to(&self, point: DVec3) -> (Triangle, DVec3) {
+        let (nearest, _) = self.bvh.nearest(point, |i| {
+            (point - self.triangle(i).closest_point_to(point)).length()
+        }).unwrap();
+
+        let triangle = self.triangle(nearest);
+        (triangle, triangle.closest_point_to(point))
+    }
+}
+
+impl CollisionPrimative for TriMesh {
+    /// Returns the Sphere that shares a centre point with the TriMesh and completely encloses it.
+    fn bounding_sphere(&self) -> &Sphere {
+        &self.bounding_sphere
+    }
+
+    fn as_ray_cast(&self) -> Option<&dyn RayCast> {
+        Some(self)
+    }
+}
+
+impl Collidable for TriMesh {
+    /// Calculates and returns the closest point on the surface of the TriMesh, with the given
+    /// transform, to the given target point.
+    fn closest_point_to(&self, transform: &PhysTransform, target: DVec3) -> DVec3 {
+        let target_local = transform.get_point_in_local_space(target);
+        let (_, closest) = self.closest_triangle_to(target_local);
+
+        transform.get_point_in_global_space(closest)
+    }
+
+    /// Calculates and returns the shortest distance between the surface of the TriMesh, with the
+    /// given transform, and the target point in global coords.
+    fn shortest_distance_to(&self, transform: &PhysTransform, target: DVec3) -> f64 {
+        (target - self.closest_point_to(transform, target)).length()
+    }
+}
+
+/// Returns the vertex positions of the given Bevy Mesh as DVec3s, if it has any.
+pub(crate) fn read_mesh_positions(mesh: &Mesh) -> Option<Vec<DVec3>> {
+    match mesh.attribute(Mesh::ATTRIBUTE_POSITION)? {
+        VertexAttributeValues::Float3(positions) => Some(positions.iter()
+            .map(|[x, y, z]| DVec3::new(*x as f64, *y as f64, *z as f64))
+            .collect()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use bevy::prelude::shape;
+
+    const EPSILON: f64 = 0.000001;
+
+    /// A ramp rising along the x-axis, made of two triangles.
+    fn ramp() -> TriMesh {
+        let vertices = vec![
+            DVec3::new(-2.0, -1.0, -1.0),
+            DVec3::new(-2.0, -1.0, 1.0),
+            DVec3::new(2.0, 1.0, 1.0),
+            DVec3::new(2.0, 1.0, -1.0),
+        ];
+
+        TriMesh::from_buffers(&vertices, &[0, 1, 2, 0, 2, 3]).unwrap()
+    }
+
+    #[test]
+    fn test_closest_point_to() {
+        let mesh = ramp();
+        let transform = PhysTransform::from_xyz(0.0, 1.0, 0.0);
+
+        // above the middle of the ramp.
+        let target = DVec3::new(0.0, 2.0, 0.5);
+        let expected = DVec3::new(0.4, 1.2, 0.5);
+        assert!((expected - mesh.closest_point_to(&transform, target)).length() < EPSILON);
+
+        // beyond the top edge.
+        let target = DVec3::new(4.0, 2.0, 0.0);
+        assert!((DVec3::new(2.0, 2.0, 0.0) - mesh.closest_point_to(&transform, target)).length() < EPSILON);
+        assert!((mesh.shortest_distance_to(&transform, target) - 2.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_from_buffers() {
+        let vertices = [DVec3::ZERO, DVec3::X, DVec3::Z];
+
+        assert!(TriMesh::from_buffers(&vertices, &[0, 1]).is_none());
+        assert!(TriMesh::from_buffers(&vertices, &[0, 1, 3]).is_none());
+        assert_eq!(1, TriMesh::from_buffers(&vertices, &[0, 2, 1]).unwrap().indices().len());
+    }
+
+    #[test]
+    fn test_from_mesh() {
+        let mesh = TriMesh::from_mesh(&Mesh::from(shape::Box::new(2.0, 2.0, 2.0))).unwrap();
+
+        assert_eq!(12, mesh.indices().len());
+        assert_eq!(3.0_f64.sqrt(), mesh.bounding_sphere().radius());
+
+        let found = mesh.triangles_in_aabb(DVec3::new(-0.5, 0.9, -0.5), DVec3::new(0.5, 1.1, 0.5));
+        assert_eq!(2, found.len());
+        for (_, triangle) in found {
+            assert_eq!(DVec3::Y, triangle.normal());
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use bevy::math::DVec3;
+
+use crate::{
+    physics::components::PhysTransform,
+    physics::shapes::SupportMap,
+};
+
+/// A single triangle of a TriMesh or HeightField, with its vertices in the local body coords of
+/// the shape it belongs to. The front face is the one from which the vertices appear anticlockwise.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Triangle {
+    pub vertices: [DVec3; 3],
+}
+
+impl Triangle {
+    /// Creates a new Triangle from the given vertices in local body coords.
+    pub fn new(a: DVec3, b: DVec3, c: DVec3) -> Self {
+        Self { vertices: [a, b, c] }
+    }
+
+    /// Returns the unit normal of the front face in local body coords, or zero if the triangle is
+    /// degenerate.
+    pub fn normal(&self) -> DVec3 {
+        let [a, b, c] = self.vertices;
+
+        (b - a).cross(c - a).normalize_or_zero()
+    }
+
+    /// Returns the minimum and maximum corners of the triangle's bounding box in local body coords.
+    pub fn bounds(&self) -> (DVec3, DVec3) {
+        let [a, b, c] = self.vertices;
+
+        (a.min(b).min(c), a.max(b).max(c))
+    }
+
+    /// Returns the closest point on the triangle to the given point, both in local body coords.
+    pub fn closest_point_to(&self, point: DVec3) -> DVec3 {
+        let [a, b, c] = self.vertices;
+        calc_closest_point_on_triangle(a, b, c, point)
+    }
+
+    /// Returns true if the given point, in local body coords, lies within the prism formed by
+    /// sweeping the triangle along its normal.
+    pub fn contains_projection_of(&self, point: DVec3) -> bool {
+        let [a, b, c] = self.vertices;
+        let normal = (b - a).cross(c - a);
+
+        [(a, b), (b, c), (c, a)].iter()
+            .all(|(start, end)| (*end - *start).cross(point - *start).dot(normal) >= 0.0)
+    }
+}
+
+impl SupportMap for Triangle {
+    fn support_point(&self, transform: &PhysTransform, direction: DVec3) -> DVec3 {
+        let local_direction = transform.get_direction_in_local_space(direction);
+
+        let vertex = self.vertices.iter()
+            .copied()
+            .max_by(|a, b| a.dot(local_direction).partial_cmp(&b.dot(local_direction)).unwrap())
+            .unwrap();
+
+        transform.get_point_in_global_space(vertex)
+    }
+}
+
+/// Shapes made up of many triangles, which can be queried for those near to a region of space.
+pub(crate) trait TriangleSet {
+    /// Returns the triangles, along with their indices, whose bounding boxes overlap the
+    /// axis-aligned box with the given minimum and maximum corners in local body coords.
+    fn triangles_in_aabb(&self, min: DVec3, max: DVec3) -> Vec<(usize, Triangle)>;
+
+    /// Returns the triangles, along with their indices, that may be hit by the ray with the given
+    /// origin and direction, within the given maximum time of impact, in local body coords.
+    fn triangles_along_ray(
+        &self,
+        origin: DVec3,
+        direction: DVec3,
+        max_toi: f64,
+    ) -> Vec<(usize, Triangle)>;
+
+    /// Returns the triangle nearest to the given point, along with the closest point on it, both in
+    /// local body coords.
+    fn closest_triangle_to(&self, point: DVec3) -> (Triangle, DVec3);
+}
+
+/// Returns the point on the triangle with the given vertices that is closest to the given point.
+pub(crate) fn calc_closest_point_on_triangle(a: DVec3, b: DVec3, c: DVec3, point: DVec3) -> DVec3 {
+    // Determine which of the triangle's Voronoi regions contains the point, as in Ericson's
+    // Real-Time Collision Detection (5.1.5).
+    let ab = b - a;
+    let ac = c - a;
+
+    let ap = point - a;
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0.0 && d2 <= 0.0 { return a; }
+
+    let bp = point - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0.0 && d4 <= d3 { return b; }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = point - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0.0 && d5 <= d6 { return c; }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let total = va + vb + vc;
+    if total == 0.0 {
+        // a degenerate triangle, where every region test above has failed.
+        return a;
+    }
+
+    a + ab * (vb / total) + ac * (vc / total)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_closest_point_to() {
+        let t = Triangle::new(DVec3::ZERO, DVec3::new(0.0, 0.0, 2.0), DVec3::new(2.0, 0.0, 0.0));
+
+        assert_eq!(DVec3::Y, t.normal());
+
+        // above the face.
+        assert_eq!(DVec3::new(0.5, 0.0, 0.5), t.closest_point_to(DVec3::new(0.5, 3.0, 0.5)));
+        assert!(t.contains_projection_of(DVec3::new(0.5, 3.0, 0.5)));
+
+        // beyond the hypotenuse.
+        assert_eq!(DVec3::new(1.0, 0.0, 1.0), t.closest_point_to(DVec3::new(2.0, 1.0, 2.0)));
+        assert!(!t.contains_projection_of(DVec3::new(2.0, 1.0, 2.0)));
+
+        // beyond a vertex.
+        assert_eq!(DVec3::ZERO, t.closest_point_to(DVec3::new(-1.0, 0.0, -1.0)));
+    }
+}
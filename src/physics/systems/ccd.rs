@@ -0,0 +1,330 @@
+use bevy::{
+    prelude::*,
+    math::DVec3,
+};
+
+use crate::{
+    constants,
+    physics::components::{
+        BoundaryCollider,
+        Ccd,
+        Collider,
+        CollisionGroups,
+        PhysTransform,
+        PreviousPhysTransform,
+        RigidBodyType,
+        Sensor,
+        Sleeping,
+    },
+    physics::resources::{
+        CollisionFilter,
+        PhysicsConfig,
+    },
+    physics::shapes::{
+        Capsule,
+        Collidable,
+        CollisionPrimative,
+        Compound,
+        ConvexHull,
+        Cuboid,
+        Cylinder,
+        HeightField,
+        Sphere,
+        SupportMap,
+        TriMesh,
+        calc_closest_point_on_segment,
+        gjk,
+    },
+    physics::systems::collision_detection::can_collide,
+};
+
+/// System labels covering sub-systems in the continuous collision detection process.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+enum CcdSystems {
+    Sweep,
+}
+
+/// A SystemSet that sweeps bodies with a Ccd component along their motion in the current step and
+/// clamps that motion to their first time of impact.
+pub fn get_system_set() -> SystemSet {
+    SystemSet::new()
+        .with_system(sweep_bodies.system()
+                     .label(CcdSystems::Sweep)
+        )
+}
+
+/// Sweeps the core sphere of each awake, Dynamic, body with a Ccd component from its PreviousPhysTransform
+/// to its current PhysTransform, against all other non-sensor colliders and boundaries in their
+/// current positions.
+///
+/// Where the sphere hits something, the body is moved back to the time of impact, plus the
+/// allowed penetration given in the PhysicsConfig, so that the contact is found during collision
+/// detection. The rotation and velocity of the body are unaffected and the remainder of its motion
+/// in the step is lost.
+#[allow(clippy::type_complexity)]
+fn sweep_bodies(
+    config: Res<PhysicsConfig>,
+    filter: Res<CollisionFilter>,
+    groups_query: Query<&CollisionGroups>,
+    boundary_query: Query<(Entity, &BoundaryCollider, &PhysTransform)>,
+    mut set: QuerySet<(
+        Query<(Entity, &Collider, &PreviousPhysTransform, &PhysTransform, Option<&RigidBodyType>),
+              (With<Ccd>, Without<Sensor>, Without<Sleeping>)>,
+        Query<(Entity, &Collider, &PhysTransform), Without<Sensor>>,
+        Query<&mut PhysTransform>,
+    )>,
+) {
+    let mut clamped = vec![];
+
+    for (entity, collider, previous, transform, body_type) in set.q0().iter() {
+        // Kinematic bodies always follow their own path.
+        if matches!(body_type, Some(t) if !t.is_dynamic()) { continue; }
+
+        let start = previous.translation;
+        let end = transform.translation();
+        let length = (end - start).length();
+
+        if length <= 0.0 { continue; }
+
+        let radius = calc_core_radius(collider.0.as_ref());
+        let mut time_of_impact: Option<f64> = None;
+
+        for (other, other_collider, other_transform) in set.q1().iter() {
+            if other == entity || !can_collide(entity, other, &groups_query, &filter) {
+                continue;
+            }
+
+            // cull targets whose bounding sphere is nowhere near the path of the body.
+            let bounding_radius = other_collider.0.bounding_sphere().radius();
+            if calc_distance_to_segment(other_transform.translation(), start, end)
+                > bounding_radius + radius
+            {
+                continue;
+            }
+
+            let toi = calc_time_of_impact(start, end, radius, |point| {
+                calc_distance(other_collider.0.as_ref(), other_transform, point)
+            });
+            time_of_impact = earliest(time_of_impact, toi);
+        }
+
+        for (bnd_ent, bnd, bnd_transform) in boundary_query.iter() {
+            if !can_collide(entity, bnd_ent, &groups_query, &filter) {
+                continue;
+            }
+
+            let toi = calc_time_of_impact(start, end, radius, |point| {
+                bnd.0.shortest_distance_to(bnd_transform, point)
+            });
+            time_of_impact = earliest(time_of_impact, toi);
+        }
+
+        if let Some(toi) = time_of_impact {
+            let t = toi + config.ccd_allowed_penetration / length;
+            if t < 1.0 {
+                clamped.push((entity, start.lerp(end, t)));
+            }
+        }
+    }
+
+    for (entity, translation) in clamped {
+        let mut transform = set.q2_mut().get_mut(entity)
+            .expect("Entity does not exist!");
+
+        transform.translation = translation;
+        transform.update();
+    }
+}
+
+// --- Helper methods
+
+/// Returns the radius of the largest sphere, centred on the given primative, that it contains.
+/// Sweeping this core sphere rather than the whole shape ensures the shape is in contact with
+/// whatever the sphere hits.
+///
+/// The core of a Compound is the largest sphere contained by any one of its convex children.
+/// TriMeshes and HeightFields are surfaces with no interior, so their core is a point.
+fn calc_core_radius(primative: &dyn CollisionPrimative) -> f64 {
+    if let Some(sphere) = primative.downcast_ref::<Sphere>() {
+        sphere.radius()
+    } else if let Some(cuboid) = primative.downcast_ref::<Cuboid>() {
+        cuboid.extents().min_element()
+    } else if let Some(capsule) = primative.downcast_ref::<Capsule>() {
+        capsule.radius()
+    } else if let Some(cylinder) = primative.downcast_ref::<Cylinder>() {
+        cylinder.radius().min(cylinder.half_height())
+    } else if let Some(hull) = primative.downcast_ref::<ConvexHull>() {
+        calc_inscribed_radius(hull, &PhysTransform::IDENTITY)
+    } else if let Some(compound) = primative.downcast_ref::<Compound>() {
+        compound.children().iter()
+            .filter_map(|(t, child)| Some(calc_inscribed_radius(child.as_support_map()?, t)))
+            .fold(0.0, f64::max)
+    } else {
+        0.0
+    }
+}
+
+/// Returns the distance from the origin to the surface of the given convex shape, with the given
+/// transform, or 0.0 if the origin lies outside it.
+fn calc_inscribed_radius(shape: &dyn SupportMap, transform: &PhysTransform) -> f64 {
+    match gjk::calc_surface_projection(shape, transform, DVec3::ZERO) {
+        Some((point, true)) => point.length(),
+        _ => 0.0,
+    }
+}
+
+/// Returns the shortest distance from the given primative, with the given transform, to the given
+/// point. Primatives with no distance calculation are represented by their bounding sphere.
+fn calc_distance(primative: &dyn CollisionPrimative, transform: &PhysTransform, point: DVec3) -> f64 {
+    if let Some(sphere) = primative.downcast_ref::<Sphere>() {
+        sphere.shortest_distance_to(transform, point)
+    } else if let Some(cuboid) = primative.downcast_ref::<Cuboid>() {
+        cuboid.shortest_distance_to(transform, point)
+    } else if let Some(capsule) = primative.downcast_ref::<Capsule>() {
+        capsule.shortest_distance_to(transform, point)
+    } else if let Some(cylinder) = primative.downcast_ref::<Cylinder>() {
+        cylinder.shortest_distance_to(transform, point)
+    } else if let Some(hull) = primative.downcast_ref::<ConvexHull>() {
+        hull.shortest_distance_to(transform, point)
+    } else if let Some(mesh) = primative.downcast_ref::<TriMesh>() {
+        mesh.shortest_distance_to(transform, point)
+    } else if let Some(field) = primative.downcast_ref::<HeightField>() {
+        field.shortest_distance_to(transform, point)
+    } else if let Some(compound) = primative.downcast_ref::<Compound>() {
+        compound.children().iter()
+            .map(|(t, child)| calc_distance(child.as_ref(), &transform.mul_transform(t), point))
+            .fold(f64::INFINITY, f64::min)
+    } else {
+        primative.bounding_sphere().shortest_distance_to(transform, point)
+    }
+}
+
+/// Returns the shortest distance from the given point to the line segment between start and end.
+fn calc_distance_to_segment(point: DVec3, start: DVec3, end: DVec3) -> f64 {
+    (point - calc_closest_point_on_segment(start, end, point)).length()
+}
+
+/// Returns the time of impact, as a proportion of the motion from start to end, of a sphere with
+/// the given radius moving in a straight line towards a convex shape with the given distance
+/// function. Returns None if the sphere does not reach the shape, or is already in contact with it
+/// at the start.
+///
+/// The time of impact is found by conservative advancement; the sphere is repeatedly advanced by
+/// the gap between it and the shape, which can never carry it past the surface.
+fn calc_time_of_impact(
+    start: DVec3,
+    end: DVec3,
+    radius: f64,
+    distance: impl Fn(DVec3) -> f64,
+) -> Option<f64> {
+    let length = (end - start).length();
+    let mut t = 0.0;
+    let mut gap = distance(start) - radius;
+
+    if gap <= 0.0 || length <= 0.0 { return None; }
+
+    for _ in 0..constants::CCD_MAX_ITERATIONS {
+        if gap < constants::CCD_TOLERANCE {
+            return Some(t);
+        }
+
+        t += gap / length;
+        if t > 1.0 { return None; }
+
+        gap = distance(start.lerp(end, t)) - radius;
+    }
+
+    None
+}
+
+/// Returns the earliest of the two given times of impact, if any.
+fn earliest(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        _ => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_calc_time_of_impact() {
+        // a thin wall, 0.1 thick, in the y-z plane at the origin.
+        let wall = Cuboid::new(DVec3::new(0.05, 10.0, 10.0));
+        let wall_transform = PhysTransform::IDENTITY;
+        let distance = |point| wall.shortest_distance_to(&wall_transform, point);
+
+        // a sphere of radius 0.5 jumping straight through it in one step.
+        let start = DVec3::new(-2.0, 0.0, 0.0);
+        let end = DVec3::new(2.0, 0.0, 0.0);
+        let toi = calc_time_of_impact(start, end, 0.5, distance).unwrap();
+        assert!((toi - 1.45 / 4.0).abs() < 0.001);
+
+        // one that falls short.
+        let end = DVec3::new(-1.0, 0.0, 0.0);
+        assert_eq!(None, calc_time_of_impact(start, end, 0.5, distance));
+
+        // one that passes beside it.
+        let start = DVec3::new(-2.0, 11.0, 0.0);
+        let end = DVec3::new(2.0, 11.0, 0.0);
+        assert_eq!(None, calc_time_of_impact(start, end, 0.5, distance));
+
+        // one already in contact.
+        let start = DVec3::new(-0.3, 0.0, 0.0);
+        assert_eq!(None, calc_time_of_impact(start, end, 0.5, distance));
+
+        // a sphere falling through a floor at an angle.
+        let floor = BoundaryCollider::default();
+        let floor_transform = PhysTransform::IDENTITY;
+        let start = DVec3::new(0.0, 2.0, 0.0);
+        let end = DVec3::new(3.0, -2.0, 0.0);
+        let toi = calc_time_of_impact(start, end, 1.0, |point| {
+            floor.0.shortest_distance_to(&floor_transform, point)
+        }).unwrap();
+        assert!((toi - 0.25).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_calc_core_radius() {
+        let hull = ConvexHull::new(vec![
+            DVec3::new(-1.0, -2.0, -3.0),
+            DVec3::new(1.0, 2.0, 3.0),
+            DVec3::new(1.0, -2.0, -3.0),
+            DVec3::new(-1.0, 2.0, 3.0),
+            DVec3::new(-1.0, 2.0, -3.0),
+            DVec3::new(1.0, -2.0, 3.0),
+            DVec3::new(-1.0, -2.0, 3.0),
+            DVec3::new(1.0, 2.0, -3.0),
+        ]);
+        assert!((calc_core_radius(&hull) - 1.0).abs() < 0.001);
+
+        // a dumbbell, whose core lies within the bar rather than either end.
+        let compound = Compound::new()
+            .with_child(PhysTransform::from_xyz(-2.0, 0.0, 0.0), Sphere::new(1.0))
+            .with_child(PhysTransform::from_xyz(2.0, 0.0, 0.0), Sphere::new(1.0))
+            .with_child(PhysTransform::IDENTITY, Cuboid::new(DVec3::new(2.0, 0.2, 0.2)));
+        assert!((calc_core_radius(&compound) - 0.2).abs() < 0.001);
+
+        let mesh = TriMesh::new(
+            vec![
+                DVec3::new(-1.0, 0.0, -1.0),
+                DVec3::new(1.0, 0.0, -1.0),
+                DVec3::new(0.0, 0.0, 1.0),
+            ],
+            vec![[0, 1, 2]],
+        );
+        assert_eq!(0.0, calc_core_radius(&mesh));
+    }
+
+    #[test]
+    fn test_calc_distance_to_segment() {
+        let start = DVec3::new(0.0, 0.0, 0.0);
+        let end = DVec3::new(4.0, 0.0, 0.0);
+
+        assert_eq!(1.0, calc_distance_to_segment(DVec3::new(2.0, 1.0, 0.0), start, end));
+        assert_eq!(1.0, calc_distance_to_segment(DVec3::new(-1.0, 0.0, 0.0), start, end));
+        assert_eq!(2.0, calc_distance_to_segment(DVec3::new(6.0, 0.0, 0.0), start, end));
+    }
+}
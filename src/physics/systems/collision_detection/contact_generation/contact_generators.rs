@@ -9,6 +9,7 @@ use crate::{
         PhysTransform,
     },
     physics::shapes::*,
+    physics::shapes::triangle::TriangleSet,
 };
 
 // In general, the generated contact normal must point from the first body in the returned
@@ -45,6 +46,8 @@ pub fn sphere_and_sphere(
 
     Some(Contact {
         entities: vec![ent1, ent2],
+        boundary: None,
+        feature_id: 0,
         normal,
         penetration,
         point,
@@ -57,6 +60,7 @@ pub fn sphere_and_sphere(
 /// considered to be immovable.
 pub fn half_space_and_sphere(
     ent_sphere: Entity,
+    ent_plane: Entity,
     plane: &Plane,
     sphere: &Sphere,
     sphere_transform: &PhysTransform,
@@ -76,6 +80,8 @@ pub fn half_space_and_sphere(
 
     Some(Contact {
         entities: vec![ent_sphere],
+        boundary: Some(ent_plane),
+        feature_id: 0,
         normal,
         penetration,
         point,
@@ -110,6 +116,8 @@ pub fn sphere_and_cuboid(
 
     Some(Contact {
         entities: vec![ent_sphere, ent_cuboid],
+        boundary: None,
+        feature_id: 0,
         normal,
         penetration: shortest_dist.abs(),
         point: closest_point,
@@ -122,6 +130,7 @@ pub fn sphere_and_cuboid(
 /// be intersecting. Contact normal is the inverted half-space normal.
 pub fn half_space_and_cuboid(
     ent_cuboid: Entity,
+    ent_plane: Entity,
     plane: &Plane,
     cuboid: &Cuboid,
     plane_transform: &PhysTransform,
@@ -129,7 +138,8 @@ pub fn half_space_and_cuboid(
 ) -> Option<Vec<Contact>> {
     let mut contacts = vec![];
 
-    for vertex_position in cuboid.vertices(cuboid_transform).iter() {
+    // each contact is identified by the index of the contacting vertex.
+    for (i, vertex_position) in cuboid.vertices(cuboid_transform).iter().enumerate() {
         let vertex_dist = plane.shortest_distance_to(plane_transform, *vertex_position);
         if vertex_dist <= 0.0 {
             let normal = -plane.normal();
@@ -139,6 +149,8 @@ pub fn half_space_and_cuboid(
 
             contacts.push(Contact {
                 entities: vec![ent_cuboid],
+                boundary: Some(ent_plane),
+                feature_id: i as u32,
                 normal,
                 penetration,
                 point,
@@ -215,7 +227,7 @@ pub fn cuboid_and_cuboid(
     // At this point we must have found a penetrating case, otherwise an error has occurred.
     assert!(case != usize::MAX);
 
-    let contact = if case < 3 {
+    let mut contact = if case < 3 {
         // face of cuboid 1, vertex of cuboid 2.
         let axis_idx = case;
         calc_cuboid_face_vertex_contact(ent1, ent2, c2, c1_transform, c2_transform, axis_idx,
@@ -235,6 +247,9 @@ pub fn cuboid_and_cuboid(
         panic!("incorrect case enumeration ({})!", case);
     };
 
+    // identify the contact by the case as well as the feature(s) within it.
+    contact.feature_id += case as u32 * 8;
+
     Some(contact)
 }
 
@@ -289,6 +304,8 @@ fn calc_cuboid_edge_edge_contact(
 
     Contact {
         entities: vec![ent1, ent2],
+        boundary: None,
+        feature_id: 0,
         normal,
         penetration,
         point: contact_point,
@@ -359,16 +376,20 @@ fn calc_cuboid_face_vertex_contact(
         normal = normal * -1.0;
     }
 
-    // Find contacting vertex of cuboid 2.
+    // Find contacting vertex of cuboid 2, identified by the axes along which it is flipped.
     let mut vertex = c2.extents();
+    let mut feature_id = 0;
     if c2_transform.axis(0).dot(normal) < 0.0 {
         vertex.x = -vertex.x;
+        feature_id |= 1;
     }
     if c2_transform.axis(1).dot(normal) < 0.0 {
         vertex.y = -vertex.y;
+        feature_id |= 2;
     }
     if c2_transform.axis(2).dot(normal) < 0.0 {
         vertex.z = -vertex.z;
+        feature_id |= 4;
     }
 
     // Convert to world coords.
@@ -376,6 +397,8 @@ fn calc_cuboid_face_vertex_contact(
 
     Contact {
         entities: vec![ent1, ent2],
+        boundary: None,
+        feature_id,
         normal,
         penetration,
         point: vertex,
@@ -409,25 +432,1239 @@ fn check_axis(
     true
 }
 
-/// Returns the overlap of the two given cuboids when projected onto the given axis. If the cuboids
-/// are not overlapping, returns None.
-fn penetration_on_axis(
-    c1: &Cuboid,
-    c2: &Cuboid,
-    c1_transform: &PhysTransform,
-    c2_transform: &PhysTransform,
-    axis: DVec3,
-    centre_to_centre: DVec3,
-) -> Option<f64> {
-    let distance = centre_to_centre.dot(axis).abs();
+/// Returns the overlap of the two given cuboids when projected onto the given axis. If the cuboids
+/// are not overlapping, returns None.
+fn penetration_on_axis(
+    c1: &Cuboid,
+    c2: &Cuboid,
+    c1_transform: &PhysTransform,
+    c2_transform: &PhysTransform,
+    axis: DVec3,
+    centre_to_centre: DVec3,
+) -> Option<f64> {
+    let distance = centre_to_centre.dot(axis).abs();
+
+    let overlap = c1.project_onto_axis(c1_transform, axis) +
+        c2.project_onto_axis(c2_transform, axis) - distance;
+
+    if overlap < 0.0 {
+        return None;
+    }
+    Some(overlap)
+}
+
+/// Evaluates a sphere and capsule for intersection, generating a Contact if they are found to be
+/// intersecting. Contact normal is in the direction sphere to capsule.
+pub fn sphere_and_capsule(
+    ent_sphere: Entity,
+    ent_capsule: Entity,
+    sphere: &Sphere,
+    capsule: &Capsule,
+    sphere_transform: &PhysTransform,
+    capsule_transform: &PhysTransform,
+) -> Option<Contact> {
+    let sphere_centre = sphere_transform.translation();
+    let (start, end) = capsule.segment(capsule_transform);
+    let segment_point = calc_closest_point_on_segment(start, end, sphere_centre);
+
+    calc_spheres_contact(ent_sphere, ent_capsule, sphere_centre, sphere.radius(), segment_point,
+                         capsule.radius(), sphere_transform, capsule_transform,
+                         capsule_transform.axis(0))
+}
+
+/// Evaluates two capsules for intersection, generating a Contact if they are found to be
+/// intersecting. Contact normal is from capsule 1 to capsule 2.
+pub fn capsule_and_capsule(
+    ent1: Entity,
+    ent2: Entity,
+    c1: &Capsule,
+    c2: &Capsule,
+    c1_transform: &PhysTransform,
+    c2_transform: &PhysTransform,
+) -> Option<Contact> {
+    let (start1, end1) = c1.segment(c1_transform);
+    let (start2, end2) = c2.segment(c2_transform);
+    let (point1, point2) = calc_closest_points_between_segments(start1, end1, start2, end2);
+
+    calc_spheres_contact(ent1, ent2, point1, c1.radius(), point2, c2.radius(), c1_transform,
+                         c2_transform, c1_transform.axis(0))
+}
+
+/// Evaluates a half-space and capsule for intersection, generating a Contact for each of the
+/// capsule's hemispherical ends that is found to be intersecting. Contact normal is the inverted
+/// half-space normal.
+pub fn half_space_and_capsule(
+    ent_capsule: Entity,
+    ent_plane: Entity,
+    plane: &Plane,
+    capsule: &Capsule,
+    plane_transform: &PhysTransform,
+    capsule_transform: &PhysTransform,
+) -> Option<Vec<Contact>> {
+    let mut contacts = vec![];
+    let (start, end) = capsule.segment(capsule_transform);
+
+    // each contact is identified by the index of the contacting end.
+    for (i, centre) in [start, end].iter().enumerate() {
+        let penetration = capsule.radius() - plane.shortest_distance_to(plane_transform, *centre);
+
+        if penetration > 0.0 {
+            let point = plane.closest_point_to(plane_transform, *centre);
+
+            contacts.push(Contact {
+                entities: vec![ent_capsule],
+                boundary: Some(ent_plane),
+                feature_id: i as u32,
+                normal: -plane.normal(),
+                penetration,
+                point,
+                relative_points: vec![point - capsule_transform.translation],
+            });
+        }
+    }
+
+    if contacts.is_empty() {
+        return None;
+    }
+    Some(contacts)
+}
+
+/// Evaluates a capsule and cuboid for intersection, generating a Contact if they are found to be
+/// intersecting. Contact normal is in the direction capsule to cuboid.
+pub fn capsule_and_cuboid(
+    ent_capsule: Entity,
+    ent_cuboid: Entity,
+    capsule: &Capsule,
+    cuboid: &Cuboid,
+    capsule_transform: &PhysTransform,
+    cuboid_transform: &PhysTransform,
+) -> Option<Contact> {
+    let (start, end) = capsule.segment(capsule_transform);
+    let (segment_point, cuboid_point) = calc_closest_points_to_segment(start, end, |point| {
+        cuboid.closest_point_to(cuboid_transform, point)
+    });
+
+    let d = (cuboid_point - segment_point).length();
+
+    if d >= capsule.radius() {
+        return None;
+    }
+    if d > DEEP_CONTACT_THRESHOLD {
+        return Some(calc_surface_contact(ent_capsule, ent_cuboid, segment_point, cuboid_point,
+                                         capsule.radius(), capsule_transform, cuboid_transform));
+    }
+
+    // the central segment penetrates the cuboid, so find the axis of least penetration instead.
+    let capsule_axis = capsule_transform.axis(1);
+    let mut axes = vec![(capsule_axis, AxisFeature::Edge)];
+    for i in 0..3 {
+        axes.push((cuboid_transform.axis(i), AxisFeature::Face2));
+        axes.push((capsule_axis.cross(cuboid_transform.axis(i)), AxisFeature::Edge));
+    }
+
+    calc_sat_contact(ent_capsule, ent_cuboid, capsule, cuboid, capsule_transform, cuboid_transform,
+                     &axes)
+}
+
+/// Evaluates a sphere and cylinder for intersection, generating a Contact if they are found to be
+/// intersecting. Contact normal is in the direction sphere to cylinder.
+pub fn sphere_and_cylinder(
+    ent_sphere: Entity,
+    ent_cylinder: Entity,
+    sphere: &Sphere,
+    cylinder: &Cylinder,
+    sphere_transform: &PhysTransform,
+    cylinder_transform: &PhysTransform,
+) -> Option<Contact> {
+    let sphere_centre = sphere_transform.translation();
+    let closest_point = cylinder.closest_point_to(cylinder_transform, sphere_centre);
+
+    let d = (closest_point - sphere_centre).length();
+
+    if d >= sphere.radius() {
+        return None;
+    }
+    if d > DEEP_CONTACT_THRESHOLD {
+        return Some(calc_surface_contact(ent_sphere, ent_cylinder, sphere_centre, closest_point,
+                                         sphere.radius(), sphere_transform, cylinder_transform));
+    }
+
+    // the sphere's centre is inside the cylinder, so find the axis of least penetration instead.
+    let axes = [
+        (cylinder_transform.axis(1), AxisFeature::Face2),
+        (calc_radial_direction(cylinder_transform, sphere_centre), AxisFeature::Face2),
+    ];
+
+    calc_sat_contact(ent_sphere, ent_cylinder, sphere, cylinder, sphere_transform,
+                     cylinder_transform, &axes)
+}
+
+/// Evaluates a capsule and cylinder for intersection, generating a Contact if they are found to be
+/// intersecting. Contact normal is in the direction capsule to cylinder.
+pub fn capsule_and_cylinder(
+    ent_capsule: Entity,
+    ent_cylinder: Entity,
+    capsule: &Capsule,
+    cylinder: &Cylinder,
+    capsule_transform: &PhysTransform,
+    cylinder_transform: &PhysTransform,
+) -> Option<Contact> {
+    let (start, end) = capsule.segment(capsule_transform);
+    let (segment_point, cylinder_point) = calc_closest_points_to_segment(start, end, |point| {
+        cylinder.closest_point_to(cylinder_transform, point)
+    });
+
+    let d = (cylinder_point - segment_point).length();
+
+    if d >= capsule.radius() {
+        return None;
+    }
+    if d > DEEP_CONTACT_THRESHOLD {
+        return Some(calc_surface_contact(ent_capsule, ent_cylinder, segment_point, cylinder_point,
+                                         capsule.radius(), capsule_transform, cylinder_transform));
+    }
+
+    // the central segment penetrates the cylinder, so find the axis of least penetration instead.
+    let capsule_axis = capsule_transform.axis(1);
+    let cylinder_axis = cylinder_transform.axis(1);
+    let axes = [
+        (cylinder_axis, AxisFeature::Face2),
+        (calc_radial_direction(cylinder_transform, capsule_transform.translation()),
+         AxisFeature::Face2),
+        (capsule_axis, AxisFeature::Edge),
+        (capsule_axis.cross(cylinder_axis), AxisFeature::Edge),
+    ];
+
+    calc_sat_contact(ent_capsule, ent_cylinder, capsule, cylinder, capsule_transform,
+                     cylinder_transform, &axes)
+}
+
+/// Evaluates a cylinder and cuboid for intersection, generating Contact(s) if they are found to be
+/// intersecting. Where a cap or side of the cylinder lies flat against a face of the cuboid, the
+/// two are clipped against each other to give a contact at each penetrating vertex. Contact normal
+/// is in the direction cylinder to cuboid.
+pub fn cylinder_and_cuboid(
+    ent_cylinder: Entity,
+    ent_cuboid: Entity,
+    cylinder: &Cylinder,
+    cuboid: &Cuboid,
+    cylinder_transform: &PhysTransform,
+    cuboid_transform: &PhysTransform,
+) -> Option<Vec<Contact>> {
+    let cylinder_axis = cylinder_transform.axis(1);
+
+    // cuboid faces are tested first so that they are preferred when a cap lies flat on one.
+    let mut axes = vec![];
+    for i in 0..3 {
+        axes.push((cuboid_transform.axis(i), AxisFeature::Face2));
+    }
+    axes.push((cylinder_axis, AxisFeature::Face1));
+    for i in 0..3 {
+        axes.push((cylinder_axis.cross(cuboid_transform.axis(i)), AxisFeature::Edge));
+    }
+    axes.push((calc_radial_direction(cylinder_transform, cuboid_transform.translation()),
+               AxisFeature::Edge));
+
+    let contact = calc_sat_contact(ent_cylinder, ent_cuboid, cylinder, cuboid, cylinder_transform,
+                                   cuboid_transform, &axes)?;
+
+    let face1 = calc_cylinder_face(cylinder, cylinder_transform, contact.normal);
+    let face2 = calc_cuboid_face(cuboid, cuboid_transform, -contact.normal);
+
+    calc_clipped_contacts(ent_cylinder, ent_cuboid, &face1, &face2, &contact, cylinder_transform,
+                          cuboid_transform)
+        .or_else(|| Some(vec![contact]))
+}
+
+/// Evaluates two cylinders for intersection, generating Contact(s) if they are found to be
+/// intersecting. Where a cap or side of each cylinder lies flat against the other, the two are
+/// clipped against each other to give a contact at each penetrating vertex. Contact normal is from
+/// cylinder 1 to cylinder 2.
+pub fn cylinder_and_cylinder(
+    ent1: Entity,
+    ent2: Entity,
+    c1: &Cylinder,
+    c2: &Cylinder,
+    c1_transform: &PhysTransform,
+    c2_transform: &PhysTransform,
+) -> Option<Vec<Contact>> {
+    let axis1 = c1_transform.axis(1);
+    let axis2 = c2_transform.axis(1);
+
+    // the direction between the closest points of the two central axes.
+    let (point1, point2) = calc_closest_points_between_segments(
+        c1_transform.translation() - axis1 * c1.half_height(),
+        c1_transform.translation() + axis1 * c1.half_height(),
+        c2_transform.translation() - axis2 * c2.half_height(),
+        c2_transform.translation() + axis2 * c2.half_height(),
+    );
+
+    let axes = [
+        (axis1, AxisFeature::Face1),
+        (axis2, AxisFeature::Face2),
+        (axis1.cross(axis2), AxisFeature::Edge),
+        (calc_radial_direction(c1_transform, c2_transform.translation()), AxisFeature::Edge),
+        (calc_radial_direction(c2_transform, c1_transform.translation()), AxisFeature::Edge),
+        ((point2 - point1).normalize_or_zero(), AxisFeature::Edge),
+    ];
+
+    let contact = calc_sat_contact(ent1, ent2, c1, c2, c1_transform, c2_transform, &axes)?;
+
+    let face1 = calc_cylinder_face(c1, c1_transform, contact.normal);
+    let face2 = calc_cylinder_face(c2, c2_transform, -contact.normal);
+
+    calc_clipped_contacts(ent1, ent2, &face1, &face2, &contact, c1_transform, c2_transform)
+        .or_else(|| Some(vec![contact]))
+}
+
+/// Evaluates a half-space and cylinder for intersection, generating Contact(s) if they are found
+/// to be intersecting. Contact normal is the inverted half-space normal.
+pub fn half_space_and_cylinder(
+    ent_cylinder: Entity,
+    ent_plane: Entity,
+    plane: &Plane,
+    cylinder: &Cylinder,
+    plane_transform: &PhysTransform,
+    cylinder_transform: &PhysTransform,
+) -> Option<Vec<Contact>> {
+    let mut contacts = vec![];
+    let normal = -plane.normal();
+    let axis = cylinder_transform.axis(1);
+
+    // The deepest point on the rim of each cap lies in the direction of the half-space, projected
+    // onto the plane of the cap. Where the caps are (nearly) parallel to the plane, four points
+    // around each rim are tested instead so that the cylinder can rest on its end.
+    let radial = normal - axis * normal.dot(axis);
+    let rim_directions = if radial.length_squared() > FLAT_CAP_THRESHOLD {
+        vec![radial.normalize()]
+    } else {
+        let u = cylinder_transform.axis(0);
+        let w = cylinder_transform.axis(2);
+        vec![u, w, -u, -w]
+    };
+
+    // each contact is identified by its cap and its position on the rim.
+    for (i, cap) in [-cylinder.half_height(), cylinder.half_height()].iter().enumerate() {
+        for (j, direction) in rim_directions.iter().enumerate() {
+            let vertex = cylinder_transform.translation() + axis * *cap
+                + *direction * cylinder.radius();
+            let vertex_dist = plane.shortest_distance_to(plane_transform, vertex);
+
+            if vertex_dist <= 0.0 {
+                // contact point is mid-point between vertex and plane.
+                let point = vertex - normal * (vertex_dist.abs() * 0.5);
+
+                contacts.push(Contact {
+                    entities: vec![ent_cylinder],
+                    boundary: Some(ent_plane),
+                    feature_id: (i * 4 + j) as u32,
+                    normal,
+                    penetration: vertex_dist.abs(),
+                    point,
+                    relative_points: vec![point - cylinder_transform.translation],
+                });
+            }
+        }
+    }
+
+    if contacts.is_empty() {
+        return None;
+    }
+    Some(contacts)
+}
+
+/// Evaluates any two convex shapes that can be described by a support mapping for intersection,
+/// using the GJK and EPA algorithms, generating a Contact if they are found to be intersecting.
+/// Contact normal is from shape 1 to shape 2.
+pub fn convex_and_convex(
+    ent1: Entity,
+    ent2: Entity,
+    a: &dyn SupportMap,
+    b: &dyn SupportMap,
+    a_transform: &PhysTransform,
+    b_transform: &PhysTransform,
+) -> Option<Contact> {
+    let simplex = match gjk::gjk(a, a_transform, b, b_transform) {
+        gjk::GjkResult::Intersecting(simplex) => simplex,
+        gjk::GjkResult::Separated { .. } => return None,
+    };
+
+    let penetration = gjk::epa(a, a_transform, b, b_transform, simplex)?;
+    if penetration.depth <= 0.0 {
+        return None;
+    }
+
+    // contact point is mid-way between the deepest points of each shape.
+    let point = (penetration.point_a + penetration.point_b) * 0.5;
+
+    Some(Contact {
+        entities: vec![ent1, ent2],
+        boundary: None,
+        feature_id: 0,
+        normal: penetration.normal,
+        penetration: penetration.depth,
+        point,
+        relative_points: vec![point - a_transform.translation, point - b_transform.translation],
+    })
+}
+
+/// Evaluates a half-space and convex hull for intersection, generating Contact(s) if they are
+/// found to be intersecting. Contact normal is the inverted half-space normal.
+pub fn half_space_and_convex_hull(
+    ent_hull: Entity,
+    ent_plane: Entity,
+    plane: &Plane,
+    hull: &ConvexHull,
+    plane_transform: &PhysTransform,
+    hull_transform: &PhysTransform,
+) -> Option<Vec<Contact>> {
+    let mut contacts = vec![];
+    let normal = -plane.normal();
+
+    // each contact is identified by the index of the contacting point.
+    for (i, vertex) in hull.vertices(hull_transform).iter().enumerate() {
+        let vertex_dist = plane.shortest_distance_to(plane_transform, *vertex);
+        if vertex_dist <= 0.0 {
+            // contact point is mid-point between vertex and plane.
+            let point = *vertex - normal * (vertex_dist.abs() * 0.5);
+
+            contacts.push(Contact {
+                entities: vec![ent_hull],
+                boundary: Some(ent_plane),
+                feature_id: i as u32,
+                normal,
+                penetration: vertex_dist.abs(),
+                point,
+                relative_points: vec![point - hull_transform.translation],
+            });
+        }
+    }
+
+    if contacts.is_empty() {
+        return None;
+    }
+    Some(contacts)
+}
+
+/// Evaluates a sphere and triangle mesh for intersection, generating Contact(s) if they are found
+/// to be intersecting. Contact normal is from the sphere to the mesh.
+pub fn sphere_and_tri_mesh(
+    ent_sphere: Entity,
+    ent_mesh: Entity,
+    sphere: &Sphere,
+    mesh: &TriMesh,
+    sphere_transform: &PhysTransform,
+    mesh_transform: &PhysTransform,
+) -> Option<Vec<Contact>> {
+    calc_sphere_and_triangles(ent_sphere, ent_mesh, sphere, mesh, sphere_transform, mesh_transform)
+}
+
+/// Evaluates a sphere and height field for intersection, generating Contact(s) if they are found
+/// to be intersecting. Contact normal is from the sphere to the height field.
+pub fn sphere_and_height_field(
+    ent_sphere: Entity,
+    ent_field: Entity,
+    sphere: &Sphere,
+    field: &HeightField,
+    sphere_transform: &PhysTransform,
+    field_transform: &PhysTransform,
+) -> Option<Vec<Contact>> {
+    calc_sphere_and_triangles(ent_sphere, ent_field, sphere, field, sphere_transform, field_transform)
+}
+
+/// Evaluates a cuboid and triangle mesh for intersection, generating Contact(s) if they are found
+/// to be intersecting. Contact normal is from the cuboid to the mesh.
+pub fn cuboid_and_tri_mesh(
+    ent_cuboid: Entity,
+    ent_mesh: Entity,
+    cuboid: &Cuboid,
+    mesh: &TriMesh,
+    cuboid_transform: &PhysTransform,
+    mesh_transform: &PhysTransform,
+) -> Option<Vec<Contact>> {
+    calc_cuboid_and_triangles(ent_cuboid, ent_mesh, cuboid, mesh, cuboid_transform, mesh_transform)
+}
+
+/// Evaluates a cuboid and height field for intersection, generating Contact(s) if they are found
+/// to be intersecting. Contact normal is from the cuboid to the height field.
+pub fn cuboid_and_height_field(
+    ent_cuboid: Entity,
+    ent_field: Entity,
+    cuboid: &Cuboid,
+    field: &HeightField,
+    cuboid_transform: &PhysTransform,
+    field_transform: &PhysTransform,
+) -> Option<Vec<Contact>> {
+    calc_cuboid_and_triangles(ent_cuboid, ent_field, cuboid, field, cuboid_transform, field_transform)
+}
+
+/// Evaluates a convex shape, with the given bounding radius, and triangle mesh for intersection,
+/// generating Contact(s) if they are found to be intersecting. Contact normal is from the shape to
+/// the mesh.
+pub fn convex_and_tri_mesh(
+    ent_shape: Entity,
+    ent_mesh: Entity,
+    shape: &dyn SupportMap,
+    radius: f64,
+    mesh: &TriMesh,
+    shape_transform: &PhysTransform,
+    mesh_transform: &PhysTransform,
+) -> Option<Vec<Contact>> {
+    calc_support_map_and_triangles(
+        ent_shape, ent_mesh, shape, radius, mesh, shape_transform, mesh_transform,
+    )
+}
+
+/// Evaluates a convex shape, with the given bounding radius, and height field for intersection,
+/// generating Contact(s) if they are found to be intersecting. Contact normal is from the shape to
+/// the height field.
+pub fn convex_and_height_field(
+    ent_shape: Entity,
+    ent_field: Entity,
+    shape: &dyn SupportMap,
+    radius: f64,
+    field: &HeightField,
+    shape_transform: &PhysTransform,
+    field_transform: &PhysTransform,
+) -> Option<Vec<Contact>> {
+    calc_support_map_and_triangles(
+        ent_shape, ent_field, shape, radius, field, shape_transform, field_transform,
+    )
+}
+
+// Closest points nearer than this are treated as a deep contact, for which a normal cannot be
+// reliably derived from the points.
+const DEEP_CONTACT_THRESHOLD: f64 = 0.0001;
+
+// Cylinder caps whose squared sine of the angle to a plane is below this are treated as flat.
+const FLAT_CAP_THRESHOLD: f64 = 0.01;
+
+// The number of iterations used to search a segment for its closest point to a shape.
+const SEGMENT_SEARCH_ITERATIONS: usize = 64;
+
+// Triangle contacts whose normal is within this cosine of the face normal are treated as face
+// contacts.
+const FACE_CONTACT_THRESHOLD: f64 = 0.99;
+
+// The tilt, away from the contact normal, of the directions in which a convex shape's support
+// points are sampled for contacts against the face of a triangle.
+const FACE_SUPPORT_TILT: f64 = 0.2;
+
+// Contact points nearer than this to an existing contact, e.g. on an edge shared by two triangles,
+// are discarded.
+const DUPLICATE_CONTACT_THRESHOLD: f64 = 0.0001;
+
+// The number of points around the rim of a cylinder's cap used to clip it against another face.
+const CAP_RIM_POINTS: usize = 8;
+
+/// Generates the contact data for a pair of spheres, with the given centres and radii, that
+/// belong to the given bodies, or returns None if they are not intersecting. The fallback normal
+/// is used if the centres coincide.
+#[allow(clippy::too_many_arguments)]
+fn calc_spheres_contact(
+    ent1: Entity,
+    ent2: Entity,
+    centre1: DVec3,
+    radius1: f64,
+    centre2: DVec3,
+    radius2: f64,
+    transform1: &PhysTransform,
+    transform2: &PhysTransform,
+    fallback_normal: DVec3,
+) -> Option<Contact> {
+    let midline = centre2 - centre1;
+    let d = midline.length();
+    let sum_of_radii = radius1 + radius2;
+
+    if d >= sum_of_radii {
+        return None;
+    }
+
+    let normal = if d > 0.0 { midline / d } else { fallback_normal };
+    let penetration = sum_of_radii - d;
+    // contact point is mid-way through the overlap.
+    let point = centre1 + normal * (radius1 - penetration * 0.5);
+
+    Some(Contact {
+        entities: vec![ent1, ent2],
+        boundary: None,
+        feature_id: 0,
+        normal,
+        penetration,
+        point,
+        relative_points: vec![point - transform1.translation, point - transform2.translation],
+    })
+}
+
+/// Generates the contact data for a body, represented by a sphere with the given centre and
+/// radius, whose surface is penetrated by the given closest point on another body.
+fn calc_surface_contact(
+    ent1: Entity,
+    ent2: Entity,
+    centre: DVec3,
+    closest_point: DVec3,
+    radius: f64,
+    transform1: &PhysTransform,
+    transform2: &PhysTransform,
+) -> Contact {
+    let midline = closest_point - centre;
+    let d = midline.length();
+
+    Contact {
+        entities: vec![ent1, ent2],
+        boundary: None,
+        feature_id: 0,
+        normal: midline / d,
+        penetration: radius - d,
+        point: closest_point,
+        relative_points: vec![closest_point - transform1.translation,
+            closest_point - transform2.translation],
+    }
+}
+
+/// Returns the unit direction, perpendicular to the central axis of the given cylinder transform,
+/// from the axis towards the given point. Zero if the point lies on the axis.
+fn calc_radial_direction(transform: &PhysTransform, point: DVec3) -> DVec3 {
+    let axis = transform.axis(1);
+    let offset = point - transform.translation();
+
+    (offset - axis * offset.dot(axis)).normalize_or_zero()
+}
+
+/// Returns the closest points on each of a pair of line segments, given the start and end points
+/// of each.
+fn calc_closest_points_between_segments(
+    start1: DVec3,
+    end1: DVec3,
+    start2: DVec3,
+    end2: DVec3,
+) -> (DVec3, DVec3) {
+    // Let the closest points be q1 = start1 + s * d1 and q2 = start2 + t * d2, with s and t
+    // clamped to the segments.
+    let d1 = end1 - start1;
+    let d2 = end2 - start2;
+    let r = start1 - start2;
+    let a = d1.length_squared();
+    let e = d2.length_squared();
+    let f = d2.dot(r);
+
+    if a <= f64::EPSILON && e <= f64::EPSILON {
+        return (start1, start2);
+    }
+
+    let (s, t) = if a <= f64::EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(r);
+
+        if e <= f64::EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(d2);
+            let denom = a * e - b * b;
+
+            // if the segments are parallel pick an arbitrary s.
+            let s = if denom != 0.0 { ((b * f - c * e) / denom).clamp(0.0, 1.0) } else { 0.0 };
+            let t = (b * s + f) / e;
+
+            if t < 0.0 {
+                ((-c / a).clamp(0.0, 1.0), 0.0)
+            } else if t > 1.0 {
+                (((b - c) / a).clamp(0.0, 1.0), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+
+    (start1 + d1 * s, start2 + d2 * t)
+}
+
+/// Returns the point on the line segment between start and end that is closest to a convex shape,
+/// along with the closest point on the shape to it, given a function returning the closest point on
+/// the shape to any other point.
+fn calc_closest_points_to_segment(
+    start: DVec3,
+    end: DVec3,
+    closest_point: impl Fn(DVec3) -> DVec3,
+) -> (DVec3, DVec3) {
+    // The distance to a convex shape is convex along the segment, so a ternary search finds the
+    // minimum.
+    let distance_sq = |t: f64| {
+        let point = start.lerp(end, t);
+        (closest_point(point) - point).length_squared()
+    };
+
+    let mut low = 0.0;
+    let mut high = 1.0;
+    for _ in 0..SEGMENT_SEARCH_ITERATIONS {
+        let t1 = low + (high - low) / 3.0;
+        let t2 = high - (high - low) / 3.0;
+
+        if distance_sq(t1) < distance_sq(t2) {
+            high = t2;
+        } else {
+            low = t1;
+        }
+    }
+
+    let point = start.lerp(end, (low + high) * 0.5);
+    (point, closest_point(point))
+}
+
+/// Generates contacts between a sphere and each nearby triangle of the given set that it
+/// penetrates, or returns None if there are none.
+///
+/// Contacts with the edges or vertices of triangles are discarded where they lie in the plane of a
+/// face that the sphere is in contact with, as they are internal to a flat surface.
+fn calc_sphere_and_triangles(
+    ent_sphere: Entity,
+    ent_set: Entity,
+    sphere: &Sphere,
+    set: &dyn TriangleSet,
+    sphere_transform: &PhysTransform,
+    set_transform: &PhysTransform,
+) -> Option<Vec<Contact>> {
+    let radius = sphere.radius();
+    let centre = set_transform.get_point_in_local_space(sphere_transform.translation());
+
+    // Contacts with the interior of a face, and with the edges or vertices of triangles.
+    let mut face_contacts: Vec<Contact> = vec![];
+    let mut edge_contacts: Vec<Contact> = vec![];
+
+    // each contact is identified by the index of the penetrated triangle.
+    for (i, triangle) in set.triangles_in_aabb(centre - DVec3::splat(radius), centre + DVec3::splat(radius)) {
+        let closest = triangle.closest_point_to(centre);
+        let d = (closest - centre).length();
+
+        if d >= radius { continue; }
+
+        // a sphere centred on the triangle is pushed out of its front face.
+        let normal = if d > DEEP_CONTACT_THRESHOLD {
+            (closest - centre) / d
+        } else {
+            -triangle.normal()
+        };
+        if normal == DVec3::ZERO { continue; }
+
+        let point = set_transform.get_point_in_global_space(closest);
+        let contact = Contact {
+            entities: vec![ent_sphere, ent_set],
+            boundary: None,
+            feature_id: i as u32,
+            normal: set_transform.get_direction_in_global_space(normal),
+            penetration: radius - d,
+            point,
+            relative_points: vec![point - sphere_transform.translation,
+                point - set_transform.translation],
+        };
+
+        if triangle.contains_projection_of(centre) {
+            face_contacts.push(contact);
+        } else {
+            edge_contacts.push(contact);
+        }
+    }
+
+    let mut contacts: Vec<Contact> = vec![];
+
+    for contact in face_contacts {
+        if !is_duplicate_contact(&contacts, contact.point) {
+            contacts.push(contact);
+        }
+    }
+
+    for contact in edge_contacts {
+        let internal = contacts.iter()
+            .any(|c| (contact.point - c.point).dot(c.normal) > -DUPLICATE_CONTACT_THRESHOLD);
+
+        if !internal && !is_duplicate_contact(&contacts, contact.point) {
+            contacts.push(contact);
+        }
+    }
+
+    if contacts.is_empty() {
+        return None;
+    }
+    Some(contacts)
+}
+
+/// Generates contacts between a cuboid and each nearby triangle of the given set that it
+/// penetrates, or returns None if there are none. The cuboid's vertices are the candidate contact
+/// points against the face of a triangle.
+fn calc_cuboid_and_triangles(
+    ent_cuboid: Entity,
+    ent_set: Entity,
+    cuboid: &Cuboid,
+    set: &dyn TriangleSet,
+    cuboid_transform: &PhysTransform,
+    set_transform: &PhysTransform,
+) -> Option<Vec<Contact>> {
+    let vertices = cuboid.vertices(cuboid_transform);
+
+    calc_convex_and_triangles(
+        ent_cuboid,
+        ent_set,
+        cuboid,
+        cuboid.bounding_sphere().radius(),
+        set,
+        cuboid_transform,
+        set_transform,
+        &|_| vertices.to_vec(),
+        &|point| {
+            cuboid_transform.get_point_in_local_space(point).abs().cmple(cuboid.extents()).all()
+        },
+    )
+}
+
+/// Generates contacts between a convex shape, with the given bounding radius, and each nearby
+/// triangle of the given set that it penetrates, or returns None if there are none. The candidate
+/// contact points against a face are the shape's support points in eight directions tilted a
+/// little away from the contact normal, which find the corners, ends and rims that rest on it.
+fn calc_support_map_and_triangles(
+    ent_shape: Entity,
+    ent_set: Entity,
+    shape: &dyn SupportMap,
+    radius: f64,
+    set: &dyn TriangleSet,
+    shape_transform: &PhysTransform,
+    set_transform: &PhysTransform,
+) -> Option<Vec<Contact>> {
+    let support_points = |normal: DVec3| {
+        let tangent = normal.cross(if normal.x.abs() < 0.9 { DVec3::X } else { DVec3::Y })
+            .normalize();
+        let bitangent = normal.cross(tangent);
+
+        (0..8)
+            .map(|k| {
+                let (sin, cos) = (k as f64 * std::f64::consts::FRAC_PI_4).sin_cos();
+                let tilt = (tangent * cos + bitangent * sin) * FACE_SUPPORT_TILT;
+
+                shape.support_point(shape_transform, normal + tilt)
+            })
+            .collect()
+    };
+
+    calc_convex_and_triangles(
+        ent_shape,
+        ent_set,
+        shape,
+        radius,
+        set,
+        shape_transform,
+        set_transform,
+        &support_points,
+        &|point| gjk::calc_closest_point(shape, shape_transform, point).is_none(),
+    )
+}
+
+/// Generates contacts between a convex shape, with the given bounding radius, and each nearby
+/// triangle of the given set that it penetrates, or returns None if there are none.
+///
+/// Where the shape presses against the face of a triangle, a contact is generated for each of the
+/// shape's candidate contact points, given the face normal, that penetrates the face, and for each
+/// vertex of the triangle within the shape, so that the shape can rest stably. Otherwise a single
+/// contact is found by GJK/EPA. Contacts with the edges or vertices of triangles are discarded
+/// where they lie in the plane of a face that the shape is in contact with, as they are internal
+/// to a flat surface.
+#[allow(clippy::too_many_arguments)]
+fn calc_convex_and_triangles(
+    ent_shape: Entity,
+    ent_set: Entity,
+    shape: &dyn SupportMap,
+    radius: f64,
+    set: &dyn TriangleSet,
+    shape_transform: &PhysTransform,
+    set_transform: &PhysTransform,
+    candidates: &dyn Fn(DVec3) -> Vec<DVec3>,
+    contains: &dyn Fn(DVec3) -> bool,
+) -> Option<Vec<Contact>> {
+    let mut contacts: Vec<Contact> = vec![];
+    // Contacts with the edges or vertices of triangles, and the planes of the faces in contact.
+    let mut edge_contacts: Vec<Contact> = vec![];
+    let mut face_planes: Vec<(DVec3, DVec3)> = vec![];
+    // Face contacts with triangles that no vertex penetrates, used only if there are no others.
+    let mut fallback_contacts: Vec<Contact> = vec![];
+
+    let centre = set_transform.get_point_in_local_space(shape_transform.translation());
+
+    let vertex_contact = |feature_id: usize, normal: DVec3, penetration: f64, point: DVec3| {
+        Contact {
+            entities: vec![ent_shape, ent_set],
+            boundary: None,
+            feature_id: feature_id as u32,
+            normal,
+            penetration,
+            point,
+            relative_points: vec![point - shape_transform.translation,
+                point - set_transform.translation],
+        }
+    };
+
+    // each contact is identified by the index of the penetrated triangle along with the index of
+    // the contacting candidate point (0-7) or triangle vertex (8-10), or 11 where there is neither.
+    for (i, triangle) in set.triangles_in_aabb(centre - DVec3::splat(radius), centre + DVec3::splat(radius)) {
+        let result = gjk::gjk(shape, shape_transform, &triangle, set_transform);
+        if let gjk::GjkResult::Separated { .. } = result { continue; }
+
+        // the face is pressed against from the side of the shape's centre.
+        let face_normal = set_transform.get_direction_in_global_space(triangle.normal());
+        let face_point = set_transform.get_point_in_global_space(triangle.vertices[0]);
+        let normal = if (shape_transform.translation - face_point).dot(face_normal) >= 0.0 {
+            -face_normal
+        } else {
+            face_normal
+        };
+        let mut vertex_contacts = vec![];
+
+        // candidate points beyond the face.
+        for (j, vertex) in candidates(normal).iter().take(8).enumerate() {
+            let penetration = (*vertex - face_point).dot(normal);
+            let vertex_local = set_transform.get_point_in_local_space(*vertex);
+
+            if penetration > 0.0 && triangle.contains_projection_of(vertex_local) {
+                // contact point is mid-point between vertex and face.
+                let point = *vertex - normal * (penetration * 0.5);
+                vertex_contacts.push(vertex_contact(i * 12 + j, normal, penetration, point));
+            }
+        }
+
+        // triangle vertices within the shape.
+        let deepest = shape.support_point(shape_transform, normal);
+        for (j, vertex) in triangle.vertices.iter().enumerate() {
+            let vertex = set_transform.get_point_in_global_space(*vertex);
+            let penetration = (deepest - vertex).dot(normal);
+
+            if penetration > 0.0 && contains(vertex) {
+                // contact point is mid-point between vertex and face.
+                let point = vertex + normal * (penetration * 0.5);
+                vertex_contacts.push(vertex_contact(i * 12 + 8 + j, normal, penetration, point));
+            }
+        }
+
+        if !vertex_contacts.is_empty() {
+            face_planes.push((face_point, normal));
+
+            for contact in vertex_contacts {
+                if !is_duplicate_contact(&contacts, contact.point) {
+                    contacts.push(contact);
+                }
+            }
+            continue;
+        }
+
+        // the shape only overlaps the edges or vertices of the triangle, or no vertex penetrates.
+        let contact = match convex_and_convex(
+            ent_shape, ent_set, shape, &triangle, shape_transform, set_transform,
+        ) {
+            Some(contact) => Contact { feature_id: (i * 12 + 11) as u32, ..contact },
+            None => continue,
+        };
+
+        if contact.normal.dot(face_normal).abs() <= FACE_CONTACT_THRESHOLD {
+            edge_contacts.push(contact);
+        } else {
+            fallback_contacts.push(contact);
+        }
+    }
+
+    for contact in edge_contacts {
+        let internal = face_planes.iter()
+            .any(|(point, normal)| (contact.point - *point).dot(*normal) > -contact.penetration);
+
+        if !internal && !is_duplicate_contact(&contacts, contact.point) {
+            contacts.push(contact);
+        }
+    }
+
+    if contacts.is_empty() {
+        contacts = fallback_contacts;
+    }
+
+    if contacts.is_empty() {
+        return None;
+    }
+    Some(contacts)
+}
+
+/// Returns true if any of the given contacts is at (nearly) the same point as the given point.
+fn is_duplicate_contact(contacts: &[Contact], point: DVec3) -> bool {
+    contacts.iter().any(|c| (c.point - point).length() < DUPLICATE_CONTACT_THRESHOLD)
+}
+
+/// Returns the vertices, in global coords, of the face of the given cuboid whose outward normal is
+/// within the face contact threshold of the given unit direction, wound around the face. Where no
+/// face is that close, only the deepest vertex in the direction is returned.
+fn calc_cuboid_face(cuboid: &Cuboid, transform: &PhysTransform, direction: DVec3) -> Vec<DVec3> {
+    let local_direction = transform.get_direction_in_local_space(direction);
+    let abs = local_direction.abs();
+    let axis = if abs.x >= abs.y && abs.x >= abs.z {
+        0
+    } else if abs.y >= abs.z {
+        1
+    } else {
+        2
+    };
+
+    if abs[axis] < FACE_CONTACT_THRESHOLD {
+        return vec![cuboid.support_point(transform, direction)];
+    }
+
+    let extents = cuboid.extents();
+    let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+
+    [(1.0, 1.0), (-1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)].iter()
+        .map(|(a, b)| {
+            let mut vertex = DVec3::ZERO;
+            vertex[axis] = extents[axis].copysign(local_direction[axis]);
+            vertex[u] = a * extents[u];
+            vertex[v] = b * extents[v];
+            transform.get_point_in_global_space(vertex)
+        })
+        .collect()
+}
+
+/// Returns the vertices, in global coords, of the feature of the given cylinder furthest in the
+/// given unit direction. This is a polygon around the rim of a cap where the direction is within
+/// the face contact threshold of the axis, the segment down the side where it is that close to
+/// perpendicular to the axis, and otherwise the single deepest point on the rim.
+fn calc_cylinder_face(
+    cylinder: &Cylinder,
+    transform: &PhysTransform,
+    direction: DVec3,
+) -> Vec<DVec3> {
+    let axis = transform.axis(1);
+    let along = direction.dot(axis);
+    let centre = transform.translation();
+
+    if along.abs() >= FACE_CONTACT_THRESHOLD {
+        let cap = centre + axis * cylinder.half_height().copysign(along);
+        let (u, w) = (transform.axis(0), transform.axis(2));
+
+        (0..CAP_RIM_POINTS)
+            .map(|i| {
+                let angle = std::f64::consts::TAU * i as f64 / CAP_RIM_POINTS as f64;
+                cap + (u * angle.cos() + w * angle.sin()) * cylinder.radius()
+            })
+            .collect()
+    } else if along.abs() <= 1.0 - FACE_CONTACT_THRESHOLD {
+        let radial = (direction - axis * along).normalize() * cylinder.radius();
+        let half_axis = axis * cylinder.half_height();
+
+        vec![centre + radial - half_axis, centre + radial + half_axis]
+    } else {
+        vec![cylinder.support_point(transform, direction)]
+    }
+}
+
+/// Generates a Contact at each penetrating vertex of a pair of features, one from each shape,
+/// that face each other across the given contact. Each feature is a convex polygon, a segment or
+/// a single point, given by its vertices in global coords.
+///
+/// The feature with the most vertices is the reference, and the vertices of the other are clipped
+/// to lie within its sides. Those that remain beneath the reference feature, along the contact
+/// normal, each give a Contact identified by the contact's feature id and their order. Returns
+/// None if neither feature has more than one vertex, or no vertex penetrates.
+#[allow(clippy::too_many_arguments)]
+fn calc_clipped_contacts(
+    ent1: Entity,
+    ent2: Entity,
+    face1: &[DVec3],
+    face2: &[DVec3],
+    contact: &Contact,
+    transform1: &PhysTransform,
+    transform2: &PhysTransform,
+) -> Option<Vec<Contact>> {
+    // the reference feature, its outward normal, and the incident feature.
+    let (reference, reference_normal, incident) = if face2.len() > face1.len() {
+        (face2, -contact.normal, face1)
+    } else {
+        (face1, contact.normal, face2)
+    };
+
+    if reference.len() < 2 { return None; }
+
+    let mut points = incident.to_vec();
+    for (plane_point, plane_normal) in calc_side_planes(reference, reference_normal) {
+        points = clip_polygon(&points, plane_point, plane_normal);
+    }
+
+    let surface = reference.iter()
+        .map(|v| v.dot(reference_normal))
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    let mut contacts: Vec<Contact> = vec![];
+    for point in points {
+        let penetration = surface - point.dot(reference_normal);
+        if penetration < 0.0 || is_duplicate_contact(&contacts, point) { continue; }
+
+        contacts.push(Contact {
+            entities: vec![ent1, ent2],
+            boundary: None,
+            feature_id: contact.feature_id * 32 + contacts.len() as u32,
+            normal: contact.normal,
+            penetration,
+            point,
+            relative_points: vec![point - transform1.translation, point - transform2.translation],
+        });
+    }
+
+    if contacts.is_empty() {
+        return None;
+    }
+    Some(contacts)
+}
+
+/// Returns the planes, each as a point and outward normal, that bound the sides of the given
+/// convex polygon with the given face normal. A segment is bounded by the planes through its ends.
+fn calc_side_planes(face: &[DVec3], normal: DVec3) -> Vec<(DVec3, DVec3)> {
+    if face.len() == 2 {
+        let edge = face[1] - face[0];
+        return vec![(face[0], -edge), (face[1], edge)];
+    }
+
+    let centre = face.iter().fold(DVec3::ZERO, |total, v| total + *v) / face.len() as f64;
+
+    (0..face.len())
+        .map(|i| {
+            let a = face[i];
+            let side = (face[(i + 1) % face.len()] - a).cross(normal);
+
+            (a, if side.dot(centre - a) > 0.0 { -side } else { side })
+        })
+        .collect()
+}
+
+/// Clips the given polygon, segment or point to the inside of the plane with the given point and
+/// outward normal, using the Sutherland-Hodgman algorithm.
+fn clip_polygon(polygon: &[DVec3], plane_point: DVec3, plane_normal: DVec3) -> Vec<DVec3> {
+    let distance = |p: DVec3| (p - plane_point).dot(plane_normal);
+    let mut result = vec![];
+
+    if polygon.len() == 1 {
+        result.extend(polygon.iter().filter(|p| distance(**p) <= 0.0));
+        return result;
+    }
+
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+        let (da, db) = (distance(a), distance(b));
+
+        if da <= 0.0 {
+            result.push(a);
+        }
+        // the edge crosses the plane.
+        if (da < 0.0 && db > 0.0) || (da > 0.0 && db < 0.0) {
+            result.push(a + (b - a) * da / (da - db));
+        }
+    }
+
+    result
+}
+
+/// Convex shapes that are symmetric about their centre, which can be tested for intersection by
+/// projecting them onto candidate separating axes.
+trait SymmetricConvex: SupportMap {
+    /// Projects the half-size of the shape with the given transform onto the given unit axis.
+    fn project_onto_axis(&self, transform: &PhysTransform, axis: DVec3) -> f64;
+}
+
+impl SymmetricConvex for Sphere {
+    fn project_onto_axis(&self, _transform: &PhysTransform, _axis: DVec3) -> f64 {
+        self.radius()
+    }
+}
+
+impl SymmetricConvex for Cuboid {
+    fn project_onto_axis(&self, transform: &PhysTransform, axis: DVec3) -> f64 {
+        Cuboid::project_onto_axis(self, transform, axis)
+    }
+}
+
+impl SymmetricConvex for Capsule {
+    fn project_onto_axis(&self, transform: &PhysTransform, axis: DVec3) -> f64 {
+        Capsule::project_onto_axis(self, transform, axis)
+    }
+}
+
+impl SymmetricConvex for Cylinder {
+    fn project_onto_axis(&self, transform: &PhysTransform, axis: DVec3) -> f64 {
+        Cylinder::project_onto_axis(self, transform, axis)
+    }
+}
+
+/// The feature of a pair of shapes that a candidate separating axis is derived from, which
+/// determines where the contact point is placed.
+#[derive(Debug, Clone, Copy)]
+enum AxisFeature {
+    /// A face of shape 1, so the contact is at the deepest point of shape 2.
+    Face1,
+    /// A face of shape 2, so the contact is at the deepest point of shape 1.
+    Face2,
+    /// Features of both shapes, so the contact is mid-way between their deepest points.
+    Edge,
+}
+
+/// Tests two shapes for intersection on each of the given candidate separating axes, generating a
+/// Contact on the axis of least penetration if they overlap on all of them. The contact is
+/// identified by the index of that axis.
+#[allow(clippy::too_many_arguments)]
+fn calc_sat_contact<A: SymmetricConvex, B: SymmetricConvex>(
+    ent1: Entity,
+    ent2: Entity,
+    a: &A,
+    b: &B,
+    a_transform: &PhysTransform,
+    b_transform: &PhysTransform,
+    axes: &[(DVec3, AxisFeature)],
+) -> Option<Contact> {
+    let centre_to_centre = b_transform.translation() - a_transform.translation();
+
+    // The penetration, normal, feature and index of the best axis found so far.
+    let mut best: Option<(f64, DVec3, AxisFeature, usize)> = None;
+
+    for (i, (axis, feature)) in axes.iter().enumerate() {
+        // Skip axes that have been generated from near-parallel edges.
+        if axis.length_squared() < 0.001 { continue; }
+        let axis = axis.normalize();
+
+        let distance = centre_to_centre.dot(axis);
+        let overlap = a.project_onto_axis(a_transform, axis)
+            + b.project_onto_axis(b_transform, axis) - distance.abs();
 
-    let overlap = c1.project_onto_axis(c1_transform, axis) +
-        c2.project_onto_axis(c2_transform, axis) - distance;
+        // a separating axis, so there is no contact.
+        if overlap < 0.0 {
+            return None;
+        }
 
-    if overlap < 0.0 {
-        return None;
+        match best {
+            Some((penetration, ..)) if penetration <= overlap => (),
+            _ => {
+                // ensure the normal points from shape 1 towards shape 2.
+                let normal = if distance < 0.0 { -axis } else { axis };
+                best = Some((overlap, normal, *feature, i));
+            },
+        }
     }
-    Some(overlap)
+
+    let (penetration, normal, feature, i) = best?;
+
+    let point = match feature {
+        AxisFeature::Face1 => b.support_point(b_transform, -normal),
+        AxisFeature::Face2 => a.support_point(a_transform, normal),
+        AxisFeature::Edge => (a.support_point(a_transform, normal)
+                              + b.support_point(b_transform, -normal)) * 0.5,
+    };
+
+    Some(Contact {
+        entities: vec![ent1, ent2],
+        boundary: None,
+        feature_id: i as u32,
+        normal,
+        penetration,
+        point,
+        relative_points: vec![point - a_transform.translation, point - b_transform.translation],
+    })
 }
 
 #[cfg(test)]
@@ -483,6 +1720,7 @@ mod test {
     #[test]
     fn test_half_space_and_sphere() {
         let ent_s = Entity::new(1);
+        let ent_p = Entity::new(2);
 
         let r = 1.0;
         let s = Sphere::new(r);
@@ -495,7 +1733,7 @@ mod test {
         let s_transform = PhysTransform::from_translation(
             DVec3::new(0.0, r + 0.001, 0.0),
         );
-        let contact = half_space_and_sphere(ent_s, &p, &s, &s_transform, &p_transform);
+        let contact = half_space_and_sphere(ent_s, ent_p, &p, &s, &s_transform, &p_transform);
 
         assert!(contact.is_none());
 
@@ -507,7 +1745,7 @@ mod test {
         let s_transform = PhysTransform::from_translation(
             DVec3::new(0.0, r - expected_penetration, 0.0),
         );
-        let contact = half_space_and_sphere(ent_s, &p, &s, &s_transform, &p_transform).unwrap();
+        let contact = half_space_and_sphere(ent_s, ent_p, &p, &s, &s_transform, &p_transform).unwrap();
 
         println!("Contact: {:?}", contact);
 
@@ -607,6 +1845,7 @@ mod test {
     #[test]
     fn test_half_space_and_cuboid() {
         let ent_c = Entity::new(1);
+        let ent_p = Entity::new(2);
 
         let extents = DVec3::new(3.0, 3.0, 4.0);
         let c = Cuboid::new(extents);
@@ -628,13 +1867,13 @@ mod test {
         let c_transform = PhysTransform::from_translation(
             DVec3::new(0.0, 4.0, 0.0),
         );
-        let contacts = half_space_and_cuboid(ent_c, &p, &c, &p_transform, &c_transform);
+        let contacts = half_space_and_cuboid(ent_c, ent_p, &p, &c, &p_transform, &c_transform);
         assert!(contacts.is_none());
 
         let c_transform = PhysTransform::from_translation(
             DVec3::new(-10.0, extents.y + 1.0, 0.0),
         );
-        let contacts = half_space_and_cuboid(ent_c, &p2, &c, &p2_transform, &c_transform);
+        let contacts = half_space_and_cuboid(ent_c, ent_p, &p2, &c, &p2_transform, &c_transform);
         assert!(contacts.is_none());
 
         // FOUR PENETRATING VERTICES.
@@ -643,7 +1882,7 @@ mod test {
         );
         let expected_penetration = 2.0;
 
-        let contacts = half_space_and_cuboid(ent_c, &p, &c, &p_transform, &c_transform).unwrap();
+        let contacts = half_space_and_cuboid(ent_c, ent_p, &p, &c, &p_transform, &c_transform).unwrap();
 
         assert_eq!(4, contacts.len());
         for contact in contacts.iter() {
@@ -671,7 +1910,7 @@ mod test {
             DVec3::new(0.0, 3.0 * 2.0_f64.sqrt() - expected_penetration, 0.0),
         );
 
-        let contacts = half_space_and_cuboid(ent_c, &p, &c, &p_transform, &c_transform).unwrap();
+        let contacts = half_space_and_cuboid(ent_c, ent_p, &p, &c, &p_transform, &c_transform).unwrap();
 
         assert_eq!(2, contacts.len());
         for contact in contacts.iter() {
@@ -695,7 +1934,7 @@ mod test {
         let translation = DVec3::new(0.0, 3.0 * 3.0_f64.sqrt() - expected_penetration, 0.0);
         let c_transform = PhysTransform::from_rotation_translation(rotation, translation);
 
-        let contacts = half_space_and_cuboid(ent_c, &p, &c, &p_transform, &c_transform).unwrap();
+        let contacts = half_space_and_cuboid(ent_c, ent_p, &p, &c, &p_transform, &c_transform).unwrap();
 
         assert_eq!(1, contacts.len());
         assert_eq!(expected_normal, contacts[0].normal);
@@ -705,6 +1944,583 @@ mod test {
         assert!((contacts[0].point.z - 0.0).abs() < EPSILON);
     }
 
+    #[test]
+    fn test_sphere_and_capsule() {
+        let ent_s = Entity::new(1);
+        let ent_c = Entity::new(2);
+
+        let s = Sphere::new(1.0);
+        let c = Capsule::new(1.0, 2.0);
+        let c_transform = PhysTransform::IDENTITY;
+
+        // NO PENETRATION, beyond the end of the capsule.
+        let s_transform = PhysTransform::from_xyz(0.0, 4.001, 0.0);
+        assert!(sphere_and_capsule(ent_s, ent_c, &s, &c, &s_transform, &c_transform).is_none());
+
+        // PENETRATION, beside the central segment.
+        let s_transform = PhysTransform::from_xyz(1.9, 1.0, 0.0);
+        let contact = sphere_and_capsule(ent_s, ent_c, &s, &c, &s_transform, &c_transform).unwrap();
+
+        assert!((contact.normal - -DVec3::X).length() < EPSILON);
+        assert!((contact.penetration - 0.1).abs() < EPSILON);
+        assert!((contact.point - DVec3::new(0.95, 1.0, 0.0)).length() < EPSILON);
+    }
+
+    #[test]
+    fn test_capsule_and_capsule() {
+        let ent1 = Entity::new(1);
+        let ent2 = Entity::new(2);
+
+        let c1 = Capsule::new(1.0, 2.0);
+        let c2 = Capsule::new(0.5, 2.0);
+        let c1_transform = PhysTransform::IDENTITY;
+
+        // NO PENETRATION, crossing at right angles.
+        let rotation = DQuat::from_rotation_x(std::f64::consts::PI * 0.5);
+        let c2_transform = PhysTransform::from_rotation_translation(rotation, DVec3::new(1.6, 1.0, 0.0));
+        assert!(capsule_and_capsule(ent1, ent2, &c1, &c2, &c1_transform, &c2_transform).is_none());
+
+        // PENETRATION.
+        let c2_transform = PhysTransform::from_rotation_translation(rotation, DVec3::new(1.4, 1.0, 0.0));
+        let contact = capsule_and_capsule(ent1, ent2, &c1, &c2, &c1_transform, &c2_transform).unwrap();
+
+        assert!((contact.normal - DVec3::X).length() < EPSILON);
+        assert!((contact.penetration - 0.1).abs() < EPSILON);
+        assert!((contact.point - DVec3::new(0.95, 1.0, 0.0)).length() < EPSILON);
+    }
+
+    #[test]
+    fn test_half_space_and_capsule() {
+        let ent_c = Entity::new(1);
+        let ent_p = Entity::new(2);
+
+        let c = Capsule::new(1.0, 2.0);
+        let p_transform = PhysTransform::IDENTITY;
+        let p = Plane::new(&p_transform);
+
+        // NO PENETRATION, standing upright.
+        let c_transform = PhysTransform::from_xyz(0.0, 3.001, 0.0);
+        assert!(half_space_and_capsule(ent_c, ent_p, &p, &c, &p_transform, &c_transform).is_none());
+
+        // ONE PENETRATING END, standing upright.
+        let c_transform = PhysTransform::from_xyz(0.0, 2.9, 0.0);
+        let contacts = half_space_and_capsule(ent_c, ent_p, &p, &c, &p_transform, &c_transform).unwrap();
+
+        assert_eq!(1, contacts.len());
+        assert_eq!(-DVec3::Y, contacts[0].normal);
+        assert!((contacts[0].penetration - 0.1).abs() < EPSILON);
+
+        // TWO PENETRATING ENDS, lying down.
+        let c_transform = PhysTransform::from_rotation_translation(
+            DQuat::from_rotation_z(std::f64::consts::PI * 0.5),
+            DVec3::new(0.0, 0.9, 0.0),
+        );
+        let contacts = half_space_and_capsule(ent_c, ent_p, &p, &c, &p_transform, &c_transform).unwrap();
+
+        assert_eq!(2, contacts.len());
+        for contact in contacts.iter() {
+            assert!((contact.penetration - 0.1).abs() < EPSILON);
+            assert!((contact.point.x.abs() - 2.0).abs() < EPSILON);
+            assert!(contact.point.y.abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_capsule_and_cuboid() {
+        let ent_c = Entity::new(1);
+        let ent_b = Entity::new(2);
+
+        let c = Capsule::new(1.0, 2.0);
+        let b = Cuboid::new(DVec3::new(3.0, 3.0, 3.0));
+        let b_transform = PhysTransform::IDENTITY;
+
+        // NO PENETRATION, standing on top of the cuboid.
+        let c_transform = PhysTransform::from_xyz(1.0, 6.001, 0.0);
+        assert!(capsule_and_cuboid(ent_c, ent_b, &c, &b, &c_transform, &b_transform).is_none());
+
+        // PENETRATION, standing on top of the cuboid.
+        let c_transform = PhysTransform::from_xyz(1.0, 5.9, 0.0);
+        let contact = capsule_and_cuboid(ent_c, ent_b, &c, &b, &c_transform, &b_transform).unwrap();
+
+        assert!((contact.normal - -DVec3::Y).length() < EPSILON);
+        assert!((contact.penetration - 0.1).abs() < EPSILON);
+        assert!((contact.point - DVec3::new(1.0, 3.0, 0.0)).length() < EPSILON);
+
+        // DEEP PENETRATION, the central segment poking into the top face.
+        let c_transform = PhysTransform::from_xyz(1.0, 4.5, 0.0);
+        let contact = capsule_and_cuboid(ent_c, ent_b, &c, &b, &c_transform, &b_transform).unwrap();
+
+        assert!((contact.normal - -DVec3::Y).length() < EPSILON);
+        assert!((contact.penetration - 1.5).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_sphere_and_cylinder() {
+        let ent_s = Entity::new(1);
+        let ent_c = Entity::new(2);
+
+        let s = Sphere::new(1.0);
+        let c = Cylinder::new(2.0, 3.0);
+        let c_transform = PhysTransform::IDENTITY;
+
+        // NO PENETRATION, beside the curved face.
+        let s_transform = PhysTransform::from_xyz(0.0, 0.0, 3.001);
+        assert!(sphere_and_cylinder(ent_s, ent_c, &s, &c, &s_transform, &c_transform).is_none());
+
+        // PENETRATION, beside the curved face.
+        let s_transform = PhysTransform::from_xyz(0.0, 1.0, 2.9);
+        let contact = sphere_and_cylinder(ent_s, ent_c, &s, &c, &s_transform, &c_transform).unwrap();
+
+        assert!((contact.normal - -DVec3::Z).length() < EPSILON);
+        assert!((contact.penetration - 0.1).abs() < EPSILON);
+        assert!((contact.point - DVec3::new(0.0, 1.0, 2.0)).length() < EPSILON);
+
+        // DEEP PENETRATION, the centre just inside the top cap.
+        let s_transform = PhysTransform::from_xyz(0.0, 2.9, 0.0);
+        let contact = sphere_and_cylinder(ent_s, ent_c, &s, &c, &s_transform, &c_transform).unwrap();
+
+        assert!((contact.normal - -DVec3::Y).length() < EPSILON);
+        assert!((contact.penetration - 1.1).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_capsule_and_cylinder() {
+        let ent_c = Entity::new(1);
+        let ent_y = Entity::new(2);
+
+        let c = Capsule::new(1.0, 2.0);
+        let y = Cylinder::new(2.0, 3.0);
+        let y_transform = PhysTransform::IDENTITY;
+
+        // NO PENETRATION, lying beside the curved face.
+        let c_transform = PhysTransform::from_xyz(3.001, 0.0, 0.0);
+        assert!(capsule_and_cylinder(ent_c, ent_y, &c, &y, &c_transform, &y_transform).is_none());
+
+        // PENETRATION, lying beside the curved face.
+        let c_transform = PhysTransform::from_xyz(2.9, 0.0, 0.0);
+        let contact = capsule_and_cylinder(ent_c, ent_y, &c, &y, &c_transform, &y_transform).unwrap();
+
+        assert!((contact.normal - -DVec3::X).length() < EPSILON);
+        assert!((contact.penetration - 0.1).abs() < EPSILON);
+        assert!((contact.point.x - 2.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_cylinder_and_cuboid() {
+        let ent_c = Entity::new(1);
+        let ent_b = Entity::new(2);
+
+        let c = Cylinder::new(1.0, 2.0);
+        let b = Cuboid::new(DVec3::new(3.0, 3.0, 3.0));
+        let b_transform = PhysTransform::IDENTITY;
+
+        // NO PENETRATION, standing on top of the cuboid.
+        let c_transform = PhysTransform::from_xyz(0.0, 5.001, 0.0);
+        assert!(cylinder_and_cuboid(ent_c, ent_b, &c, &b, &c_transform, &b_transform).is_none());
+
+        // PENETRATION, standing on top of the cuboid, around the rim.
+        let c_transform = PhysTransform::from_xyz(0.0, 4.9, 0.0);
+        let contacts = cylinder_and_cuboid(ent_c, ent_b, &c, &b, &c_transform, &b_transform)
+            .unwrap();
+
+        assert_eq!(CAP_RIM_POINTS, contacts.len());
+        for contact in contacts.iter() {
+            assert!((contact.normal - -DVec3::Y).length() < EPSILON);
+            assert!((contact.penetration - 0.1).abs() < EPSILON);
+            // the cuboid's face, clipped to the rim of the cap.
+            assert!((contact.point.y - 3.0).abs() < EPSILON);
+            assert!((contact.point.x.powi(2) + contact.point.z.powi(2) - 1.0).abs() < EPSILON);
+        }
+
+        // PENETRATION, lying on its side with one end overhanging the cuboid.
+        let c_transform = PhysTransform::from_rotation_translation(
+            DQuat::from_rotation_z(std::f64::consts::PI * 0.5),
+            DVec3::new(2.0, 3.9, 0.0),
+        );
+        let contacts = cylinder_and_cuboid(ent_c, ent_b, &c, &b, &c_transform, &b_transform)
+            .unwrap();
+
+        assert_eq!(2, contacts.len());
+        for contact in contacts.iter() {
+            assert!((contact.normal - -DVec3::Y).length() < EPSILON);
+            assert!((contact.penetration - 0.1).abs() < EPSILON);
+        }
+        let mut xs: Vec<f64> = contacts.iter().map(|c| c.point.x).collect();
+        xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert!((xs[0] - 0.0).abs() < EPSILON);
+        assert!((xs[1] - 3.0).abs() < EPSILON);
+
+        // NO PENETRATION, lying alongside a top edge.
+        let c_transform = PhysTransform::from_rotation_translation(
+            DQuat::from_rotation_z(std::f64::consts::PI * 0.5),
+            DVec3::new(0.0, 3.0 + 0.5_f64.sqrt() * 1.001, 3.0 + 0.5_f64.sqrt() * 1.001),
+        );
+        assert!(cylinder_and_cuboid(ent_c, ent_b, &c, &b, &c_transform, &b_transform).is_none());
+    }
+
+    #[test]
+    fn test_cylinder_and_cylinder() {
+        let ent1 = Entity::new(1);
+        let ent2 = Entity::new(2);
+
+        let c1 = Cylinder::new(1.0, 2.0);
+        let c2 = Cylinder::new(1.0, 2.0);
+        let c1_transform = PhysTransform::IDENTITY;
+
+        // NO PENETRATION, side by side.
+        let c2_transform = PhysTransform::from_xyz(2.001, 1.0, 0.0);
+        assert!(cylinder_and_cylinder(ent1, ent2, &c1, &c2, &c1_transform, &c2_transform).is_none());
+
+        // PENETRATION, side by side, along the length they share.
+        let c2_transform = PhysTransform::from_xyz(1.9, 1.0, 0.0);
+        let contacts = cylinder_and_cylinder(ent1, ent2, &c1, &c2, &c1_transform, &c2_transform)
+            .unwrap();
+
+        assert_eq!(2, contacts.len());
+        for contact in contacts.iter() {
+            assert!((contact.normal - DVec3::X).length() < EPSILON);
+            assert!((contact.penetration - 0.1).abs() < EPSILON);
+        }
+        let mut ys: Vec<f64> = contacts.iter().map(|c| c.point.y).collect();
+        ys.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert!((ys[0] - -1.0).abs() < EPSILON);
+        assert!((ys[1] - 2.0).abs() < EPSILON);
+
+        // PENETRATION, stacked, at each point around the rim.
+        let c2_transform = PhysTransform::from_xyz(0.0, 3.9, 0.0);
+        let contacts = cylinder_and_cylinder(ent1, ent2, &c1, &c2, &c1_transform, &c2_transform)
+            .unwrap();
+
+        assert_eq!(CAP_RIM_POINTS, contacts.len());
+        for contact in contacts.iter() {
+            assert!((contact.normal - DVec3::Y).length() < EPSILON);
+            assert!((contact.penetration - 0.1).abs() < EPSILON);
+        }
+
+        // PENETRATION, offset stacked, within the overlap of the caps only.
+        let c2_transform = PhysTransform::from_xyz(1.0, 3.9, 0.0);
+        let contacts = cylinder_and_cylinder(ent1, ent2, &c1, &c2, &c1_transform, &c2_transform)
+            .unwrap();
+
+        assert!(contacts.len() >= 3);
+        for contact in contacts.iter() {
+            assert!((contact.normal - DVec3::Y).length() < EPSILON);
+            assert!(contact.point.x > -EPSILON && contact.point.x < 1.0 + EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_half_space_and_cylinder() {
+        let ent_c = Entity::new(1);
+        let ent_p = Entity::new(2);
+
+        let c = Cylinder::new(1.0, 2.0);
+        let p_transform = PhysTransform::IDENTITY;
+        let p = Plane::new(&p_transform);
+
+        // NO PENETRATION, standing on end.
+        let c_transform = PhysTransform::from_xyz(0.0, 2.001, 0.0);
+        assert!(half_space_and_cylinder(ent_c, ent_p, &p, &c, &p_transform, &c_transform).is_none());
+
+        // FOUR PENETRATING RIM POINTS, standing on end.
+        let c_transform = PhysTransform::from_xyz(0.0, 1.9, 0.0);
+        let contacts = half_space_and_cylinder(ent_c, ent_p, &p, &c, &p_transform, &c_transform).unwrap();
+
+        assert_eq!(4, contacts.len());
+        for contact in contacts.iter() {
+            assert_eq!(-DVec3::Y, contact.normal);
+            assert!((contact.penetration - 0.1).abs() < EPSILON);
+            assert!((contact.point.y - -0.05).abs() < EPSILON);
+        }
+
+        // TWO PENETRATING RIM POINTS, lying on its side.
+        let c_transform = PhysTransform::from_rotation_translation(
+            DQuat::from_rotation_z(std::f64::consts::PI * 0.5),
+            DVec3::new(0.0, 0.9, 0.0),
+        );
+        let contacts = half_space_and_cylinder(ent_c, ent_p, &p, &c, &p_transform, &c_transform).unwrap();
+
+        assert_eq!(2, contacts.len());
+        for contact in contacts.iter() {
+            assert!((contact.penetration - 0.1).abs() < EPSILON);
+            assert!((contact.point.x.abs() - 2.0).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_convex_and_convex() {
+        let ent_h = Entity::new(1);
+        let ent_c = Entity::new(2);
+
+        // a square based pyramid, with its apex pointing down.
+        let h = ConvexHull::new(vec![
+            DVec3::new(0.0, -1.0, 0.0),
+            DVec3::new(1.0, 1.0, 1.0),
+            DVec3::new(1.0, 1.0, -1.0),
+            DVec3::new(-1.0, 1.0, 1.0),
+            DVec3::new(-1.0, 1.0, -1.0),
+        ]);
+        let c = Cuboid::new(DVec3::new(2.0, 1.0, 2.0));
+        let c_transform = PhysTransform::IDENTITY;
+
+        // NO PENETRATION.
+        let h_transform = PhysTransform::from_xyz(0.0, 2.001, 0.0);
+        assert!(convex_and_convex(ent_h, ent_c, &h, &c, &h_transform, &c_transform).is_none());
+
+        // APEX PENETRATING TOP FACE.
+        let h_transform = PhysTransform::from_xyz(0.5, 1.9, 0.0);
+        let contact = convex_and_convex(ent_h, ent_c, &h, &c, &h_transform, &c_transform).unwrap();
+
+        assert_eq!(vec![ent_h, ent_c], contact.entities);
+        assert!((contact.normal - -DVec3::Y).length() < EPSILON);
+        assert!((contact.penetration - 0.1).abs() < EPSILON);
+        assert!((contact.point - DVec3::new(0.5, 0.95, 0.0)).length() < EPSILON);
+    }
+
+    #[test]
+    fn test_half_space_and_convex_hull() {
+        let ent_h = Entity::new(1);
+        let ent_p = Entity::new(2);
+
+        let h = ConvexHull::new(vec![
+            DVec3::new(0.0, -1.0, 0.0),
+            DVec3::new(1.0, 1.0, 0.0),
+            DVec3::new(-1.0, 1.0, 0.0),
+            DVec3::new(0.0, 1.0, 1.0),
+        ]);
+        let p_transform = PhysTransform::IDENTITY;
+        let p = Plane::new(&p_transform);
+
+        // NO PENETRATION.
+        let h_transform = PhysTransform::from_xyz(0.0, 1.001, 0.0);
+        assert!(half_space_and_convex_hull(ent_h, ent_p, &p, &h, &p_transform, &h_transform).is_none());
+
+        // ONE PENETRATING POINT.
+        let h_transform = PhysTransform::from_xyz(0.0, 0.9, 0.0);
+        let contacts = half_space_and_convex_hull(ent_h, ent_p, &p, &h, &p_transform, &h_transform).unwrap();
+
+        assert_eq!(1, contacts.len());
+        assert_eq!(0, contacts[0].feature_id);
+        assert_eq!(-DVec3::Y, contacts[0].normal);
+        assert!((contacts[0].penetration - 0.1).abs() < EPSILON);
+        assert!((contacts[0].point - DVec3::new(0.0, -0.05, 0.0)).length() < EPSILON);
+    }
+
+    /// A 10 by 10 square floor in the x-z plane, made of two triangles.
+    fn floor() -> TriMesh {
+        let vertices = vec![
+            DVec3::new(-5.0, 0.0, -5.0),
+            DVec3::new(-5.0, 0.0, 5.0),
+            DVec3::new(5.0, 0.0, 5.0),
+            DVec3::new(5.0, 0.0, -5.0),
+        ];
+
+        TriMesh::new(vertices, vec![[0, 1, 2], [0, 2, 3]])
+    }
+
+    #[test]
+    fn test_sphere_and_tri_mesh() {
+        let ent_s = Entity::new(1);
+        let ent_m = Entity::new(2);
+
+        let s = Sphere::new(1.0);
+        let m = floor();
+        let m_transform = PhysTransform::from_xyz(0.0, -1.0, 0.0);
+
+        // NO PENETRATION.
+        let s_transform = PhysTransform::from_xyz(0.3, 0.001, 0.2);
+        assert!(sphere_and_tri_mesh(ent_s, ent_m, &s, &m, &s_transform, &m_transform).is_none());
+
+        // PENETRATING ONE TRIANGLE.
+        let s_transform = PhysTransform::from_xyz(0.3, -0.1, 0.2);
+        let contacts = sphere_and_tri_mesh(ent_s, ent_m, &s, &m, &s_transform, &m_transform).unwrap();
+
+        assert_eq!(1, contacts.len());
+        assert_eq!(vec![ent_s, ent_m], contacts[0].entities);
+        assert!((contacts[0].normal - -DVec3::Y).length() < EPSILON);
+        assert!((contacts[0].penetration - 0.1).abs() < EPSILON);
+        assert!((contacts[0].point - DVec3::new(0.3, -1.0, 0.2)).length() < EPSILON);
+
+        // PENETRATING THE EDGE SHARED BY BOTH TRIANGLES, giving a single contact.
+        let s_transform = PhysTransform::from_xyz(1.0, -0.1, 1.0);
+        let contacts = sphere_and_tri_mesh(ent_s, ent_m, &s, &m, &s_transform, &m_transform).unwrap();
+
+        assert_eq!(1, contacts.len());
+    }
+
+    #[test]
+    fn test_convex_and_tri_mesh() {
+        let ent_s = Entity::new(1);
+        let ent_m = Entity::new(2);
+        let m = floor();
+        let m_transform = PhysTransform::IDENTITY;
+
+        // NO PENETRATION.
+        let c = Capsule::new(0.5, 1.0);
+        let c_transform = PhysTransform::from_rotation_translation(
+            DQuat::from_rotation_z(std::f64::consts::FRAC_PI_2),
+            DVec3::new(0.5, 0.501, 0.3),
+        );
+        assert!(convex_and_tri_mesh(ent_s, ent_m, &c, 1.5, &m, &c_transform, &m_transform)
+            .is_none());
+
+        // A CAPSULE LYING FLAT, touching at both ends.
+        let c_transform = PhysTransform::from_rotation_translation(
+            DQuat::from_rotation_z(std::f64::consts::FRAC_PI_2),
+            DVec3::new(0.5, 0.4, 0.3),
+        );
+        let contacts = convex_and_tri_mesh(ent_s, ent_m, &c, 1.5, &m, &c_transform, &m_transform)
+            .unwrap();
+
+        let xs = contacts.iter().map(|c| c.point.x);
+        assert!(xs.clone().fold(f64::INFINITY, f64::min) < -0.4);
+        assert!(xs.fold(f64::NEG_INFINITY, f64::max) > 1.4);
+        for contact in contacts.iter() {
+            assert_eq!(vec![ent_s, ent_m], contact.entities);
+            assert!((contact.normal - -DVec3::Y).length() < 0.001);
+            assert!(contact.penetration > 0.0 && contact.penetration < 0.1 + 0.001);
+        }
+
+        // A CYLINDER STANDING ON ITS CAP, touching around its rim.
+        let c = Cylinder::new(0.5, 0.5);
+        let c_transform = PhysTransform::from_xyz(0.5, 0.4, 0.3);
+        let contacts = convex_and_tri_mesh(ent_s, ent_m, &c, 1.0, &m, &c_transform, &m_transform)
+            .unwrap();
+
+        assert_eq!(8, contacts.len());
+        for contact in contacts.iter() {
+            let radial = contact.point - DVec3::new(0.5, contact.point.y, 0.3);
+            assert!((radial.length() - 0.5).abs() < 0.001);
+            assert!((contact.penetration - 0.1).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_convex_and_height_field() {
+        let ent_c = Entity::new(1);
+        let ent_h = Entity::new(2);
+
+        let cube = Cuboid::new(DVec3::new(0.5, 0.5, 0.5));
+        let c = ConvexHull::new(cube.vertices(&PhysTransform::IDENTITY).to_vec());
+        let h = HeightField::new(vec![0.0; 16], 4, 4, 1.0, 1.0);
+        let h_transform = PhysTransform::IDENTITY;
+        let radius = c.bounding_sphere().radius();
+
+        // NO PENETRATION.
+        let c_transform = PhysTransform::from_xyz(0.1, 0.501, 0.2);
+        assert!(convex_and_height_field(ent_c, ent_h, &c, radius, &h, &c_transform, &h_transform)
+            .is_none());
+
+        // RESTING FLAT ACROSS SEVERAL CELLS, with the four penetrating corners of the hull and the
+        // one grid vertex beneath the hull, at any orientation about the vertical.
+        for angle in [0.0, 0.3, std::f64::consts::FRAC_PI_4].iter() {
+            let c_transform = PhysTransform::from_rotation_translation(
+                DQuat::from_rotation_y(*angle),
+                DVec3::new(0.1, 0.45, 0.2),
+            );
+            let contacts = convex_and_height_field(
+                ent_c, ent_h, &c, radius, &h, &c_transform, &h_transform,
+            ).unwrap();
+
+            assert_eq!(5, contacts.len());
+            for contact in contacts.iter() {
+                assert!((contact.normal - -DVec3::Y).length() < 0.001);
+                assert!((contact.penetration - 0.05).abs() < 0.001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sphere_and_height_field() {
+        let ent_s = Entity::new(1);
+        let ent_h = Entity::new(2);
+
+        let s = Sphere::new(1.0);
+        // a 45 degree slope rising along the x-axis.
+        let h = HeightField::new(vec![
+            0.0, 1.0, 2.0,
+            0.0, 1.0, 2.0,
+            0.0, 1.0, 2.0,
+        ], 3, 3, 1.0, 1.0);
+        let h_transform = PhysTransform::IDENTITY;
+
+        // NO PENETRATION.
+        let s_transform = PhysTransform::from_xyz(0.0, 1.0 + 1.001 * 2.0_f64.sqrt(), 0.0);
+        assert!(sphere_and_height_field(ent_s, ent_h, &s, &h, &s_transform, &h_transform).is_none());
+
+        // PENETRATING THE SLOPE.
+        let s_transform = PhysTransform::from_xyz(0.0, 1.0 + 0.9 * 2.0_f64.sqrt(), 0.0);
+        let contacts = sphere_and_height_field(ent_s, ent_h, &s, &h, &s_transform, &h_transform).unwrap();
+
+        assert_eq!(1, contacts.len());
+        let expected_normal = DVec3::new(1.0, -1.0, 0.0).normalize();
+        assert!((contacts[0].normal - expected_normal).length() < EPSILON);
+        assert!((contacts[0].penetration - 0.1).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_cuboid_and_tri_mesh() {
+        let ent_c = Entity::new(1);
+        let ent_m = Entity::new(2);
+
+        let c = Cuboid::new(DVec3::new(1.0, 1.0, 1.0));
+        let m = floor();
+        let m_transform = PhysTransform::IDENTITY;
+
+        // NO PENETRATION.
+        let c_transform = PhysTransform::from_xyz(0.5, 1.001, 0.3);
+        assert!(cuboid_and_tri_mesh(ent_c, ent_m, &c, &m, &c_transform, &m_transform).is_none());
+
+        // RESTING FLAT, with four penetrating vertices.
+        let c_transform = PhysTransform::from_xyz(0.5, 0.9, 0.3);
+        let contacts = cuboid_and_tri_mesh(ent_c, ent_m, &c, &m, &c_transform, &m_transform).unwrap();
+
+        assert_eq!(4, contacts.len());
+        for contact in contacts.iter() {
+            assert_eq!(vec![ent_c, ent_m], contact.entities);
+            assert!((contact.normal - -DVec3::Y).length() < EPSILON);
+            assert!((contact.penetration - 0.1).abs() < EPSILON);
+            assert!((contact.point.y - -0.05).abs() < EPSILON);
+        }
+
+        // STANDING ON AN EDGE, with two penetrating vertices.
+        let c_transform = PhysTransform::from_rotation_translation(
+            DQuat::from_rotation_z(std::f64::consts::PI * 0.25),
+            DVec3::new(0.5, 2.0_f64.sqrt() - 0.1, 0.3),
+        );
+        let contacts = cuboid_and_tri_mesh(ent_c, ent_m, &c, &m, &c_transform, &m_transform).unwrap();
+
+        assert_eq!(2, contacts.len());
+        for contact in contacts.iter() {
+            assert!((contact.penetration - 0.1).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_cuboid_and_height_field() {
+        let ent_c = Entity::new(1);
+        let ent_h = Entity::new(2);
+
+        let c = Cuboid::new(DVec3::new(0.5, 0.5, 0.5));
+        let h = HeightField::new(vec![0.0; 16], 4, 4, 1.0, 1.0);
+        let h_transform = PhysTransform::IDENTITY;
+
+        // NO PENETRATION.
+        let c_transform = PhysTransform::from_xyz(0.1, 0.501, 0.2);
+        assert!(cuboid_and_height_field(ent_c, ent_h, &c, &h, &c_transform, &h_transform).is_none());
+
+        // RESTING FLAT ACROSS SEVERAL CELLS, with the four penetrating cuboid vertices and the one
+        // grid vertex beneath the cuboid.
+        let c_transform = PhysTransform::from_xyz(0.1, 0.45, 0.2);
+        let contacts = cuboid_and_height_field(ent_c, ent_h, &c, &h, &c_transform, &h_transform).unwrap();
+
+        assert_eq!(5, contacts.len());
+        for contact in contacts.iter() {
+            assert!((contact.normal - -DVec3::Y).length() < EPSILON);
+            assert!((contact.penetration - 0.05).abs() < EPSILON);
+        }
+    }
+
     #[test]
     fn test_cuboid_and_cuboid() {
         let ent_c1 = Entity::new(1);
@@ -6,17 +6,23 @@ use crate::{
         PhysTransform,
     },
     physics::shapes::{
+        Capsule,
         CollisionPrimative,
+        Compound,
+        ConvexHull,
         Cuboid,
+        Cylinder,
+        HeightField,
         Plane,
         Sphere,
+        TriMesh,
     },
     physics::systems::collision_detection::contact_generation::contact_generators,
 };
 
 /// Generates contacts between two shapes with the CollisionPrimative trait by downcasting to the
 /// concrete shape type known at runtime and dispatching to the appropriate contact generation
-/// function.
+/// function. Compounds are broken down into their children.
 pub fn generate_primative_contacts(
     ent_a: Entity,
     ent_b: Entity,
@@ -25,12 +31,35 @@ pub fn generate_primative_contacts(
     transform_a: &PhysTransform,
     transform_b: &PhysTransform,
 ) -> Option<Vec<Contact>> {
+    if let Some(compound) = a.downcast_ref::<Compound>() {
+        return generate_compound_contacts(ent_a, compound, transform_a, |child, child_transform| {
+            generate_primative_contacts(ent_a, ent_b, child, b, child_transform, transform_b)
+        });
+    }
+    if let Some(compound) = b.downcast_ref::<Compound>() {
+        return generate_compound_contacts(ent_b, compound, transform_b, |child, child_transform| {
+            generate_primative_contacts(ent_a, ent_b, a, child, transform_a, child_transform)
+        });
+    }
+
     let a_is_sphere =  a.is::<Sphere>();
     let b_is_sphere =  b.is::<Sphere>();
 
     let a_is_cuboid = a.is::<Cuboid>();
     let b_is_cuboid = b.is::<Cuboid>();
 
+    let a_is_capsule = a.is::<Capsule>();
+    let b_is_capsule = b.is::<Capsule>();
+
+    let a_is_cylinder = a.is::<Cylinder>();
+    let b_is_cylinder = b.is::<Cylinder>();
+
+    let a_is_tri_mesh = a.is::<TriMesh>();
+    let b_is_tri_mesh = b.is::<TriMesh>();
+
+    let a_is_height_field = a.is::<HeightField>();
+    let b_is_height_field = b.is::<HeightField>();
+
     if a_is_sphere && b_is_sphere {
         return contact_generators::sphere_and_sphere(
             ent_a,
@@ -71,6 +100,264 @@ pub fn generate_primative_contacts(
             transform_a,
         ).map(|c| vec![c])
     }
+    if a_is_capsule && b_is_capsule {
+        return contact_generators::capsule_and_capsule(
+            ent_a,
+            ent_b,
+            a.downcast_ref::<Capsule>().unwrap(),
+            b.downcast_ref::<Capsule>().unwrap(),
+            transform_a,
+            transform_b,
+        ).map(|c| vec![c])
+    }
+    if a_is_cylinder && b_is_cylinder {
+        return contact_generators::cylinder_and_cylinder(
+            ent_a,
+            ent_b,
+            a.downcast_ref::<Cylinder>().unwrap(),
+            b.downcast_ref::<Cylinder>().unwrap(),
+            transform_a,
+            transform_b,
+        )
+    }
+    if a_is_sphere && b_is_capsule {
+        return contact_generators::sphere_and_capsule(
+            ent_a,
+            ent_b,
+            a.downcast_ref::<Sphere>().unwrap(),
+            b.downcast_ref::<Capsule>().unwrap(),
+            transform_a,
+            transform_b,
+        ).map(|c| vec![c])
+    }
+    if a_is_capsule && b_is_sphere {
+        return contact_generators::sphere_and_capsule(
+            ent_b,
+            ent_a,
+            b.downcast_ref::<Sphere>().unwrap(),
+            a.downcast_ref::<Capsule>().unwrap(),
+            transform_b,
+            transform_a,
+        ).map(|c| vec![c])
+    }
+    if a_is_sphere && b_is_cylinder {
+        return contact_generators::sphere_and_cylinder(
+            ent_a,
+            ent_b,
+            a.downcast_ref::<Sphere>().unwrap(),
+            b.downcast_ref::<Cylinder>().unwrap(),
+            transform_a,
+            transform_b,
+        ).map(|c| vec![c])
+    }
+    if a_is_cylinder && b_is_sphere {
+        return contact_generators::sphere_and_cylinder(
+            ent_b,
+            ent_a,
+            b.downcast_ref::<Sphere>().unwrap(),
+            a.downcast_ref::<Cylinder>().unwrap(),
+            transform_b,
+            transform_a,
+        ).map(|c| vec![c])
+    }
+    if a_is_capsule && b_is_cuboid {
+        return contact_generators::capsule_and_cuboid(
+            ent_a,
+            ent_b,
+            a.downcast_ref::<Capsule>().unwrap(),
+            b.downcast_ref::<Cuboid>().unwrap(),
+            transform_a,
+            transform_b,
+        ).map(|c| vec![c])
+    }
+    if a_is_cuboid && b_is_capsule {
+        return contact_generators::capsule_and_cuboid(
+            ent_b,
+            ent_a,
+            b.downcast_ref::<Capsule>().unwrap(),
+            a.downcast_ref::<Cuboid>().unwrap(),
+            transform_b,
+            transform_a,
+        ).map(|c| vec![c])
+    }
+    if a_is_capsule && b_is_cylinder {
+        return contact_generators::capsule_and_cylinder(
+            ent_a,
+            ent_b,
+            a.downcast_ref::<Capsule>().unwrap(),
+            b.downcast_ref::<Cylinder>().unwrap(),
+            transform_a,
+            transform_b,
+        ).map(|c| vec![c])
+    }
+    if a_is_cylinder && b_is_capsule {
+        return contact_generators::capsule_and_cylinder(
+            ent_b,
+            ent_a,
+            b.downcast_ref::<Capsule>().unwrap(),
+            a.downcast_ref::<Cylinder>().unwrap(),
+            transform_b,
+            transform_a,
+        ).map(|c| vec![c])
+    }
+    if a_is_cylinder && b_is_cuboid {
+        return contact_generators::cylinder_and_cuboid(
+            ent_a,
+            ent_b,
+            a.downcast_ref::<Cylinder>().unwrap(),
+            b.downcast_ref::<Cuboid>().unwrap(),
+            transform_a,
+            transform_b,
+        )
+    }
+    if a_is_cuboid && b_is_cylinder {
+        return contact_generators::cylinder_and_cuboid(
+            ent_b,
+            ent_a,
+            b.downcast_ref::<Cylinder>().unwrap(),
+            a.downcast_ref::<Cuboid>().unwrap(),
+            transform_b,
+            transform_a,
+        )
+    }
+    if a_is_sphere && b_is_tri_mesh {
+        return contact_generators::sphere_and_tri_mesh(
+            ent_a,
+            ent_b,
+            a.downcast_ref::<Sphere>().unwrap(),
+            b.downcast_ref::<TriMesh>().unwrap(),
+            transform_a,
+            transform_b,
+        )
+    }
+    if a_is_tri_mesh && b_is_sphere {
+        return contact_generators::sphere_and_tri_mesh(
+            ent_b,
+            ent_a,
+            b.downcast_ref::<Sphere>().unwrap(),
+            a.downcast_ref::<TriMesh>().unwrap(),
+            transform_b,
+            transform_a,
+        )
+    }
+    if a_is_sphere && b_is_height_field {
+        return contact_generators::sphere_and_height_field(
+            ent_a,
+            ent_b,
+            a.downcast_ref::<Sphere>().unwrap(),
+            b.downcast_ref::<HeightField>().unwrap(),
+            transform_a,
+            transform_b,
+        )
+    }
+    if a_is_height_field && b_is_sphere {
+        return contact_generators::sphere_and_height_field(
+            ent_b,
+            ent_a,
+            b.downcast_ref::<Sphere>().unwrap(),
+            a.downcast_ref::<HeightField>().unwrap(),
+            transform_b,
+            transform_a,
+        )
+    }
+    if a_is_cuboid && b_is_tri_mesh {
+        return contact_generators::cuboid_and_tri_mesh(
+            ent_a,
+            ent_b,
+            a.downcast_ref::<Cuboid>().unwrap(),
+            b.downcast_ref::<TriMesh>().unwrap(),
+            transform_a,
+            transform_b,
+        )
+    }
+    if a_is_tri_mesh && b_is_cuboid {
+        return contact_generators::cuboid_and_tri_mesh(
+            ent_b,
+            ent_a,
+            b.downcast_ref::<Cuboid>().unwrap(),
+            a.downcast_ref::<TriMesh>().unwrap(),
+            transform_b,
+            transform_a,
+        )
+    }
+    if a_is_cuboid && b_is_height_field {
+        return contact_generators::cuboid_and_height_field(
+            ent_a,
+            ent_b,
+            a.downcast_ref::<Cuboid>().unwrap(),
+            b.downcast_ref::<HeightField>().unwrap(),
+            transform_a,
+            transform_b,
+        )
+    }
+    if a_is_height_field && b_is_cuboid {
+        return contact_generators::cuboid_and_height_field(
+            ent_b,
+            ent_a,
+            b.downcast_ref::<Cuboid>().unwrap(),
+            a.downcast_ref::<HeightField>().unwrap(),
+            transform_b,
+            transform_a,
+        )
+    }
+
+    // Any other convex shape is tested against each nearby triangle by GJK/EPA.
+    if let (Some(shape), true) = (a.as_support_map(), b_is_tri_mesh) {
+        return contact_generators::convex_and_tri_mesh(
+            ent_a,
+            ent_b,
+            shape,
+            a.bounding_sphere().radius(),
+            b.downcast_ref::<TriMesh>().unwrap(),
+            transform_a,
+            transform_b,
+        )
+    }
+    if let (true, Some(shape)) = (a_is_tri_mesh, b.as_support_map()) {
+        return contact_generators::convex_and_tri_mesh(
+            ent_b,
+            ent_a,
+            shape,
+            b.bounding_sphere().radius(),
+            a.downcast_ref::<TriMesh>().unwrap(),
+            transform_b,
+            transform_a,
+        )
+    }
+    if let (Some(shape), true) = (a.as_support_map(), b_is_height_field) {
+        return contact_generators::convex_and_height_field(
+            ent_a,
+            ent_b,
+            shape,
+            a.bounding_sphere().radius(),
+            b.downcast_ref::<HeightField>().unwrap(),
+            transform_a,
+            transform_b,
+        )
+    }
+    if let (true, Some(shape)) = (a_is_height_field, b.as_support_map()) {
+        return contact_generators::convex_and_height_field(
+            ent_b,
+            ent_a,
+            shape,
+            b.bounding_sphere().radius(),
+            a.downcast_ref::<HeightField>().unwrap(),
+            transform_b,
+            transform_a,
+        )
+    }
+
+    // Any remaining pair of convex shapes falls back on the generic GJK/EPA test.
+    if let (Some(a), Some(b)) = (a.as_support_map(), b.as_support_map()) {
+        return contact_generators::convex_and_convex(
+            ent_a,
+            ent_b,
+            a,
+            b,
+            transform_a,
+            transform_b,
+        ).map(|c| vec![c])
+    }
 
     None
 }
@@ -79,18 +366,29 @@ pub fn generate_primative_contacts(
 /// CollisionPrimative.
 pub fn generate_boundary_contacts(
     ent_other: Entity,
+    ent_bnd: Entity,
     bnd: &Plane,
     other: &Box<dyn CollisionPrimative>,
     transform_bnd: &PhysTransform,
     transform_other: &PhysTransform,
 ) -> Option<Vec<Contact>> {
+    if let Some(compound) = other.downcast_ref::<Compound>() {
+        return generate_compound_contacts(ent_other, compound, transform_other, |child, child_transform| {
+            generate_boundary_contacts(ent_other, ent_bnd, bnd, child, transform_bnd, child_transform)
+        });
+    }
+
     // Downcast at runtime to determine concrete type of CollisionPrimative.
     let other_is_cuboid = other.is::<Cuboid>();
     let other_is_sphere = other.is::<Sphere>();
+    let other_is_capsule = other.is::<Capsule>();
+    let other_is_cylinder = other.is::<Cylinder>();
+    let other_is_convex_hull = other.is::<ConvexHull>();
 
     if other_is_sphere {
         return contact_generators::half_space_and_sphere(
             ent_other,
+            ent_bnd,
             bnd,
             other.downcast_ref::<Sphere>().unwrap(),
             transform_other,
@@ -100,12 +398,136 @@ pub fn generate_boundary_contacts(
     if other_is_cuboid {
         return contact_generators::half_space_and_cuboid(
             ent_other,
+            ent_bnd,
             bnd,
             other.downcast_ref::<Cuboid>().unwrap(),
+            transform_bnd,
+            transform_other,
+        )
+    }
+    if other_is_capsule {
+        return contact_generators::half_space_and_capsule(
+            ent_other,
+            ent_bnd,
+            bnd,
+            other.downcast_ref::<Capsule>().unwrap(),
+            transform_bnd,
+            transform_other,
+        )
+    }
+    if other_is_cylinder {
+        return contact_generators::half_space_and_cylinder(
+            ent_other,
+            ent_bnd,
+            bnd,
+            other.downcast_ref::<Cylinder>().unwrap(),
+            transform_bnd,
             transform_other,
+        )
+    }
+    if other_is_convex_hull {
+        return contact_generators::half_space_and_convex_hull(
+            ent_other,
+            ent_bnd,
+            bnd,
+            other.downcast_ref::<ConvexHull>().unwrap(),
             transform_bnd,
+            transform_other,
         )
     }
 
     None
 }
+
+/// Generates contacts for each child of the given compound, belonging to the given entity with
+/// the given transform, using the given function of a child shape and its global transform.
+///
+/// The contacts are adjusted to be relative to the compound body, rather than the child, and their
+/// feature ids are combined with the index of the child so that they remain unique. Compounds of
+/// more than 256 children may therefore share feature ids between children.
+fn generate_compound_contacts(
+    ent: Entity,
+    compound: &Compound,
+    transform: &PhysTransform,
+    mut generate: impl FnMut(&Box<dyn CollisionPrimative>, &PhysTransform) -> Option<Vec<Contact>>,
+) -> Option<Vec<Contact>> {
+    let mut contacts = vec![];
+
+    for (i, (child_transform, child)) in compound.children().iter().enumerate() {
+        let child_contacts = match generate(child, &transform.mul_transform(child_transform)) {
+            Some(child_contacts) => child_contacts,
+            None => continue,
+        };
+
+        for mut contact in child_contacts {
+            let point = contact.point;
+            for (e, relative_point) in contact.entities.iter().zip(contact.relative_points.iter_mut()) {
+                if *e == ent {
+                    *relative_point = point - transform.translation();
+                }
+            }
+            contact.feature_id = contact.feature_id.wrapping_shl(8) | (i as u32 & 0xff);
+
+            contacts.push(contact);
+        }
+    }
+
+    if contacts.is_empty() {
+        return None;
+    }
+    Some(contacts)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use bevy::math::DVec3;
+
+    #[test]
+    fn test_compound_contacts() {
+        let ent_c = Entity::new(1);
+        let ent_s = Entity::new(2);
+
+        // a dumbbell along the x-axis, with a sphere resting on its right end.
+        let c: Box<dyn CollisionPrimative> = Box::new(Compound::new()
+            .with_child(PhysTransform::from_xyz(-2.0, 0.0, 0.0), Sphere::new(1.0))
+            .with_child(PhysTransform::from_xyz(2.0, 0.0, 0.0), Sphere::new(1.0)));
+        let s: Box<dyn CollisionPrimative> = Box::new(Sphere::new(1.0));
+
+        let c_transform = PhysTransform::from_xyz(0.0, 1.0, 0.0);
+        let s_transform = PhysTransform::from_xyz(2.0, 2.9, 0.0);
+
+        for (a, b, ta, tb, ent_a, ent_b) in [
+            (&c, &s, &c_transform, &s_transform, ent_c, ent_s),
+            (&s, &c, &s_transform, &c_transform, ent_s, ent_c),
+        ].iter() {
+            let contacts = generate_primative_contacts(*ent_a, *ent_b, a, b, ta, tb).unwrap();
+
+            assert_eq!(1, contacts.len());
+            let contact = &contacts[0];
+            assert_eq!(1, contact.feature_id & 0xff);
+            assert!((contact.penetration - 0.1).abs() < 0.000001);
+
+            // relative points are relative to each body, not the child sphere.
+            for (e, relative_point) in contact.entities.iter().zip(contact.relative_points.iter()) {
+                let translation = if *e == ent_c { c_transform.translation() } else { s_transform.translation() };
+                assert_eq!(contact.point - translation, *relative_point);
+            }
+        }
+
+        // both ends resting on a floor.
+        let floor_transform = PhysTransform::IDENTITY;
+        let floor = Plane::new(&floor_transform);
+        let c_transform = PhysTransform::from_xyz(0.0, 0.9, 0.0);
+        let contacts = generate_boundary_contacts(
+            ent_c, ent_s, &floor, &c, &floor_transform, &c_transform,
+        ).unwrap();
+
+        assert_eq!(2, contacts.len());
+        for contact in contacts.iter() {
+            assert_eq!(contact.point - c_transform.translation(), contact.relative_points[0]);
+        }
+        assert_eq!(DVec3::new(-2.0, 0.0, 0.0), contacts[0].point);
+    }
+}
@@ -5,6 +5,7 @@ pub use processor::{
     get_system_set,
     initialize,
 };
+pub(crate) use processor::can_collide;
 pub use contact_generation::dispatcher::{
     generate_primative_contacts,
     generate_boundary_contacts,
@@ -2,15 +2,26 @@ use bevy::{
     prelude::*,
     math::DVec3,
 };
+use std::collections::HashSet;
 
 use crate::{
-    constants,
     physics::collision_detection,
     physics::components::{
         BoundaryCollider,
         Collider,
+        CollisionGroups,
         Mass,
         PhysTransform,
+        RigidBodyType,
+        Sensor,
+        Sleeping,
+    },
+    physics::events::BodyEscaped,
+    physics::resources::{
+        CollisionFilter,
+        ContactManifolds,
+        PhysicsConfig,
+        SensorOverlaps,
     },
     physics::shapes::Aabb3D,
     physics::oct_tree::{
@@ -23,6 +34,12 @@ use crate::{
 /// A vector list containing possible collisions represented by the pair of Entitys concerned.
 type CollisionCandidates = Vec<(Entity, Entity)>;
 
+/// A query filter matching the Entitys held in the OctTree, i.e. bodies with mass and sensors.
+type InTree = Or<(With<Mass>, With<Sensor>)>;
+
+/// The dimensions of an OctTree: its maximum depth, and the centre and extents of its play area.
+type TreeDimensions = (i32, DVec3, DVec3);
+
 /// System labels covering sub-systems in the collision detection and contact generation process.
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
 enum CollisionDetectionSystems {
@@ -50,54 +67,128 @@ pub fn get_system_set() -> SystemSet {
 /// A system to be run at startup that performs necessary setup for collision detection to run.
 ///
 /// Creates resources required for collision detection and contact generation. Namely, an OctTree
-/// used for spatial partitioning, filling it with currently available primative shapes, and the
-/// CollisionCandidates vector.
+/// used for spatial partitioning, filling it with currently available primative shapes, the
+/// CollisionCandidates vector, the ContactManifolds store and the SensorOverlaps store.
 pub fn initialize(
     mut commands: Commands,
-    shapes_query: Query<(Entity, &Collider, &PhysTransform), With<Mass>>,
+    config: Res<PhysicsConfig>,
+    shapes_query: Query<(Entity, &Collider, &PhysTransform), InTree>,
 ) {
-    // create OctTree that covers the gameplay volume.
-    let centre = DVec3::new(
-        constants::PLAY_AREA_CENTRE_X,
-        constants::PLAY_AREA_CENTRE_Y,
-        constants::PLAY_AREA_CENTRE_Y,
-    );
-    let bounding_box = Aabb3D::from_xyz(
-        constants::PLAY_AREA_EXTENT_X,
-        constants::PLAY_AREA_EXTENT_Y,
-        constants::PLAY_AREA_EXTENT_Y,
-    );
-
-    let mut tree = OctTree::new(constants::MAX_OCT_TREE_DEPTH);
-    tree.initialize(centre, bounding_box);
-
-    // insert the ids of all entities (with mass) that have a primative shape for collisions.
+    let dimensions = (config.max_oct_tree_depth, config.play_area_centre, config.play_area_extents);
+    commands.insert_resource(build_tree(dimensions, &shapes_query));
+
+    // Create collision candidates resource.
+    let collision_candidates: CollisionCandidates = vec![];
+    commands.insert_resource(collision_candidates);
+
+    commands.insert_resource(ContactManifolds::default());
+    commands.insert_resource(SensorOverlaps::default());
+}
+
+/// Creates an OctTree with the given dimensions and fills it with the ids of all entities (with
+/// mass, or sensors) that have a primative shape for collisions.
+fn build_tree(
+    (max_depth, centre, extents): TreeDimensions,
+    shapes_query: &Query<(Entity, &Collider, &PhysTransform), InTree>,
+) -> OctTree<Entity> {
+    let mut tree = OctTree::new(max_depth);
+    tree.initialize(centre, Aabb3D::from_dvec3(extents));
+
     for (ent, collider, transform) in shapes_query.iter() {
         tree.insert(collider, transform, ent);
     }
 
-    commands.insert_resource(tree);
+    tree
+}
 
-    // Create collision candidates resource.
-    let collision_candidates: CollisionCandidates = vec![];
-    commands.insert_resource(collision_candidates);
+/// Returns the centre and extents of a play area that encloses the given play area and the
+/// bounding spheres of all the given shapes, expanded by the given growth factor to leave room for
+/// further movement.
+fn calc_expanded_play_area(
+    centre: DVec3,
+    extents: DVec3,
+    growth_factor: f64,
+    shapes_query: &Query<(Entity, &Collider, &PhysTransform), InTree>,
+) -> (DVec3, DVec3) {
+    let mut min = centre - extents;
+    let mut max = centre + extents;
+
+    for (_, collider, transform) in shapes_query.iter() {
+        let radius = collider.0.bounding_sphere().radius();
+        min = min.min(transform.translation() - DVec3::splat(radius));
+        max = max.max(transform.translation() + DVec3::splat(radius));
+    }
+
+    let centre = (min + max) * 0.5;
+    let extents = (max - min) * 0.5 * growth_factor;
+
+    (centre, extents)
 }
 
 /// Updates the OctTree by inserting any new entities with a Collider into the tree and updating
 /// the position of any entities that have moved since the last frame.
+///
+/// The tree is rebuilt if its dimensions in the PhysicsConfig have changed, the dimensions last
+/// applied being kept locally. A BodyEscaped event is sent for any body that leaves the play area
+/// and, if enabled in the PhysicsConfig, the play area of the tree is expanded to enclose it. The
+/// PhysicsConfig itself is left unchanged.
 fn update_tree(
-    added_query: Query<(Entity, &Collider, &PhysTransform), (With<Mass>, Added<PhysTransform>)>,
-    moved_query: Query<(Entity, &Collider, &PhysTransform), (With<Mass>, Changed<PhysTransform>)>,
+    config: Res<PhysicsConfig>,
+    mut applied: Local<Option<TreeDimensions>>,
+    shapes_query: Query<(Entity, &Collider, &PhysTransform), InTree>,
+    added_query: Query<(Entity, &Collider, &PhysTransform), (InTree, Added<PhysTransform>)>,
+    moved_query: Query<(Entity, &Collider, &PhysTransform), (InTree, Changed<PhysTransform>)>,
     mut tree: ResMut<OctTree<Entity>>,
+    mut escaped_events: EventWriter<BodyEscaped>,
 ) {
-    // insert any new items into the tree.
-    for (ent, collider, transform) in added_query.iter() {
-        tree.insert(collider, transform, ent);
+    let root = tree.get_root_node().expect("The OctTree has not been initialised!");
+    let current = (tree.max_depth(), root.centre, *root.boundary.extents());
+
+    // the tree was first built from the PhysicsConfig, so is taken to match it.
+    let configured = (config.max_oct_tree_depth, config.play_area_centre, config.play_area_extents);
+    let previous = applied.replace(configured).unwrap_or(current);
+
+    let escape = |entity, transform: &PhysTransform| {
+        BodyEscaped { entity, translation: transform.translation() }
+    };
+
+    if previous != configured {
+        let was_escaped: HashSet<Entity> = tree.escaped().copied().collect();
+        *tree = build_tree(configured, &shapes_query);
+
+        for (ent, _, transform) in shapes_query.iter() {
+            if tree.is_escaped(ent) && !was_escaped.contains(&ent) {
+                escaped_events.send(escape(ent, transform));
+            }
+        }
+    } else {
+        // insert any new items into the tree.
+        for (ent, collider, transform) in added_query.iter() {
+            if !tree.insert(collider, transform, ent) {
+                escaped_events.send(escape(ent, transform));
+            }
+        }
+
+        // update any existing items that have moved.
+        for (ent, collider, transform) in moved_query.iter() {
+            let was_escaped = tree.is_escaped(ent);
+
+            if !tree.update(collider, transform, ent) && !was_escaped {
+                escaped_events.send(escape(ent, transform));
+            }
+        }
     }
 
-    // update any existing items that have moved.
-    for (ent, collider, transform) in moved_query.iter() {
-        tree.update(collider, transform, ent);
+    if config.auto_expand_play_area && tree.escaped().next().is_some() {
+        let root = tree.get_root_node().expect("The OctTree has not been initialised!");
+        let (centre, extents) = calc_expanded_play_area(
+            root.centre,
+            *root.boundary.extents(),
+            config.play_area_growth_factor,
+            &shapes_query,
+        );
+
+        *tree = build_tree((tree.max_depth(), centre, extents), &shapes_query);
     }
 }
 
@@ -152,19 +243,59 @@ fn broad_phase(
 }
 
 /// Narrow-phase collision detection and contact generation. Any contacts found are added to the
-/// ECS.
+/// ContactManifold for the pair of Entitys involved, and the manifolds of any pairs no longer in
+/// contact are expired.
+///
+/// Where one of the Entitys is a Sensor the overlap is recorded in the SensorOverlaps instead, and
+/// no contacts are kept. Sensors do not overlap other sensors or boundaries.
+///
+/// Pairs of Entitys whose CollisionGroups do not interact, or that are rejected by the
+/// CollisionFilter, are skipped.
+///
+/// Contacts are not regenerated between pairs of Entitys that are both asleep or immovable, nor
+/// between a sleeping Entity and a boundary. Their manifolds are retained as they are.
+#[allow(clippy::too_many_arguments)]
 fn contact_generation(
-    mut commands: Commands,
+    config: Res<PhysicsConfig>,
     collider_query: Query<(Entity, &Collider, &PhysTransform)>,
-    boundary_query: Query<(&BoundaryCollider, &PhysTransform)>,
+    boundary_query: Query<(Entity, &BoundaryCollider, &PhysTransform)>,
+    sensor_query: Query<&Sensor>,
+    groups_query: Query<&CollisionGroups>,
+    sleeping_query: Query<&Sleeping>,
+    awake_query: Query<(&Mass, Option<&RigidBodyType>), Without<Sleeping>>,
+    filter: Res<CollisionFilter>,
     mut candidates: ResMut<CollisionCandidates>,
+    mut manifolds: ResMut<ContactManifolds>,
+    mut overlaps: ResMut<SensorOverlaps>,
 ) {
+    manifolds.begin_step();
+    overlaps.begin_step();
+
     // work through the collision candidates list of primatives produced by the OctTree and generate
     // contacts.
     while let Some((ent_a, ent_b)) = candidates.pop() {
+        // order the pair so that the contacts generated for it are consistent between steps.
+        let (ent_a, ent_b) = if ent_a < ent_b { (ent_a, ent_b) } else { (ent_b, ent_a) };
+
+        if !can_collide(ent_a, ent_b, &groups_query, &filter) {
+            continue;
+        }
+
         if let (Ok((ent_a, collider_a, transform_a)), Ok((ent_b, collider_b, transform_b))) =
             (collider_query.get(ent_a), collider_query.get(ent_b))
         {
+            let sensor = match (sensor_query.get(ent_a).is_ok(), sensor_query.get(ent_b).is_ok()) {
+                (true, true) => continue,
+                (true, false) => Some((ent_a, ent_b)),
+                (false, true) => Some((ent_b, ent_a)),
+                (false, false) => None,
+            };
+
+            if sensor.is_none() && !is_active(ent_a, &awake_query) && !is_active(ent_b, &awake_query) {
+                manifolds.retain(ent_a, ent_b);
+                continue;
+            }
+
             let contacts = collision_detection::generate_primative_contacts(
                 ent_a,
                 ent_b,
@@ -174,34 +305,68 @@ fn contact_generation(
                 transform_b,
             );
 
-            // spawn entity(s) holding each discovered contact as a component.
-            if let Some(c) = contacts {
-                for contact in c {
-                    commands.spawn()
-                        .insert(contact);
-                }
+            if let (Some((sensor, other)), Some(_)) = (sensor, &contacts) {
+                overlaps.insert(sensor, other);
+            } else if let Some(c) = contacts {
+                let transform = if c[0].entities[0] == ent_a { transform_a } else { transform_b };
+
+                manifolds.update(c, transform, config.contact_match_tolerance);
             }
         }
     }
 
     // test all internal colliders for contact with the boundaries.
-    for (bnd, bnd_transform) in boundary_query.iter() {
+    for (bnd_ent, bnd, bnd_transform) in boundary_query.iter() {
         for (coll_ent, coll, coll_transform) in collider_query.iter() {
+            if sensor_query.get(coll_ent).is_ok()
+                || !can_collide(coll_ent, bnd_ent, &groups_query, &filter)
+            {
+                continue;
+            }
+            if sleeping_query.get(coll_ent).is_ok() {
+                manifolds.retain(coll_ent, bnd_ent);
+                continue;
+            }
+
             let contacts = collision_detection::generate_boundary_contacts(
                 coll_ent,
+                bnd_ent,
                 &bnd.0,
                 &coll.0,
                 bnd_transform,
                 coll_transform,
             );
 
-            // spawn entity(s) holding each discovered contact as a component.
             if let Some(c) = contacts {
-                for contact in c {
-                    commands.spawn()
-                        .insert(contact);
-                }
+                manifolds.update(c, coll_transform, config.contact_match_tolerance);
             }
         }
     }
+
+    manifolds.remove_expired();
+}
+
+/// Returns true if the given Entity is a body that is awake and able to move, i.e. not Static.
+fn is_active(
+    entity: Entity,
+    awake_query: &Query<(&Mass, Option<&RigidBodyType>), Without<Sleeping>>,
+) -> bool {
+    match awake_query.get(entity) {
+        Ok((mass, body_type)) => !RigidBodyType::of(body_type, mass).is_static(),
+        Err(_) => false,
+    }
+}
+
+/// Returns true if the given pair of Entitys may collide, according to their CollisionGroups (if
+/// any) and the CollisionFilter.
+pub(crate) fn can_collide(
+    a: Entity,
+    b: Entity,
+    groups_query: &Query<&CollisionGroups>,
+    filter: &CollisionFilter,
+) -> bool {
+    let groups_a = groups_query.get(a).unwrap_or(&CollisionGroups::ALL);
+    let groups_b = groups_query.get(b).unwrap_or(&CollisionGroups::ALL);
+
+    groups_a.interacts_with(groups_b) && filter.allows(a, b)
 }
@@ -0,0 +1,90 @@
+use bevy::{
+    prelude::*,
+    math::DVec3,
+};
+
+use crate::{
+    physics::events::{
+        CollisionEnded,
+        CollisionPersisted,
+        CollisionStarted,
+        SensorEntered,
+        SensorExited,
+    },
+    physics::resources::{
+        ContactManifold,
+        ContactManifolds,
+        SensorOverlaps,
+    },
+};
+
+/// System labels covering sub-systems in the collision event process.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+enum CollisionEventSystems {
+    Send,
+}
+
+/// A SystemSet that reports the current state of contacts and sensor overlaps between Entitys as
+/// events.
+pub fn get_system_set() -> SystemSet {
+    SystemSet::new()
+        .with_system(send_collision_events.system()
+                     .label(CollisionEventSystems::Send)
+        )
+        .with_system(send_sensor_events.system()
+                     .label(CollisionEventSystems::Send)
+        )
+}
+
+/// Sends a CollisionStarted or CollisionPersisted event for each current ContactManifold,
+/// depending on whether it is new in this physics step, and a CollisionEnded event for each
+/// manifold that expired in this step.
+fn send_collision_events(
+    manifolds: Res<ContactManifolds>,
+    mut started_events: EventWriter<CollisionStarted>,
+    mut persisted_events: EventWriter<CollisionPersisted>,
+    mut ended_events: EventWriter<CollisionEnded>,
+) {
+    for manifold in manifolds.iter() {
+        let (entities, normal, points, impulse) = calc_event_data(manifold);
+
+        if manifold.age() == 0 {
+            started_events.send(CollisionStarted { entities, normal, points, impulse });
+        } else {
+            persisted_events.send(CollisionPersisted { entities, normal, points, impulse });
+        }
+    }
+
+    for manifold in manifolds.expired() {
+        let (entities, normal, points, impulse) = calc_event_data(manifold);
+
+        ended_events.send(CollisionEnded { entities, normal, points, impulse });
+    }
+}
+
+/// Sends a SensorEntered event for each overlap with a Sensor that began in this physics step, and
+/// a SensorExited event for each that ended.
+fn send_sensor_events(
+    overlaps: Res<SensorOverlaps>,
+    mut entered_events: EventWriter<SensorEntered>,
+    mut exited_events: EventWriter<SensorExited>,
+) {
+    for (sensor, entity) in overlaps.entered() {
+        entered_events.send(SensorEntered { sensor: *sensor, entity: *entity });
+    }
+
+    for (sensor, entity) in overlaps.exited() {
+        exited_events.send(SensorExited { sensor: *sensor, entity: *entity });
+    }
+}
+
+/// Returns the pair of Entitys, contact normal, contact points and total impulse magnitude of the
+/// given manifold.
+fn calc_event_data(manifold: &ContactManifold) -> ((Entity, Entity), DVec3, Vec<DVec3>, f64) {
+    (
+        manifold.pair(),
+        manifold.normal(),
+        manifold.points().iter().map(|p| p.contact.point).collect(),
+        manifold.impulse(),
+    )
+}
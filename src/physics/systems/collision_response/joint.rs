@@ -0,0 +1,687 @@
+use bevy::math::{
+    DQuat,
+    DVec3,
+};
+use std::f64::consts::TAU;
+
+use crate::physics::{
+    components::{
+        Joint,
+        JointKind,
+    },
+    systems::collision_response::solver::{
+        self,
+        SolverBody,
+    },
+};
+
+/// A Joint between two bodies held by the solver. The joint is broken down into rows, each
+/// removing a single degree of freedom, that are resolved in turn.
+#[derive(Debug, Clone)]
+pub(super) struct SolverJoint {
+    /// Indices of the bodies involved.
+    pub bodies: [usize; 2],
+    kind: JointKind,
+    local_anchors: [DVec3; 2],
+    local_axes: [DVec3; 2],
+    /// A direction perpendicular to each local axis, used to align the bodies' frames.
+    local_references: [DVec3; 2],
+    /// The angle of a Hinge at the end of the previous step, from which the current angle is
+    /// tracked through whole turns.
+    angle: f64,
+    /// The largest impulse the motor (if any) can apply in a step.
+    max_motor_impulse: f64,
+    /// Which limit (if any) is active for the current step; 1.0 for the lower, -1.0 for the upper.
+    limit_sign: f64,
+    /// The total impulse applied by the limit so far, only ever in the direction of limit_sign.
+    limit_impulse: f64,
+    /// The total impulse applied by the motor so far.
+    motor_impulse: f64,
+}
+
+/// A single degree of freedom removed by a joint. The row constrains the relative velocity of the
+/// second body with respect to the first, either of the points attached to each body along a
+/// direction (Linear) or of their rotation about an axis (Angular), in global coords.
+#[derive(Debug, Clone, Copy)]
+enum Row {
+    Linear { direction: DVec3, relative_points: [DVec3; 2] },
+    Angular { axis: DVec3 },
+}
+
+/// The state of a joint's attachments in global coords.
+struct JointFrame {
+    /// The anchors relative to the centre of each body.
+    relative_points: [DVec3; 2],
+    /// The separation of the second anchor from the first.
+    separation: DVec3,
+    axes: [DVec3; 2],
+    references: [DVec3; 2],
+}
+
+impl SolverJoint {
+    /// Creates a new SolverJoint for the given Joint between the bodies with the given indices,
+    /// in the given solver bodies. Where the Joint has no reference directions, they are captured
+    /// from the current orientation of the bodies. The step is the duration of the physics step,
+    /// used to limit the impulse of a motor. A zero local axis is taken to be the y-axis.
+    pub fn new(
+        joint: &Joint,
+        bodies: [usize; 2],
+        solver_bodies: &[SolverBody],
+        step: f64,
+    ) -> Self {
+        let normalize = |axis: DVec3| match axis.normalize_or_zero() {
+            axis if axis == DVec3::ZERO => DVec3::Y,
+            axis => axis,
+        };
+        let local_axes = [normalize(joint.local_axes[0]), normalize(joint.local_axes[1])];
+        let rotations = [solver_bodies[bodies[0]].rotation, solver_bodies[bodies[1]].rotation];
+
+        let max_motor_impulse = match joint.kind {
+            JointKind::Hinge { motor: Some(motor), .. } => motor.max_torque * step,
+            _ => 0.0,
+        };
+
+        Self {
+            bodies,
+            kind: joint.kind,
+            local_anchors: joint.local_anchors,
+            local_axes,
+            local_references: calc_local_references(joint, local_axes, rotations),
+            angle: joint.angle(),
+            max_motor_impulse,
+            limit_sign: 0.0,
+            limit_impulse: 0.0,
+            motor_impulse: 0.0,
+        }
+    }
+
+    /// Returns the reference direction in each body's local coords.
+    pub fn local_references(&self) -> [DVec3; 2] {
+        self.local_references
+    }
+
+    /// Returns the angle of a Hinge given the current positions of the bodies, tracked through
+    /// whole turns from its angle at the end of the previous step.
+    pub fn angle(&self, bodies: &[SolverBody]) -> f64 {
+        calc_hinge_angle(&self.calc_frame(bodies), self.angle)
+    }
+
+    /// Determines which limit, if any, is active given the current positions of the bodies and
+    /// clears the impulses applied in any previous solve.
+    pub fn prepare(&mut self, bodies: &[SolverBody]) {
+        let frame = self.calc_frame(bodies);
+
+        self.limit_sign = match self.calc_limit_error(&frame) {
+            Some(error) if error <= 0.0 => 1.0,
+            Some(_) => -1.0,
+            None => 0.0,
+        };
+        self.limit_impulse = 0.0;
+        self.motor_impulse = 0.0;
+    }
+
+    /// Applies the impulses required to remove the relative velocity of the bodies in each of the
+    /// joint's constrained degrees of freedom, in turn.
+    pub fn solve_velocity(&mut self, bodies: &mut [SolverBody]) {
+        let frame = self.calc_frame(bodies);
+        let [a, b] = self.bodies;
+        let (mut body_a, mut body_b) = (bodies[a], bodies[b]);
+
+        for (row, _) in self.calc_rows(&frame) {
+            row.resolve_velocity(&mut body_a, &mut body_b, 0.0);
+        }
+
+        // The limit can only push the bodies back within their range.
+        if let Some(row) = self.calc_limit_row(&frame).filter(|_| self.limit_sign != 0.0) {
+            let impulse = row.calc_impulse(&body_a, &body_b, 0.0);
+            let total = if self.limit_sign > 0.0 {
+                (self.limit_impulse + impulse).max(0.0)
+            } else {
+                (self.limit_impulse + impulse).min(0.0)
+            };
+
+            row.apply_impulse(&mut body_a, &mut body_b, total - self.limit_impulse);
+            self.limit_impulse = total;
+        }
+
+        if let JointKind::Hinge { motor: Some(motor), .. } = self.kind {
+            let row = Row::Angular { axis: frame.axes[0] };
+            let impulse = row.calc_impulse(&body_a, &body_b, motor.target_speed);
+            let total = (self.motor_impulse + impulse)
+                .clamp(-self.max_motor_impulse, self.max_motor_impulse);
+
+            row.apply_impulse(&mut body_a, &mut body_b, total - self.motor_impulse);
+            self.motor_impulse = total;
+        }
+
+        bodies[a] = body_a;
+        bodies[b] = body_b;
+    }
+
+    /// Moves the bodies to remove the error in each of the joint's constrained degrees of
+    /// freedom, in turn. Returns the total linear and angular change of each body.
+    pub fn solve_position(&self, bodies: &mut [SolverBody]) -> [(DVec3, DVec3); 2] {
+        let mut changes = [(DVec3::ZERO, DVec3::ZERO); 2];
+        let [a, b] = self.bodies;
+
+        // Each row is resolved against the frame left by the previous one.
+        let row_count = self.calc_rows(&self.calc_frame(bodies)).len();
+
+        for i in 0..=row_count {
+            let frame = self.calc_frame(bodies);
+
+            let (row, error) = if i < row_count {
+                self.calc_rows(&frame)[i]
+            } else {
+                match (self.calc_limit_row(&frame), self.calc_limit_error(&frame)) {
+                    (Some(row), Some(error)) => (row, error),
+                    _ => break,
+                }
+            };
+
+            let (mut body_a, mut body_b) = (bodies[a], bodies[b]);
+            let moves = row.resolve_position(&mut body_a, &mut body_b, error);
+            bodies[a] = body_a;
+            bodies[b] = body_b;
+
+            for (change, (linear, angular)) in changes.iter_mut().zip(moves.iter()) {
+                change.0 += *linear;
+                change.1 += *angular;
+            }
+        }
+
+        changes
+    }
+
+    // --- Helper methods
+
+    fn calc_frame(&self, bodies: &[SolverBody]) -> JointFrame {
+        let [a, b] = [&bodies[self.bodies[0]], &bodies[self.bodies[1]]];
+
+        let relative_points = [
+            a.rotation.mul_vec3(self.local_anchors[0]),
+            b.rotation.mul_vec3(self.local_anchors[1]),
+        ];
+
+        JointFrame {
+            relative_points,
+            separation: (b.translation + relative_points[1]) - (a.translation + relative_points[0]),
+            axes: [a.rotation.mul_vec3(self.local_axes[0]), b.rotation.mul_vec3(self.local_axes[1])],
+            references: [
+                a.rotation.mul_vec3(self.local_references[0]),
+                b.rotation.mul_vec3(self.local_references[1]),
+            ],
+        }
+    }
+
+    /// Returns the rows that always apply to the joint, along with the current position error
+    /// in each.
+    fn calc_rows(&self, frame: &JointFrame) -> Vec<(Row, f64)> {
+        let mut rows = vec![];
+        let axis = frame.axes[0];
+        let basis = solver::calc_contact_basis(axis);
+        let perpendiculars = [basis.y_axis, basis.z_axis];
+
+        // The point on the first body that coincides with the second anchor, for rows that allow
+        // the anchors to separate.
+        let sliding_points = [frame.relative_points[0] + frame.separation, frame.relative_points[1]];
+
+        let holds_anchors = matches!(
+            self.kind,
+            JointKind::Fixed | JointKind::BallAndSocket | JointKind::Hinge { .. }
+        );
+        let holds_frames = matches!(self.kind, JointKind::Fixed | JointKind::Slider { .. });
+
+        if holds_anchors {
+            for direction in [DVec3::X, DVec3::Y, DVec3::Z].iter() {
+                rows.push((
+                    Row::Linear { direction: *direction, relative_points: frame.relative_points },
+                    frame.separation.dot(*direction),
+                ));
+            }
+        }
+
+        if holds_frames {
+            // The small rotation taking the first frame onto the second. For each axis of the
+            // frame, v_a x v_b = w - v_a * (v_a . w), which sum to 2w.
+            let thirds = [
+                frame.axes[0].cross(frame.references[0]),
+                frame.axes[1].cross(frame.references[1]),
+            ];
+            let rotation = 0.5 * (frame.axes[0].cross(frame.axes[1])
+                + frame.references[0].cross(frame.references[1])
+                + thirds[0].cross(thirds[1]));
+
+            for direction in [DVec3::X, DVec3::Y, DVec3::Z].iter() {
+                rows.push((Row::Angular { axis: *direction }, rotation.dot(*direction)));
+            }
+        }
+
+        match self.kind {
+            JointKind::Hinge { .. } => {
+                let misalignment = frame.axes[0].cross(frame.axes[1]);
+
+                for perpendicular in perpendiculars.iter() {
+                    rows.push((Row::Angular { axis: *perpendicular }, misalignment.dot(*perpendicular)));
+                }
+            },
+            JointKind::Slider { .. } => {
+                for perpendicular in perpendiculars.iter() {
+                    rows.push((
+                        Row::Linear { direction: *perpendicular, relative_points: sliding_points },
+                        frame.separation.dot(*perpendicular),
+                    ));
+                }
+            },
+            JointKind::Distance { length } => {
+                let distance = frame.separation.length();
+
+                if distance > 0.0 {
+                    rows.push((
+                        Row::Linear {
+                            direction: frame.separation / distance,
+                            relative_points: frame.relative_points,
+                        },
+                        distance - length,
+                    ));
+                }
+            },
+            _ => {},
+        }
+
+        rows
+    }
+
+    /// Returns the row along which the joint's limits (if any) act.
+    fn calc_limit_row(&self, frame: &JointFrame) -> Option<Row> {
+        match self.kind {
+            JointKind::Hinge { limits: Some(_), .. } => Some(Row::Angular { axis: frame.axes[0] }),
+            JointKind::Slider { limits: Some(_) } => Some(Row::Linear {
+                direction: frame.axes[0],
+                relative_points: [frame.relative_points[0] + frame.separation, frame.relative_points[1]],
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns how far beyond its limits the joint is, negative below the lower limit and
+    /// positive above the upper, or None if it is within them.
+    fn calc_limit_error(&self, frame: &JointFrame) -> Option<f64> {
+        let (value, (min, max)) = match self.kind {
+            JointKind::Hinge { limits: Some(limits), .. } => {
+                (calc_hinge_angle(frame, self.angle), limits)
+            },
+            JointKind::Slider { limits: Some(limits) } => (frame.separation.dot(frame.axes[0]), limits),
+            _ => return None,
+        };
+
+        if value <= min {
+            Some(value - min)
+        } else if value >= max {
+            Some(value - max)
+        } else {
+            None
+        }
+    }
+}
+
+impl Row {
+    /// Returns the velocity of the second body relative to the first along the row.
+    fn calc_relative_velocity(&self, a: &SolverBody, b: &SolverBody) -> f64 {
+        match self {
+            Row::Linear { direction, relative_points } => {
+                let velocity_a = a.velocity + a.angular_velocity.cross(relative_points[0]);
+                let velocity_b = b.velocity + b.angular_velocity.cross(relative_points[1]);
+
+                direction.dot(velocity_b - velocity_a)
+            },
+            Row::Angular { axis } => axis.dot(b.angular_velocity - a.angular_velocity),
+        }
+    }
+
+    /// Returns the change in relative velocity along the row per unit impulse.
+    fn calc_velocity_per_unit_impulse(&self, a: &SolverBody, b: &SolverBody) -> f64 {
+        let per_body = |body: &SolverBody, i: usize| match self {
+            Row::Linear { direction, relative_points } => direction.dot(
+                solver::calc_velocity_per_unit_impulse(
+                    body.inverse_mass,
+                    body.inverse_inertia_tensor,
+                    relative_points[i],
+                ).mul_vec3(*direction)
+            ),
+            Row::Angular { axis } => axis.dot(body.inverse_inertia_tensor.mul_vec3(*axis)),
+        };
+
+        per_body(a, 0) + per_body(b, 1)
+    }
+
+    /// Returns the impulse that brings the relative velocity along the row to the given target.
+    fn calc_impulse(&self, a: &SolverBody, b: &SolverBody, target_velocity: f64) -> f64 {
+        let velocity_per_unit_impulse = self.calc_velocity_per_unit_impulse(a, b);
+
+        // Both bodies are immovable along the row.
+        if velocity_per_unit_impulse <= f64::EPSILON {
+            return 0.0;
+        }
+
+        (target_velocity - self.calc_relative_velocity(a, b)) / velocity_per_unit_impulse
+    }
+
+    /// Applies the given impulse along the row to the second body, and the reverse to the first.
+    fn apply_impulse(&self, a: &mut SolverBody, b: &mut SolverBody, impulse: f64) {
+        match self {
+            Row::Linear { direction, relative_points } => {
+                a.apply_impulse(-impulse * *direction, relative_points[0]);
+                b.apply_impulse(impulse * *direction, relative_points[1]);
+            },
+            Row::Angular { axis } => {
+                a.angular_velocity -= a.inverse_inertia_tensor.mul_vec3(impulse * *axis);
+                b.angular_velocity += b.inverse_inertia_tensor.mul_vec3(impulse * *axis);
+            },
+        }
+    }
+
+    fn resolve_velocity(&self, a: &mut SolverBody, b: &mut SolverBody, target_velocity: f64) {
+        let impulse = self.calc_impulse(a, b, target_velocity);
+        self.apply_impulse(a, b, impulse);
+    }
+
+    /// Moves the bodies to remove the given position error along the row, in proportion to their
+    /// inertia, returning the linear and angular change of each.
+    fn resolve_position(&self, a: &mut SolverBody, b: &mut SolverBody, error: f64) -> [(DVec3, DVec3); 2] {
+        let velocity_per_unit_impulse = self.calc_velocity_per_unit_impulse(a, b);
+
+        if velocity_per_unit_impulse <= f64::EPSILON {
+            return [(DVec3::ZERO, DVec3::ZERO); 2];
+        }
+
+        // The move per unit impulse matches the change in velocity per unit impulse, so the move
+        // that removes the error is found in the same way as an impulse.
+        let impulse = -error / velocity_per_unit_impulse;
+
+        let calc_change = |body: &SolverBody, i: usize, impulse: f64| match self {
+            Row::Linear { direction, relative_points } => (
+                body.inverse_mass * impulse * *direction,
+                body.inverse_inertia_tensor.mul_vec3(relative_points[i].cross(impulse * *direction)),
+            ),
+            Row::Angular { axis } => (
+                DVec3::ZERO,
+                body.inverse_inertia_tensor.mul_vec3(impulse * *axis),
+            ),
+        };
+
+        let changes = [calc_change(a, 0, -impulse), calc_change(b, 1, impulse)];
+
+        a.apply_move(changes[0].0, changes[0].1);
+        b.apply_move(changes[1].0, changes[1].1);
+
+        changes
+    }
+}
+
+/// Returns the reference direction, perpendicular to the axis, in each body's local coords. The
+/// Joint's own references are used if it has them, otherwise the second body's reference is
+/// captured from the first, given the current rotation of each body.
+fn calc_local_references(
+    joint: &Joint,
+    local_axes: [DVec3; 2],
+    rotations: [DQuat; 2],
+) -> [DVec3; 2] {
+    let perpendicular = |reference: DVec3, axis: DVec3| {
+        let reference = (reference - axis * axis.dot(reference)).normalize_or_zero();
+
+        if reference == DVec3::ZERO {
+            solver::calc_contact_basis(axis).y_axis
+        } else {
+            reference
+        }
+    };
+
+    let [reference_a, reference_b] = joint.local_references.unwrap_or_else(|| {
+        let reference_a = solver::calc_contact_basis(local_axes[0]).y_axis;
+        let global = rotations[0].mul_vec3(reference_a);
+
+        [reference_a, rotations[1].conjugate().mul_vec3(global)]
+    });
+
+    [perpendicular(reference_a, local_axes[0]), perpendicular(reference_b, local_axes[1])]
+}
+
+/// Returns the angle of the second body about the hinge axis relative to the first, from the
+/// alignment of their reference directions. The angle is taken to be the one nearest to the given
+/// previous angle, so that it keeps counting through whole turns.
+fn calc_hinge_angle(frame: &JointFrame, previous: f64) -> f64 {
+    let [reference_a, reference_b] = frame.references;
+
+    let wrapped = frame.axes[0].dot(reference_a.cross(reference_b))
+        .atan2(reference_a.dot(reference_b));
+    let change = wrapped - previous;
+
+    previous + change - TAU * (change / TAU).round()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use bevy::{
+        prelude::*,
+        math::{
+            DMat3,
+            DQuat,
+        },
+    };
+    use std::f64::consts::{
+        FRAC_PI_2,
+        PI,
+    };
+
+    const EPSILON: f64 = 0.000001;
+
+    fn body(inverse_mass: f64, inverse_inertia: f64, translation: DVec3) -> SolverBody {
+        SolverBody {
+            inverse_mass,
+            inverse_inertia_tensor: DMat3::from_diagonal(DVec3::splat(inverse_inertia)),
+            velocity: DVec3::ZERO,
+            angular_velocity: DVec3::ZERO,
+            translation,
+            rotation: DQuat::IDENTITY,
+        }
+    }
+
+    fn entities() -> [Entity; 2] {
+        [Entity::new(1), Entity::new(2)]
+    }
+
+    #[test]
+    fn test_ball_and_socket() {
+        // two point masses, their anchors a unit apart.
+        let mut bodies = vec![body(1.0, 0.0, DVec3::ZERO), body(1.0, 0.0, DVec3::new(3.0, 0.0, 0.0))];
+        bodies[0].velocity = DVec3::new(-1.0, 1.0, 0.0);
+        bodies[1].velocity = DVec3::new(1.0, -1.0, 0.0);
+
+        let joint = Joint::ball_and_socket(entities(), [DVec3::X, -DVec3::X]);
+        let mut solver_joint = SolverJoint::new(&joint, [0, 1], &bodies, 0.1);
+
+        solver_joint.prepare(&bodies);
+        solver_joint.solve_velocity(&mut bodies);
+        assert!(bodies[0].velocity.length() < EPSILON);
+        assert!(bodies[1].velocity.length() < EPSILON);
+
+        // both bodies move half way.
+        let changes = solver_joint.solve_position(&mut bodies);
+        assert!((bodies[0].translation - DVec3::new(0.5, 0.0, 0.0)).length() < EPSILON);
+        assert!((bodies[1].translation - DVec3::new(2.5, 0.0, 0.0)).length() < EPSILON);
+        assert!((changes[1].0 - DVec3::new(-0.5, 0.0, 0.0)).length() < EPSILON);
+    }
+
+    #[test]
+    fn test_hinge() {
+        // a door hung from an immovable frame, about the y-axis.
+        let mut bodies = vec![body(0.0, 0.0, DVec3::ZERO), body(1.0, 1.0, DVec3::ZERO)];
+        bodies[1].angular_velocity = DVec3::new(1.0, 2.0, 0.0);
+
+        let joint = Joint::hinge(entities(), [DVec3::ZERO; 2], [DVec3::Y; 2]);
+        let mut solver_joint = SolverJoint::new(&joint, [0, 1], &bodies, 0.1);
+
+        solver_joint.prepare(&bodies);
+        solver_joint.solve_velocity(&mut bodies);
+        assert!((bodies[1].angular_velocity - DVec3::new(0.0, 2.0, 0.0)).length() < EPSILON);
+
+        // the motor drives the door towards its target speed, limited by its torque.
+        let joint = joint.with_motor(1.0, 100.0);
+        let mut solver_joint = SolverJoint::new(&joint, [0, 1], &bodies, 0.1);
+        solver_joint.prepare(&bodies);
+        solver_joint.solve_velocity(&mut bodies);
+        assert!((bodies[1].angular_velocity - DVec3::new(0.0, 1.0, 0.0)).length() < EPSILON);
+
+        let joint = joint.with_motor(-1.0, 1.0);
+        let mut solver_joint = SolverJoint::new(&joint, [0, 1], &bodies, 0.1);
+        solver_joint.prepare(&bodies);
+        solver_joint.solve_velocity(&mut bodies);
+        assert!((bodies[1].angular_velocity - DVec3::new(0.0, 0.9, 0.0)).length() < EPSILON);
+    }
+
+    #[test]
+    fn test_zero_axis() {
+        // a hinge given no axis turns about the y-axis.
+        let mut bodies = vec![body(0.0, 0.0, DVec3::ZERO), body(1.0, 1.0, DVec3::ZERO)];
+        bodies[1].angular_velocity = DVec3::new(1.0, 2.0, 0.0);
+
+        let joint = Joint::hinge(entities(), [DVec3::ZERO; 2], [DVec3::ZERO; 2]);
+        let mut solver_joint = SolverJoint::new(&joint, [0, 1], &bodies, 0.1);
+
+        solver_joint.prepare(&bodies);
+        solver_joint.solve_velocity(&mut bodies);
+        assert!((bodies[1].angular_velocity - DVec3::new(0.0, 2.0, 0.0)).length() < EPSILON);
+    }
+
+    #[test]
+    fn test_hinge_limits() {
+        // a door opened beyond its upper limit.
+        let mut bodies = vec![body(0.0, 0.0, DVec3::ZERO), body(1.0, 1.0, DVec3::ZERO)];
+        bodies[1].rotation = DQuat::from_rotation_y(0.5);
+        bodies[1].angular_velocity = DVec3::new(0.0, 1.0, 0.0);
+
+        let joint = Joint::hinge(entities(), [DVec3::ZERO; 2], [DVec3::Y; 2])
+            .with_references([DVec3::X; 2])
+            .with_limits(-0.2, 0.2);
+        let mut solver_joint = SolverJoint::new(&joint, [0, 1], &bodies, 0.1);
+
+        // the door can't open further, but can close.
+        solver_joint.prepare(&bodies);
+        solver_joint.solve_velocity(&mut bodies);
+        assert!(bodies[1].angular_velocity.length() < EPSILON);
+
+        bodies[1].angular_velocity = DVec3::new(0.0, -1.0, 0.0);
+        solver_joint.prepare(&bodies);
+        solver_joint.solve_velocity(&mut bodies);
+        assert!((bodies[1].angular_velocity - DVec3::new(0.0, -1.0, 0.0)).length() < EPSILON);
+
+        // the door is moved back to its limit.
+        for _ in 0..4 {
+            solver_joint.solve_position(&mut bodies);
+        }
+        let (axis, angle) = bodies[1].rotation.to_axis_angle();
+        assert!((axis - DVec3::Y).length() < 0.001);
+        assert!((angle - 0.2).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_hinge_angle() {
+        // the references are captured from the door's initial rotation.
+        let mut bodies = vec![body(0.0, 0.0, DVec3::ZERO), body(1.0, 1.0, DVec3::ZERO)];
+        bodies[1].rotation = DQuat::from_rotation_y(1.0);
+
+        let mut joint = Joint::hinge(entities(), [DVec3::ZERO; 2], [DVec3::Y; 2]);
+        let solver_joint = SolverJoint::new(&joint, [0, 1], &bodies, 0.1);
+        assert!(solver_joint.angle(&bodies).abs() < EPSILON);
+        joint.local_references = Some(solver_joint.local_references());
+
+        // the angle keeps counting as the door is turned through one and a half turns.
+        for _ in 0..6 {
+            bodies[1].rotation = DQuat::from_rotation_y(FRAC_PI_2) * bodies[1].rotation;
+            let solver_joint = SolverJoint::new(&joint, [0, 1], &bodies, 0.1);
+            joint.set_angle(solver_joint.angle(&bodies));
+        }
+        assert!((joint.angle() - 3.0 * PI).abs() < EPSILON);
+
+        // so limits beyond a half turn apply.
+        let joint = joint.with_limits(0.0, 2.0 * PI);
+        let mut solver_joint = SolverJoint::new(&joint, [0, 1], &bodies, 0.1);
+        bodies[1].angular_velocity = DVec3::new(0.0, 1.0, 0.0);
+        solver_joint.prepare(&bodies);
+        solver_joint.solve_velocity(&mut bodies);
+        assert!(bodies[1].angular_velocity.length() < EPSILON);
+    }
+
+    #[test]
+    fn test_slider() {
+        let mut bodies = vec![body(0.0, 0.0, DVec3::ZERO), body(1.0, 1.0, DVec3::new(2.0, 0.5, 0.0))];
+        bodies[1].velocity = DVec3::new(1.0, 1.0, 0.0);
+        bodies[1].angular_velocity = DVec3::new(0.0, 0.0, 1.0);
+
+        let joint = Joint::slider(entities(), [DVec3::ZERO; 2], [DVec3::X; 2])
+            .with_limits(-1.0, 1.0);
+        let mut solver_joint = SolverJoint::new(&joint, [0, 1], &bodies, 0.1);
+
+        // only sliding back within the limits is allowed.
+        solver_joint.prepare(&bodies);
+        solver_joint.solve_velocity(&mut bodies);
+        assert!(bodies[1].velocity.length() < EPSILON);
+        assert!(bodies[1].angular_velocity.length() < EPSILON);
+
+        bodies[1].velocity = DVec3::new(-1.0, 0.0, 0.0);
+        solver_joint.prepare(&bodies);
+        solver_joint.solve_velocity(&mut bodies);
+        assert!((bodies[1].velocity - DVec3::new(-1.0, 0.0, 0.0)).length() < EPSILON);
+
+        // moved back on to the axis and within the limits.
+        solver_joint.solve_position(&mut bodies);
+        assert!((bodies[1].translation - DVec3::new(1.0, 0.0, 0.0)).length() < EPSILON);
+    }
+
+    #[test]
+    fn test_distance() {
+        let mut bodies = vec![body(0.0, 0.0, DVec3::ZERO), body(1.0, 0.0, DVec3::new(3.0, 0.0, 0.0))];
+        bodies[1].velocity = DVec3::new(1.0, 1.0, 0.0);
+
+        let joint = Joint::distance(entities(), [DVec3::ZERO; 2], 2.0);
+        let mut solver_joint = SolverJoint::new(&joint, [0, 1], &bodies, 0.1);
+
+        solver_joint.prepare(&bodies);
+        solver_joint.solve_velocity(&mut bodies);
+        assert!((bodies[1].velocity - DVec3::new(0.0, 1.0, 0.0)).length() < EPSILON);
+
+        solver_joint.solve_position(&mut bodies);
+        assert!((bodies[1].translation - DVec3::new(2.0, 0.0, 0.0)).length() < EPSILON);
+    }
+
+    #[test]
+    fn test_fixed() {
+        let mut bodies = vec![body(0.0, 0.0, DVec3::ZERO), body(1.0, 1.0, DVec3::ZERO)];
+        bodies[1].rotation = DQuat::from_rotation_z(0.1);
+        bodies[1].angular_velocity = DVec3::new(1.0, 2.0, 3.0);
+
+        let joint = Joint::fixed(entities(), [DVec3::ZERO; 2]);
+        let mut solver_joint = SolverJoint::new(&joint, [0, 1], &bodies, 0.1);
+
+        solver_joint.prepare(&bodies);
+        solver_joint.solve_velocity(&mut bodies);
+        assert!(bodies[1].angular_velocity.length() < EPSILON);
+
+        for _ in 0..4 {
+            solver_joint.solve_position(&mut bodies);
+        }
+        assert!(bodies[1].rotation.angle_between(DQuat::IDENTITY) < 0.001);
+
+        // a joint without references holds the bodies at their initial relative orientation.
+        let rotation = DQuat::from_rotation_y(0.5);
+        bodies[1].rotation = rotation;
+        let joint = Joint::fixed(entities(), [DVec3::ZERO; 2]);
+        let solver_joint = SolverJoint::new(&joint, [0, 1], &bodies, 0.1);
+
+        solver_joint.solve_position(&mut bodies);
+        assert!(bodies[1].rotation.angle_between(rotation) < EPSILON);
+    }
+}
@@ -1,3 +1,5 @@
+mod joint;
 mod pipeline;
+mod solver;
 
 pub use pipeline::get_system_set;
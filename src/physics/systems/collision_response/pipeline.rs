@@ -2,349 +2,269 @@ use bevy::{
     prelude::*,
     math::{
         DMat3,
-        DQuat,
         DVec3,
     },
 };
+use std::collections::{
+    HashMap,
+    HashSet,
+};
 
 use crate::{
-    constants,
     physics::components::{
         AngularVelocity,
         Contact,
         InertiaTensor,
+        Joint,
+        JointKind,
         Mass,
         PhysTransform,
+        PhysicsMaterial,
+        RigidBodyType,
+        Sleeping,
         Velocity,
     },
+    physics::resources::{
+        ContactManifolds,
+        ManifoldPoint,
+        PhysicsConfig,
+        PhysicsTime,
+    },
+    physics::systems::collision_response::solver::{
+        ContactSolver,
+        SolverBody,
+    },
 };
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
 /// System labels covering collision response sub-systems.
 enum CollisionResponseSystems {
-    CalculateImpulse,
-    ResolvePenetration,
-    ClearContacts,
+    SolveContacts,
 }
 
 /// A SystemSet that calculates and applies a dynamic response to Entitys that are in collision.
 pub fn get_system_set() -> SystemSet {
     SystemSet::new()
-        .with_system(calc_impulse.system()
-                     .label(CollisionResponseSystems::CalculateImpulse)
-        )
-        .with_system(resolve_interpenetration.system()
-                     .label(CollisionResponseSystems::ResolvePenetration)
-                     .after(CollisionResponseSystems::CalculateImpulse)
-        )
-        .with_system(remove_contacts.system()
-                     .label(CollisionResponseSystems::ClearContacts)
-                     .after(CollisionResponseSystems::ResolvePenetration)
+        .with_system(solve_contacts.system()
+                     .label(CollisionResponseSystems::SolveContacts)
         )
 }
 
-/// A system that iterates through available collision contacts, updating their motion by
-/// calculating and applying appropriate impulses and impulsive torques based on the contact and
-/// body parameters.
-fn calc_impulse(
-    contacts_query: Query<&Contact>,
-    mut q: QuerySet<(
-        Query<(&AngularVelocity, &InertiaTensor, &Mass, &Velocity)>,
-        Query<(&InertiaTensor, &Mass, &mut Velocity, &mut AngularVelocity)>,
-    )>,
+/// A system that resolves the contacts in all current ContactManifolds using an iterative
+/// ContactSolver.
+///
+/// Impulses and impulsive torques are applied to the bodies, based on the contact and body
+/// parameters, to remove their closing velocity. Impulses include a frictional component
+/// tangential to the contact, based on the combined PhysicsMaterial of the bodies involved. The
+/// bodies are then translated and rotated to remove the interpenetration between them.
+///
+/// Contacts that persist from the previous step are warm started with a proportion of the
+/// impulse previously applied, as set in the PhysicsConfig. The impulse applied at each contact is
+/// recorded in its manifold.
+///
+/// Joints are resolved by the same solver, ahead of the contacts, for both velocities and
+/// positions. Contacts between bodies connected by a Joint are ignored, unless the Joint allows
+/// them. The reference directions of a Joint are captured when it is first resolved, and the
+/// angle of a Hinge is recorded in its Joint each step.
+///
+/// Kinematic bodies are treated as immovable, pushing the bodies they touch with their current
+/// velocity.
+///
+/// Manifolds and Joints without an awake Dynamic body are left unresolved, as neither body can be
+/// moved by the solver. Joints connecting an Entity that is not a body are skipped with a warning.
+#[allow(clippy::type_complexity)]
+fn solve_contacts(
+    config: Res<PhysicsConfig>,
+    physics_time: Res<PhysicsTime>,
+    mut manifolds: ResMut<ContactManifolds>,
+    materials: Query<&PhysicsMaterial>,
+    mut joints: Query<(Entity, &mut Joint)>,
+    awake_query: Query<(&Mass, Option<&RigidBodyType>), Without<Sleeping>>,
+    mut bodies_query: Query<(&InertiaTensor, &Mass, Option<&RigidBodyType>, &mut Velocity,
+                             &mut AngularVelocity, &mut PhysTransform)>,
 ) {
-    for contact in contacts_query.iter() {
-        debug!("processing contact: {:?}", contact);
+    let mut solver = ContactSolver::new(config.angular_limit, config.low_rotation_threshold);
 
-        // transformation matrices used to convert between global coords and contact coords.
-        let contact_to_global_transform = calc_contact_basis(contact.normal);
-        let global_to_contact_transform = contact_to_global_transform.transpose();
+    let mut body_indices: HashMap<Entity, usize> = HashMap::new();
+    let mut body_entities = vec![];
 
-        debug!("contact to global transform {}", contact_to_global_transform);
-        debug!("global to contact transform {}", contact_to_global_transform.transpose());
-
-        let mut delta_velocity_per_unit_impulse = 0.0;
-        let mut contact_velocity = [DVec3::ZERO, DVec3::ZERO];
+    let is_active = |entity: Entity| match awake_query.get(entity) {
+        Ok((mass, body_type)) => RigidBodyType::of(body_type, mass).is_dynamic(),
+        Err(_) => false,
+    };
 
-        for (i, entity) in contact.entities.iter().enumerate() {
-            let (angular_velocity, inertia_tensor, mass, velocity) = q.q0().get(*entity)
-                .expect("Invalid contact entity");
-
-            delta_velocity_per_unit_impulse += calc_normal_velocity_per_unit_impulse(
-                global_to_contact_transform,
-                mass.inverse(),
-                inertia_tensor.inverse_global(),
-                contact.normal,
-                contact.relative_points[i],
-            );
-
-            contact_velocity[i] = calc_contact_velocity(
-                global_to_contact_transform,
-                contact.relative_points[i],
-                angular_velocity.vector(),
-                velocity.vector(),
-            );
-        }
+    let mut connected: HashSet<(Entity, Entity)> = HashSet::new();
+    let mut joint_entities = vec![];
 
-        let closing_velocity_contact = contact_velocity[0] - contact_velocity[1];
+    for (entity, mut joint) in joints.iter_mut() {
+        let [a, b] = joint.entities;
 
-        debug!("closing velocity in contact coords {}", closing_velocity_contact);
-        debug!("delta velocity along normal per unit impulse {}", delta_velocity_per_unit_impulse);
+        if !joint.collide_connected {
+            connected.insert((a, b));
+            connected.insert((b, a));
+        }
 
-        // Desired change in velocity = -(1 + c) * closing velocity in direction of contact normal.
-        // TODO make restitution coeff contact specific.
-        let delta_velocity = -(1.0 + constants::RESTITUTION_COEFF) * closing_velocity_contact.x;
+        if a == b || !(is_active(a) || is_active(b)) { continue; }
 
-        // Frictionless, so impulse is only in direction of contact normal.
-        let mut impulse_contact = DVec3::new(
-            delta_velocity / delta_velocity_per_unit_impulse,
-            0.0,
-            0.0,
+        let mut add_body = |entity: Entity| add_solver_body(
+            entity, &mut solver, &mut body_indices, &mut body_entities, &mut bodies_query,
         );
 
-        debug!("impulse in contact coords before checking sign {}", impulse_contact);
+        let (index_a, index_b) = match (add_body(a), add_body(b)) {
+            (Some(index_a), Some(index_b)) => (index_a, index_b),
+            _ => {
+                warn!("skipping joint {:?}: both entities must have a Mass and InertiaTensor",
+                      entity);
+                continue;
+            },
+        };
+
+        let step = physics_time.step();
+        let local_references = solver.add_joint(&joint, [index_a, index_b], step);
+        joint_entities.push(entity);
+
+        // Capture the references the first time the joint is resolved.
+        if joint.local_references.is_none() {
+            joint.local_references = Some(local_references);
+        }
+    }
+
+    let mut points: Vec<&mut ManifoldPoint> = manifolds.iter_mut()
+        .filter(|m| {
+            let (a, b) = m.pair();
+            (is_active(a) || is_active(b)) && !connected.contains(&(a, b))
+        })
+        .flat_map(|m| m.points_mut().iter_mut())
+        .collect();
 
-        // Make sure normal impulse is < 0. i.e. it acts in the opposite direction to the contact
-        // normal, pushing the 0th body away from the contact.
-        impulse_contact.x = impulse_contact.x.min(0.0);
-        debug!("impulse in contact coords after {}", impulse_contact);
+    for point in points.iter() {
+        let contact = &point.contact;
+        debug!("processing contact: {:?}", contact);
 
-        // Transform impulse back into global coords.
-        let impulse = contact_to_global_transform.mul_vec3(impulse_contact);
-        debug!("impulse in global coords {}", impulse);
+        let mut bodies = [None, None];
+        let mut relative_points = [DVec3::ZERO; 2];
 
-        // Add impulse and impulsive torque to both bodies, reversing the impulse direction for the
-        // second body.
-        for ((i, entity), sign) in contact.entities.iter().enumerate().zip([1.0, -1.0].iter()) {
-            let impulsive_torque = contact.relative_points[i].cross(*sign * impulse);
+        for (i, entity) in contact.entities.iter().enumerate() {
+            relative_points[i] = contact.relative_points[i];
 
-            if let Ok((inertia_tensor, mass, mut velocity, mut ang_velocity)) = q.q1_mut().get_mut(*entity) {
-                velocity.add(mass.inverse() * sign * impulse);
-                ang_velocity.add(inertia_tensor.inverse_global().mul_vec3(impulsive_torque));
-            }
+            bodies[i] = Some(add_solver_body(
+                *entity, &mut solver, &mut body_indices, &mut body_entities, &mut bodies_query,
+            ).expect("Invalid contact entity"));
         }
+
+        solver.add_contact(
+            bodies,
+            contact.normal,
+            contact.penetration,
+            relative_points,
+            calc_contact_material(&config.default_material, &materials, contact),
+            point.accumulated_impulse * config.warm_start_factor,
+        );
     }
-}
 
-/// Calculates and applies a translation and rotation to each movable body involved in a collision
-/// in order to remove the interpenetration between them.
-fn resolve_interpenetration(
-    contact_query: Query<&Contact>,
-    q1: Query<(&InertiaTensor, &Mass)>,
-    mut q2: Query<(&InertiaTensor, &mut PhysTransform)>,
-) {
-    for contact in contact_query.iter() {
-        debug!("contact = {:?}", contact);
-        // --- Calculate inertia.
-        let mut linear_inertia = vec![];
-        let mut angular_inertia = vec![];
+    let contact_count = solver.contact_count();
+    let joint_iterations = config.joint_iterations as usize;
 
-        for (i, entity) in contact.entities.iter().enumerate() {
-            let (inertia_tensor, mass) =
-                q1.get(*entity).expect("Invalid contact entity");
+    solver.solve_joint_velocities(joint_iterations);
+    solver.solve_velocities(config.velocity_iterations as usize * contact_count,
+                            config.velocity_epsilon);
+    solver.solve_joint_positions(joint_iterations);
+    solver.solve_positions(config.position_iterations as usize * contact_count,
+                           config.position_epsilon);
 
-            // Calculate the inertia in the direction of the contact normal.
-            linear_inertia.push(mass.inverse());
+    // Record the impulses applied for the next step.
+    for (index, point) in points.iter_mut().enumerate() {
+        point.accumulated_impulse = solver.impulse(index);
+    }
 
-            let impulsive_torque = contact.relative_points[i].cross(contact.normal);
+    // Record the angle of each hinge for the next step.
+    for (index, entity) in joint_entities.iter().enumerate() {
+        let (_, mut joint) = joints.get_mut(*entity).expect("Invalid joint entity");
 
-            angular_inertia.push(inertia_tensor.inverse_global().mul_vec3(impulsive_torque)
-                                    .cross(contact.relative_points[i])
-                                    .dot(contact.normal));
+        if let JointKind::Hinge { .. } = joint.kind {
+            joint.set_angle(solver.joint_angle(index));
         }
+    }
 
-        let total_inverse_inertia: f64 = 1.0 / (linear_inertia.iter().sum::<f64>()
-                                                + angular_inertia.iter().sum::<f64>());
-        debug!("total inverse inertia {}", total_inverse_inertia);
-
-        // --- Calculate movement required.
-        let mut linear_move_vec = vec![];
-        let mut angular_move_vec = vec![];
+    // Write the results back to the bodies.
+    for (entity, body) in body_entities.iter().zip(solver.bodies()) {
+        let (_, mass, body_type, mut velocity, mut ang_velocity, mut transform) = bodies_query
+            .get_mut(*entity)
+            .expect("Invalid contact entity");
 
-        for (i, sign) in (0..contact.entities.len()).zip(&[1.0, -1.0]) {
-            linear_move_vec.push(sign * contact.penetration * linear_inertia[i] * total_inverse_inertia);
-            angular_move_vec.push(sign * contact.penetration * angular_inertia[i] * total_inverse_inertia);
-        }
-        debug!("linear and ang move before limiting = {:?}, {:?}", linear_move_vec, angular_move_vec);
-
-        // limit angular move to mitigate over-rotation issues.
-        for i in 0..contact.entities.len() {
-            let limit = constants::ANGULAR_LIMIT * contact.relative_points[i].length();
-
-            if angular_move_vec[i] > limit {
-                linear_move_vec[i] += angular_move_vec[i] - limit;
-                angular_move_vec[i] = limit;
-            } else if angular_move_vec[i] < -limit {
-                linear_move_vec[i] += angular_move_vec[i] + limit;
-                angular_move_vec[i] = -limit;
-            }
-        }
-        debug!("linear and ang move after limiting = {:?}, {:?}", linear_move_vec, angular_move_vec);
+        // Only Dynamic bodies are moved by the solver.
+        if !RigidBodyType::of(body_type, mass).is_dynamic() { continue; }
 
-        // --- Apply movement to each body.
-        for (i, entity) in contact.entities.iter().enumerate() {
-            let (inertia_tensor, mut phys_transform) = q2.get_mut(*entity).expect("Entity does not exist!");
-
-            debug!("pos before = {}", phys_transform.translation);
-            phys_transform.translation += linear_move_vec[i] * -contact.normal;
-            debug!("pos after = {}", phys_transform.translation);
-
-            // impulsive torque per unit impulse = rel_pos x normal
-            // delta_omega per unit impulse = I^-1 * impulsive_torque per unit impulse.
-            //
-            // rotation per unit move = delta_omega per unit impulse / delta_v per unit impulse
-            //
-            if (angular_inertia[i].abs() - constants::LOW_ROTATION_THRESHOLD) >= 0.0 {
-                let rotation_change = inertia_tensor.inverse_global().mul_vec3(contact.relative_points[i].cross(contact.normal))
-                                        * (angular_move_vec[i] / angular_inertia[i]);
-
-                debug!("rotation before = {}", phys_transform.rotation);
-                phys_transform.rotation = phys_transform.rotation
-                    .mul_quat(DQuat::from_xyzw(rotation_change.x, rotation_change.y, rotation_change.z, 0.0));
-                debug!("rotation after = {}", phys_transform.rotation);
-            };
+        *velocity = Velocity::new(body.velocity);
+        *ang_velocity = AngularVelocity::new(body.angular_velocity);
 
+        if transform.translation != body.translation || transform.rotation != body.rotation {
+            transform.translation = body.translation;
+            transform.rotation = body.rotation;
         }
     }
 }
 
-/// Purges all Contact Entitys from the ECS.
-fn remove_contacts(
-    mut commands: Commands,
-    contact_entities: Query<Entity, With<Contact>>,
-) {
-    for entity in contact_entities.iter() {
-        commands.entity(entity).despawn();
+// --- Helper methods
+
+/// Returns the index of the given Entity's body in the solver, adding it if it has not been added
+/// already. Returns None if the Entity is not a body.
+#[allow(clippy::type_complexity)]
+fn add_solver_body(
+    entity: Entity,
+    solver: &mut ContactSolver,
+    body_indices: &mut HashMap<Entity, usize>,
+    body_entities: &mut Vec<Entity>,
+    bodies_query: &mut Query<(&InertiaTensor, &Mass, Option<&RigidBodyType>, &mut Velocity,
+                              &mut AngularVelocity, &mut PhysTransform)>,
+) -> Option<usize> {
+    if let Some(index) = body_indices.get(&entity) {
+        return Some(*index);
     }
-}
 
-// --- Helper methods
+    let (inertia_tensor, mass, body_type, velocity, ang_velocity, transform) = bodies_query
+        .get_mut(entity)
+        .ok()?;
 
-/// Takes the contact normal in global space and returns an arbitrary orthonormal basis for the
-/// contact as a DMat3. The matrix represents transformation from contact space into global space.
-/// Note, the origin of contact space is the global origin to simplify calculations (the transform
-/// is a rotation only, so its inverse is the transpose).
-fn calc_contact_basis(normal: DVec3) -> DMat3 {
-    // The normal will be the new x-axis.
-    // Initially, choose the y-axis to be either the global x-axis or global y-axis, whichever is
-    // further from the normal to avoid the parallel case.
-    let mut y = if normal.dot(DVec3::X).abs() - normal.dot(DVec3::Y).abs() > 0.000001 {
-        // normal nearer to global x-axis
-        DVec3::Y
+    // Static and Kinematic bodies are immovable.
+    let (inverse_mass, inverse_inertia_tensor) = if RigidBodyType::of(body_type, mass).is_dynamic() {
+        (mass.inverse(), inertia_tensor.inverse_global())
     } else {
-        // normal nearer to global y-axis
-        DVec3::X
+        (0.0, DMat3::ZERO)
     };
 
-    // z is the normalised vector at right angles to the normal and the chosen y-axis.
-    let z = normal.cross(y).normalize();
-
-    // Then y must be the vector at right angles to this z axis and the normal.
-    y = z.cross(normal);
-
-    DMat3::from_cols(
-        normal,
-        y,
-        z,
-    )
-}
-
-/// Calculates the change in velocity along the contact normal per unit of impulse and returns it
-/// as a f64 value.
-fn calc_normal_velocity_per_unit_impulse(
-    global_to_contact_transform: DMat3,
-    inverse_mass: f64,
-    inverse_inertia_tensor: DMat3,
-    normal: DVec3,
-    relative_contact_position: DVec3,
-) -> f64 {
-    // Normal linear velocity change per unit impulse
-    // v_lin = inverse_mass * J --> replace impulse vector with contact normal.
-    let linear_component = inverse_mass;
-
-    // Angular velocity change per unit impulse
-    // delta ang vel = inverse inertia tensor * impulsive torque
-    // where; impulsive torque = rel position x J --> replace J vector with contact normal.
-    let impulsive_torque_per_unit_impulse = relative_contact_position.cross(normal);
-
-    let angular_velocity_per_unit_impulse =
-        inverse_inertia_tensor.mul_vec3(impulsive_torque_per_unit_impulse);
-
-    // The angular component of the velocity change in global coords.
-    let angular_component_global =
-        angular_velocity_per_unit_impulse.cross(relative_contact_position);
-
-    // Transform to contact coords and take the component along the contact normal (x-axis in
-    // contact coords).
-    let angular_component_vector = global_to_contact_transform.mul_vec3(angular_component_global);
-    let angular_component = angular_component_vector.x;
-
-    // Combine the angular and linear parts to get the result.
-    angular_component + linear_component
-}
-
-/// Returns the velocity of the contact point in contact coords as a DVec3.
-fn calc_contact_velocity(
-    global_to_contact_transform: DMat3,
-    relative_contact_position: DVec3,
-    angular_velocity: DVec3,
-    velocity: DVec3,
-) -> DVec3 {
-    // velocity = linear velocity + (angular velocity x relative position).
-
-    // first calculate velocity in global coords.
-    let mut result = velocity + angular_velocity.cross(relative_contact_position);
+    let index = solver.add_body(SolverBody {
+        inverse_mass,
+        inverse_inertia_tensor,
+        velocity: velocity.vector(),
+        angular_velocity: ang_velocity.vector(),
+        translation: transform.translation,
+        rotation: transform.rotation,
+    });
 
-    debug!("contact velocity in global coords = {}", result);
+    body_indices.insert(entity, index);
+    body_entities.push(entity);
 
-    // convert to contact coords
-    result = global_to_contact_transform.mul_vec3(result);
-
-    debug!("contact velocity in contact coords = {}", result);
-
-    result
+    Some(index)
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    #[test]
-    fn test_calc_contact_basis() {
-        let normal = DVec3::Y;
-        let basis = calc_contact_basis(normal);
-
-        // the normal is close to y-axis, so the new y-axis is the former x-axis.
-        assert_eq!(normal, basis.mul_vec3(DVec3::X));
-        assert_eq!(DVec3::X, basis.mul_vec3(DVec3::Y));
-        assert_eq!(-DVec3::Z, basis.mul_vec3(DVec3::Z));
-
-        let normal = DVec3::X;
-        let basis = calc_contact_basis(normal);
-
-        // the normal is close to x-axis, so the new y-axis is the former y-axis.
-        assert_eq!(normal, basis.mul_vec3(DVec3::X));
-        assert_eq!(DVec3::Y, basis.mul_vec3(DVec3::Y));
-        assert_eq!(DVec3::Z, basis.mul_vec3(DVec3::Z));
+/// Returns the combined PhysicsMaterial of the bodies (and boundary) involved in the given
+/// contact. Any body without a PhysicsMaterial uses the given default.
+fn calc_contact_material(
+    default_material: &PhysicsMaterial,
+    materials: &Query<&PhysicsMaterial>,
+    contact: &Contact,
+) -> PhysicsMaterial {
+    let mut result: Option<PhysicsMaterial> = None;
+
+    for entity in contact.entities.iter().chain(contact.boundary.iter()) {
+        let material = materials.get(*entity).unwrap_or(default_material);
+
+        result = match result {
+            Some(combined) => Some(combined.combine(material)),
+            None => Some(*material),
+        };
     }
 
-    #[test]
-    fn test_calc_contact_velocity() {
-        let normal = DVec3::Z;
-        let basis = calc_contact_basis(normal).transpose();
-        let contact_point = DVec3::new(2.0, 3.0, 2.0);
-        let position = DVec3::new(2.0, 2.0, 2.0);
-        let relative_position = contact_point - position;
-        let ang_vel = DVec3::ZERO;
-        let vel = DVec3::new(0.0, 0.0, 5.0);
-
-        let result = calc_contact_velocity(
-            basis,
-            relative_position,
-            ang_vel,
-            vel,
-        );
-
-        assert_eq!(DVec3::new(5.0, 0.0, 0.0), result);
-    }
+    result.unwrap_or(*default_material)
 }
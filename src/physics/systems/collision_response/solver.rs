@@ -0,0 +1,891 @@
+use bevy::{
+    prelude::*,
+    math::{
+        DMat3,
+        DQuat,
+        DVec3,
+    },
+};
+
+use crate::physics::{
+    components::{
+        Joint,
+        PhysicsMaterial,
+    },
+    systems::collision_response::joint::SolverJoint,
+};
+
+/// The state of a body involved in one or more contacts or joints. The state is copied out of the
+/// ECS so that the solver can repeatedly update it before the result is written back.
+#[derive(Debug, Clone, Copy)]
+pub struct SolverBody {
+    pub inverse_mass: f64,
+    /// The inverse inertia tensor in global coords.
+    pub inverse_inertia_tensor: DMat3,
+    pub velocity: DVec3,
+    pub angular_velocity: DVec3,
+    pub translation: DVec3,
+    pub rotation: DQuat,
+}
+
+impl SolverBody {
+    /// Applies the given impulse, in global coords, at the given position relative to the centre
+    /// of the body.
+    pub(super) fn apply_impulse(&mut self, impulse: DVec3, relative_point: DVec3) {
+        self.velocity += self.inverse_mass * impulse;
+        self.angular_velocity += self.inverse_inertia_tensor.mul_vec3(relative_point.cross(impulse));
+    }
+
+    /// Moves the body by the given linear change and rotates it by the given (small) angular
+    /// change, both in global coords.
+    pub(super) fn apply_move(&mut self, linear_change: DVec3, angular_change: DVec3) {
+        self.translation += linear_change;
+
+        let angular_change = DQuat::from_xyzw(angular_change.x, angular_change.y, angular_change.z,
+                                              0.0);
+        self.rotation = (self.rotation + angular_change * self.rotation * 0.5).normalize();
+    }
+}
+
+/// A contact between one or two bodies held by the solver, along with the values derived from it
+/// that remain constant while the solver runs.
+#[derive(Debug, Clone)]
+struct SolverContact {
+    /// Indices of the bodies involved. The second is None for contacts with a boundary.
+    bodies: [Option<usize>; 2],
+    normal: DVec3,
+    penetration: f64,
+    relative_points: [DVec3; 2],
+    material: PhysicsMaterial,
+    /// Transforms contact coords into global coords.
+    contact_to_global_transform: DMat3,
+    /// The combined change in closing velocity per unit impulse in contact coords.
+    velocity_per_unit_impulse: DMat3,
+    /// The current closing velocity in contact coords.
+    closing_velocity: DVec3,
+    /// The closing velocity along the normal that the solver aims for, based on restitution.
+    target_normal_velocity: f64,
+    /// The total impulse applied to the first body so far, in contact coords.
+    accumulated_impulse: DVec3,
+    /// The movement of each body per unit move, along the contact normal, from translation.
+    linear_inertia: [f64; 2],
+    /// The movement of each body's contact point per unit move, along the contact normal, from
+    /// rotation.
+    angular_inertia: [f64; 2],
+}
+
+impl SolverContact {
+    /// Returns how far the contact's normal velocity is from its target, when the error can be
+    /// corrected. A contact can always be pushed apart, but can only be pulled together by
+    /// reducing the impulse already applied. Contacts between immovable bodies cannot be corrected.
+    fn velocity_error(&self) -> f64 {
+        if self.is_immovable() {
+            return 0.0;
+        }
+
+        let error = self.closing_velocity.x - self.target_normal_velocity;
+
+        if error > 0.0 || self.accumulated_impulse.x < 0.0 {
+            error.abs()
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns true if no impulse can change the contact's closing velocity, i.e. both bodies are
+    /// immovable.
+    fn is_immovable(&self) -> bool {
+        self.velocity_per_unit_impulse.determinant().abs() < f64::EPSILON
+    }
+}
+
+/// Resolves a set of contacts iteratively, resolving the most severe contact at each step and then
+/// updating the state of any other contacts that share a body with it.
+///
+/// Velocities are resolved before positions. Contacts may be given the impulse applied in the
+/// previous step, which is applied up front to warm start the solver.
+///
+/// Joints between bodies are also held by the solver, and are resolved by sweeping over each of
+/// them in turn a fixed number of times.
+#[derive(Debug, Default)]
+pub struct ContactSolver {
+    bodies: Vec<SolverBody>,
+    contacts: Vec<SolverContact>,
+    joints: Vec<SolverJoint>,
+    /// The indices of the contacts that each body is involved in.
+    body_contacts: Vec<Vec<usize>>,
+    angular_limit: f64,
+    low_rotation_threshold: f64,
+}
+
+impl ContactSolver {
+    /// Creates a new, empty, ContactSolver. The angular limit restricts the rotation used to
+    /// resolve interpenetration as a proportion of the distance between the contact point and the
+    /// centre of the body. Angular inertia below the low rotation threshold is ignored.
+    pub fn new(angular_limit: f64, low_rotation_threshold: f64) -> Self {
+        Self {
+            angular_limit,
+            low_rotation_threshold,
+            ..Default::default()
+        }
+    }
+
+    /// Adds a body to the solver, returning its index.
+    pub fn add_body(&mut self, body: SolverBody) -> usize {
+        self.bodies.push(body);
+        self.body_contacts.push(vec![]);
+
+        self.bodies.len() - 1
+    }
+
+    /// Returns the bodies held by the solver, in the order they were added.
+    pub fn bodies(&self) -> &[SolverBody] {
+        &self.bodies
+    }
+
+    /// Returns the number of contacts held by the solver.
+    pub fn contact_count(&self) -> usize {
+        self.contacts.len()
+    }
+
+    /// Adds a contact between the bodies with the given indices, returning its index. The second
+    /// body is None for contacts with an immovable boundary.
+    ///
+    /// The normal points from the first body towards the second and the relative points give the
+    /// contact point relative to the centre of each body. The warm start impulse is applied to
+    /// the first body, in global coords, before the solver iterates.
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_contact(
+        &mut self,
+        bodies: [Option<usize>; 2],
+        normal: DVec3,
+        penetration: f64,
+        relative_points: [DVec3; 2],
+        material: PhysicsMaterial,
+        warm_start_impulse: DVec3,
+    ) -> usize {
+        let contact_to_global_transform = calc_contact_basis(normal);
+        let global_to_contact_transform = contact_to_global_transform.transpose();
+
+        let mut velocity_per_unit_impulse = DMat3::ZERO;
+        let mut linear_inertia = [0.0; 2];
+        let mut angular_inertia = [0.0; 2];
+
+        for (i, body) in bodies.iter().enumerate() {
+            let body = match body {
+                Some(index) => &self.bodies[*index],
+                None => continue,
+            };
+
+            velocity_per_unit_impulse = velocity_per_unit_impulse + calc_velocity_per_unit_impulse(
+                body.inverse_mass,
+                body.inverse_inertia_tensor,
+                relative_points[i],
+            );
+
+            // Calculate the inertia in the direction of the contact normal.
+            linear_inertia[i] = body.inverse_mass;
+            angular_inertia[i] = body.inverse_inertia_tensor
+                .mul_vec3(relative_points[i].cross(normal))
+                .cross(relative_points[i])
+                .dot(normal);
+        }
+
+        // Only push the bodies apart when warm starting.
+        let mut accumulated_impulse = global_to_contact_transform.mul_vec3(warm_start_impulse);
+        if accumulated_impulse.x >= 0.0 {
+            accumulated_impulse = DVec3::ZERO;
+        }
+
+        let index = self.contacts.len();
+
+        self.contacts.push(SolverContact {
+            bodies,
+            normal,
+            penetration,
+            relative_points,
+            material,
+            contact_to_global_transform,
+            velocity_per_unit_impulse: global_to_contact_transform
+                * velocity_per_unit_impulse
+                * contact_to_global_transform,
+            closing_velocity: DVec3::ZERO,
+            target_normal_velocity: 0.0,
+            accumulated_impulse,
+            linear_inertia,
+            angular_inertia,
+        });
+
+        for body in bodies.iter().flatten() {
+            self.body_contacts[*body].push(index);
+        }
+
+        index
+    }
+
+    /// Adds the given joint between the bodies with the given indices, returning the reference
+    /// direction it uses in each body's local coords. The step is the duration of the physics
+    /// step, used to limit the impulse applied by a motor.
+    pub fn add_joint(&mut self, joint: &Joint, bodies: [usize; 2], step: f64) -> [DVec3; 2] {
+        let solver_joint = SolverJoint::new(joint, bodies, &self.bodies, step);
+        let local_references = solver_joint.local_references();
+
+        self.joints.push(solver_joint);
+        local_references
+    }
+
+    /// Returns the current angle of the hinge with the given index, in the order the joints were
+    /// added.
+    pub fn joint_angle(&self, index: usize) -> f64 {
+        self.joints[index].angle(&self.bodies)
+    }
+
+    /// Returns the total impulse applied to the first body of the contact with the given index, in
+    /// global coords.
+    pub fn impulse(&self, index: usize) -> DVec3 {
+        let contact = &self.contacts[index];
+
+        contact.contact_to_global_transform.mul_vec3(contact.accumulated_impulse)
+    }
+
+    /// Iteratively applies impulses at the contacts until every contact's closing velocity is
+    /// within the given epsilon of its target, or the maximum number of iterations is reached.
+    ///
+    /// The target closing velocity is the reverse of the closing velocity prior to solving, scaled
+    /// by the coefficient of restitution. Friction opposes any tangential velocity.
+    ///
+    /// Every contact that can be corrected is resolved once before the worst is sought, so that
+    /// friction is applied even where the normal velocity is already within the epsilon, e.g. once
+    /// warm started. These count towards the maximum number of iterations.
+    pub fn solve_velocities(&mut self, max_iterations: usize, epsilon: f64) {
+        // Targets are based on the velocities before any impulse is applied.
+        for index in 0..self.contacts.len() {
+            let closing_velocity = self.calc_closing_velocity(index);
+            let contact = &mut self.contacts[index];
+
+            contact.target_normal_velocity = if closing_velocity.x > 0.0 {
+                -contact.material.restitution * closing_velocity.x
+            } else {
+                0.0
+            };
+        }
+
+        // Warm start.
+        for index in 0..self.contacts.len() {
+            let impulse = self.impulse(index);
+            self.apply_impulse(index, impulse);
+        }
+
+        for index in 0..self.contacts.len() {
+            self.contacts[index].closing_velocity = self.calc_closing_velocity(index);
+        }
+
+        let mut iterations = 0;
+        for index in 0..self.contacts.len() {
+            if iterations >= max_iterations { return; }
+            if self.contacts[index].is_immovable() { continue; }
+
+            self.resolve_velocity(index);
+            iterations += 1;
+        }
+
+        for _ in iterations..max_iterations {
+            // Find the contact furthest from its target velocity.
+            let (index, error) = match self.contacts.iter()
+                .map(|c| c.velocity_error())
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+            {
+                Some(worst) => worst,
+                None => return,
+            };
+
+            if error < epsilon {
+                return;
+            }
+
+            self.resolve_velocity(index);
+        }
+    }
+
+    /// Removes the relative velocity of the bodies in each joint's constrained degrees of freedom,
+    /// sweeping over the joints the given number of times. Limits can only push bodies back within
+    /// range and motors are limited to their maximum torque.
+    ///
+    /// Intended to be called before solve_velocities, which calculates the closing velocities of
+    /// the contacts afresh.
+    pub fn solve_joint_velocities(&mut self, iterations: usize) {
+        for joint in self.joints.iter_mut() {
+            joint.prepare(&self.bodies);
+        }
+
+        for _ in 0..iterations {
+            for joint in self.joints.iter_mut() {
+                joint.solve_velocity(&mut self.bodies);
+            }
+        }
+    }
+
+    /// Moves the bodies to remove the error in each joint's constrained degrees of freedom,
+    /// sweeping over the joints the given number of times. The penetrations of any contacts
+    /// involving the bodies are updated accordingly.
+    pub fn solve_joint_positions(&mut self, iterations: usize) {
+        for _ in 0..iterations {
+            for index in 0..self.joints.len() {
+                let changes = self.joints[index].solve_position(&mut self.bodies);
+                let [a, b] = self.joints[index].bodies;
+
+                self.update_penetrations([Some(a), Some(b)], &changes);
+            }
+        }
+    }
+
+    /// Iteratively moves the bodies at the contacts until no contact's penetration exceeds the
+    /// given epsilon, or the maximum number of iterations is reached.
+    pub fn solve_positions(&mut self, max_iterations: usize, epsilon: f64) {
+        for _ in 0..max_iterations {
+            // Find the deepest contact.
+            let (index, penetration) = match self.contacts.iter()
+                .map(|c| c.penetration)
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+            {
+                Some(worst) => worst,
+                None => return,
+            };
+
+            if penetration < epsilon {
+                return;
+            }
+
+            self.resolve_penetration(index);
+        }
+    }
+
+    /// Returns the closing velocity of the contact with the given index, in contact coords.
+    fn calc_closing_velocity(&self, index: usize) -> DVec3 {
+        let contact = &self.contacts[index];
+        let global_to_contact_transform = contact.contact_to_global_transform.transpose();
+
+        let mut closing_velocity = DVec3::ZERO;
+
+        for (i, sign) in [1.0, -1.0].iter().enumerate() {
+            if let Some(body) = contact.bodies[i] {
+                closing_velocity += *sign * calc_contact_velocity(
+                    global_to_contact_transform,
+                    contact.relative_points[i],
+                    self.bodies[body].angular_velocity,
+                    self.bodies[body].velocity,
+                );
+            }
+        }
+
+        closing_velocity
+    }
+
+    /// Applies the given impulse, in global coords, to the first body of the contact with the
+    /// given index, and the reverse to the second.
+    fn apply_impulse(&mut self, index: usize, impulse: DVec3) {
+        let contact = &self.contacts[index];
+
+        for (i, sign) in [1.0, -1.0].iter().enumerate() {
+            if let Some(body) = contact.bodies[i] {
+                self.bodies[body].apply_impulse(*sign * impulse, contact.relative_points[i]);
+            }
+        }
+    }
+
+    /// Applies the impulse required to bring the contact with the given index to its target
+    /// velocity, then updates the closing velocities of all contacts sharing a body with it.
+    fn resolve_velocity(&mut self, index: usize) {
+        let contact = &self.contacts[index];
+
+        let desired_delta_velocity = DVec3::new(
+            contact.target_normal_velocity - contact.closing_velocity.x,
+            -contact.closing_velocity.y,
+            -contact.closing_velocity.z,
+        );
+
+        let accumulated_impulse = calc_accumulated_impulse(
+            contact.velocity_per_unit_impulse,
+            desired_delta_velocity,
+            contact.accumulated_impulse,
+            &contact.material,
+        );
+
+        let impulse = contact.contact_to_global_transform
+            .mul_vec3(accumulated_impulse - contact.accumulated_impulse);
+        debug!("impulse in global coords {}", impulse);
+
+        self.contacts[index].accumulated_impulse = accumulated_impulse;
+        self.apply_impulse(index, impulse);
+
+        for related in self.related_contacts(index) {
+            self.contacts[related].closing_velocity = self.calc_closing_velocity(related);
+        }
+    }
+
+    /// Moves the bodies of the contact with the given index to remove its interpenetration, using
+    /// a combination of translation and rotation in proportion to the inertia of the bodies. The
+    /// penetrations of all contacts sharing a body with it are then updated.
+    fn resolve_penetration(&mut self, index: usize) {
+        let contact = &self.contacts[index];
+
+        let total_inertia: f64 = contact.linear_inertia.iter().sum::<f64>()
+            + contact.angular_inertia.iter().sum::<f64>();
+
+        // Neither body can move.
+        if total_inertia <= 0.0 {
+            self.contacts[index].penetration = 0.0;
+            return;
+        }
+
+        let mut changes = [(DVec3::ZERO, DVec3::ZERO); 2];
+
+        for (i, sign) in [1.0, -1.0].iter().enumerate() {
+            let body = match contact.bodies[i] {
+                Some(body) => &self.bodies[body],
+                None => continue,
+            };
+
+            let mut linear_move = sign * contact.penetration * contact.linear_inertia[i]
+                / total_inertia;
+            let mut angular_move = sign * contact.penetration * contact.angular_inertia[i]
+                / total_inertia;
+
+            // limit angular move to mitigate over-rotation issues.
+            let limit = self.angular_limit * contact.relative_points[i].length();
+
+            if angular_move > limit {
+                linear_move += angular_move - limit;
+                angular_move = limit;
+            } else if angular_move < -limit {
+                linear_move += angular_move + limit;
+                angular_move = -limit;
+            }
+
+            // The rotation per unit impulsive torque moves the contact point along the normal by
+            // the angular inertia, so scale it to move the point back along the normal by the
+            // angular move.
+            let angular_change = if contact.angular_inertia[i].abs() >= self.low_rotation_threshold {
+                body.inverse_inertia_tensor
+                    .mul_vec3(contact.relative_points[i].cross(contact.normal))
+                    * (-angular_move / contact.angular_inertia[i])
+            } else {
+                linear_move += angular_move;
+                DVec3::ZERO
+            };
+
+            changes[i] = (linear_move * -contact.normal, angular_change);
+        }
+        debug!("linear and angular changes = {:?}", changes);
+
+        let bodies = contact.bodies;
+
+        for (i, (linear_change, angular_change)) in changes.iter().enumerate() {
+            if let Some(body) = bodies[i] {
+                self.bodies[body].apply_move(*linear_change, *angular_change);
+            }
+        }
+
+        self.update_penetrations(bodies, &changes);
+    }
+
+    /// Updates the penetration of every contact involving the given bodies, given the linear and
+    /// angular change of each.
+    fn update_penetrations(&mut self, bodies: [Option<usize>; 2], changes: &[(DVec3, DVec3); 2]) {
+        for related in self.contacts_of(bodies) {
+            let contact = &mut self.contacts[related];
+
+            for (i, sign) in [1.0, -1.0].iter().enumerate() {
+                for (j, (linear_change, angular_change)) in changes.iter().enumerate() {
+                    if contact.bodies[i].is_some() && contact.bodies[i] == bodies[j] {
+                        let delta = *linear_change
+                            + angular_change.cross(contact.relative_points[i]);
+
+                        contact.penetration += sign * delta.dot(contact.normal);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the indices of the contacts that share a body with the contact with the given index,
+    /// including itself.
+    fn related_contacts(&self, index: usize) -> Vec<usize> {
+        self.contacts_of(self.contacts[index].bodies)
+    }
+
+    /// Returns the indices of the contacts involving either of the given bodies.
+    fn contacts_of(&self, bodies: [Option<usize>; 2]) -> Vec<usize> {
+        let mut result: Vec<usize> = bodies.iter()
+            .flatten()
+            .flat_map(|body| self.body_contacts[*body].iter().copied())
+            .collect();
+
+        result.sort_unstable();
+        result.dedup();
+
+        result
+    }
+}
+
+// --- Helper methods
+
+/// Takes the contact normal in global space and returns an arbitrary orthonormal basis for the
+/// contact as a DMat3. The matrix represents transformation from contact space into global space.
+/// Note, the origin of contact space is the global origin to simplify calculations (the transform
+/// is a rotation only, so its inverse is the transpose).
+pub(super) fn calc_contact_basis(normal: DVec3) -> DMat3 {
+    // The normal will be the new x-axis.
+    // Initially, choose the y-axis to be either the global x-axis or global y-axis, whichever is
+    // further from the normal to avoid the parallel case.
+    let mut y = if normal.dot(DVec3::X).abs() - normal.dot(DVec3::Y).abs() > 0.000001 {
+        // normal nearer to global x-axis
+        DVec3::Y
+    } else {
+        // normal nearer to global y-axis
+        DVec3::X
+    };
+
+    // z is the normalised vector at right angles to the normal and the chosen y-axis.
+    let z = normal.cross(y).normalize();
+
+    // Then y must be the vector at right angles to this z axis and the normal.
+    y = z.cross(normal);
+
+    DMat3::from_cols(
+        normal,
+        y,
+        z,
+    )
+}
+
+/// Returns the skew-symmetric matrix equivalent to taking the cross product with the given vector.
+/// i.e. calc_skew_symmetric(a) * b = a x b.
+fn calc_skew_symmetric(v: DVec3) -> DMat3 {
+    DMat3::from_cols(
+        DVec3::new(0.0, v.z, -v.y),
+        DVec3::new(-v.z, 0.0, v.x),
+        DVec3::new(v.y, -v.x, 0.0),
+    )
+}
+
+/// Calculates the change in velocity of a body's contact point per unit of impulse applied at
+/// that point, in global coords, and returns it as a DMat3.
+pub(super) fn calc_velocity_per_unit_impulse(
+    inverse_mass: f64,
+    inverse_inertia_tensor: DMat3,
+    relative_contact_position: DVec3,
+) -> DMat3 {
+    // Linear velocity change per unit impulse
+    // v_lin = inverse_mass * J
+    let linear_component = DMat3::from_diagonal(DVec3::splat(inverse_mass));
+
+    // Angular velocity change per unit impulse
+    // delta ang vel = inverse inertia tensor * impulsive torque
+    // where; impulsive torque = rel position x J
+    // The velocity of the contact point due to this rotation is then;
+    // delta ang vel x rel position = -(rel position x (I^-1 * (rel position x J)))
+    let impulse_to_torque = calc_skew_symmetric(relative_contact_position);
+    let angular_component = impulse_to_torque * inverse_inertia_tensor * impulse_to_torque;
+
+    // Combine the angular and linear parts to get the result.
+    linear_component - angular_component
+}
+
+/// Calculates the total impulse, in contact coords, that must be applied to the first body in a
+/// contact (and reversed for the second) to achieve the desired change in closing velocity, given
+/// the impulse already applied. The combined change in velocity per unit impulse of the bodies
+/// must also be given in contact coords.
+///
+/// The total normal impulse may only push the bodies apart. The tangential (friction) impulse
+/// attempts to stop the bodies sliding, limited by the static friction. If the limit is exceeded
+/// the contact slides and dynamic friction is applied instead.
+fn calc_accumulated_impulse(
+    velocity_per_unit_impulse: DMat3,
+    desired_delta_velocity: DVec3,
+    accumulated_impulse: DVec3,
+    material: &PhysicsMaterial,
+) -> DVec3 {
+    // Both bodies are immovable.
+    if velocity_per_unit_impulse.determinant().abs() < f64::EPSILON {
+        return accumulated_impulse;
+    }
+
+    let mut impulse = accumulated_impulse
+        + velocity_per_unit_impulse.inverse().mul_vec3(desired_delta_velocity);
+
+    // The normal impulse is < 0, i.e. it acts in the opposite direction to the contact normal.
+    // The bodies would have to be pulled together to achieve the desired velocity.
+    if impulse.x >= 0.0 {
+        return DVec3::ZERO;
+    }
+
+    let planar_impulse = (impulse.y.powi(2) + impulse.z.powi(2)).sqrt();
+
+    if planar_impulse > material.static_friction * -impulse.x {
+        // Sliding. The friction impulse opposes the sliding direction with a magnitude
+        // proportional to the normal impulse, i.e. impulse = normal impulse * (x - friction * t),
+        // so the normal impulse must be recalculated to achieve the desired normal velocity,
+        // accounting for the impulse already applied.
+        let tangent = DVec3::new(0.0, impulse.y, impulse.z) / planar_impulse;
+        let direction = DVec3::X - material.dynamic_friction * tangent;
+
+        let normal_velocity_per_unit_impulse = velocity_per_unit_impulse.transpose().x_axis;
+
+        let normal_impulse = (desired_delta_velocity.x
+                              + normal_velocity_per_unit_impulse.dot(accumulated_impulse))
+            / normal_velocity_per_unit_impulse.dot(direction);
+
+        if normal_impulse >= 0.0 {
+            return DVec3::ZERO;
+        }
+
+        impulse = direction * normal_impulse;
+    }
+
+    impulse
+}
+
+/// Returns the velocity of the contact point in contact coords as a DVec3.
+fn calc_contact_velocity(
+    global_to_contact_transform: DMat3,
+    relative_contact_position: DVec3,
+    angular_velocity: DVec3,
+    velocity: DVec3,
+) -> DVec3 {
+    // velocity = linear velocity + (angular velocity x relative position).
+
+    // first calculate velocity in global coords.
+    let mut result = velocity + angular_velocity.cross(relative_contact_position);
+
+    debug!("contact velocity in global coords = {}", result);
+
+    // convert to contact coords
+    result = global_to_contact_transform.mul_vec3(result);
+
+    debug!("contact velocity in contact coords = {}", result);
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn point_mass(translation: DVec3, velocity: DVec3) -> SolverBody {
+        SolverBody {
+            inverse_mass: 1.0,
+            inverse_inertia_tensor: DMat3::ZERO,
+            velocity,
+            angular_velocity: DVec3::ZERO,
+            translation,
+            rotation: DQuat::IDENTITY,
+        }
+    }
+
+    #[test]
+    fn test_calc_contact_basis() {
+        let normal = DVec3::Y;
+        let basis = calc_contact_basis(normal);
+
+        // the normal is close to y-axis, so the new y-axis is the former x-axis.
+        assert_eq!(normal, basis.mul_vec3(DVec3::X));
+        assert_eq!(DVec3::X, basis.mul_vec3(DVec3::Y));
+        assert_eq!(-DVec3::Z, basis.mul_vec3(DVec3::Z));
+
+        let normal = DVec3::X;
+        let basis = calc_contact_basis(normal);
+
+        // the normal is close to x-axis, so the new y-axis is the former y-axis.
+        assert_eq!(normal, basis.mul_vec3(DVec3::X));
+        assert_eq!(DVec3::Y, basis.mul_vec3(DVec3::Y));
+        assert_eq!(DVec3::Z, basis.mul_vec3(DVec3::Z));
+    }
+
+    #[test]
+    fn test_calc_velocity_per_unit_impulse() {
+        let inverse_mass = 0.5;
+        let inverse_inertia_tensor = DMat3::from_diagonal(DVec3::new(0.2, 0.3, 0.4));
+        let relative_position = DVec3::new(1.0, -2.0, 0.5);
+        let impulse = DVec3::new(0.3, 1.0, -0.7);
+
+        // compare with the velocity change calculated directly from the impulse.
+        let expected = inverse_mass * impulse
+            + inverse_inertia_tensor.mul_vec3(relative_position.cross(impulse))
+                .cross(relative_position);
+        let result = calc_velocity_per_unit_impulse(
+            inverse_mass,
+            inverse_inertia_tensor,
+            relative_position,
+        ).mul_vec3(impulse);
+
+        assert!((expected - result).length() < 0.000001);
+    }
+
+    #[test]
+    fn test_calc_accumulated_impulse() {
+        // point masses so that the change in velocity per unit impulse is the identity.
+        let velocity_per_unit_impulse = DMat3::IDENTITY;
+        let material = PhysicsMaterial::new(0.0, 0.5, 0.3);
+
+        // separating.
+        let desired_delta_velocity = DVec3::new(1.0, -2.0, 0.0);
+        let impulse = calc_accumulated_impulse(velocity_per_unit_impulse, desired_delta_velocity,
+                                               DVec3::ZERO, &material);
+        assert_eq!(DVec3::ZERO, impulse);
+
+        // within the static friction limit, all tangential velocity is removed.
+        let desired_delta_velocity = DVec3::new(-1.0, -0.1, 0.0);
+        let impulse = calc_accumulated_impulse(velocity_per_unit_impulse, desired_delta_velocity,
+                                               DVec3::ZERO, &material);
+        assert!((impulse - DVec3::new(-1.0, -0.1, 0.0)).length() < 0.000001);
+
+        // sliding, the friction impulse is limited by the dynamic friction.
+        let desired_delta_velocity = DVec3::new(-1.0, 0.0, -2.0);
+        let impulse = calc_accumulated_impulse(velocity_per_unit_impulse, desired_delta_velocity,
+                                               DVec3::ZERO, &material);
+        assert!((impulse - DVec3::new(-1.0, 0.0, -0.3)).length() < 0.000001);
+
+        // frictionless and elastic.
+        let material = PhysicsMaterial::new(1.0, 0.0, 0.0);
+        let desired_delta_velocity = DVec3::new(-2.0, 0.0, -2.0);
+        let impulse = calc_accumulated_impulse(velocity_per_unit_impulse, desired_delta_velocity,
+                                               DVec3::ZERO, &material);
+        assert!((impulse - DVec3::new(-2.0, 0.0, 0.0)).length() < 0.000001);
+
+        // an impulse already applied can be partially removed, but never reversed.
+        let accumulated_impulse = DVec3::new(-1.0, 0.0, 0.0);
+        let impulse = calc_accumulated_impulse(velocity_per_unit_impulse, DVec3::new(0.5, 0.0, 0.0),
+                                               accumulated_impulse, &material);
+        assert!((impulse - DVec3::new(-0.5, 0.0, 0.0)).length() < 0.000001);
+        let impulse = calc_accumulated_impulse(velocity_per_unit_impulse, DVec3::new(2.0, 0.0, 0.0),
+                                               accumulated_impulse, &material);
+        assert_eq!(DVec3::ZERO, impulse);
+    }
+
+    #[test]
+    fn test_calc_contact_velocity() {
+        let normal = DVec3::Z;
+        let basis = calc_contact_basis(normal).transpose();
+        let contact_point = DVec3::new(2.0, 3.0, 2.0);
+        let position = DVec3::new(2.0, 2.0, 2.0);
+        let relative_position = contact_point - position;
+        let ang_vel = DVec3::ZERO;
+        let vel = DVec3::new(0.0, 0.0, 5.0);
+
+        let result = calc_contact_velocity(
+            basis,
+            relative_position,
+            ang_vel,
+            vel,
+        );
+
+        assert_eq!(DVec3::new(5.0, 0.0, 0.0), result);
+    }
+
+    #[test]
+    fn test_solve_stack() {
+        // Two point masses stacked on a boundary, both falling. The upper contact is resolved
+        // first, but the lower contact must be revisited once the bodies' velocities change.
+        let material = PhysicsMaterial::new(0.0, 0.0, 0.0);
+        let mut solver = ContactSolver::new(0.2, 0.0001);
+
+        let lower = solver.add_body(point_mass(DVec3::new(0.0, 1.0, 0.0), DVec3::new(0.0, -1.0, 0.0)));
+        let upper = solver.add_body(point_mass(DVec3::new(0.0, 3.0, 0.0), DVec3::new(0.0, -3.0, 0.0)));
+
+        // upper body pushing down on the lower, normal from upper to lower.
+        solver.add_contact([Some(upper), Some(lower)], -DVec3::Y, 0.2,
+                           [-DVec3::Y, DVec3::Y], material, DVec3::ZERO);
+        // lower body on the boundary, normal from the body into the boundary.
+        solver.add_contact([Some(lower), None], -DVec3::Y, 0.1,
+                           [-DVec3::Y, DVec3::ZERO], material, DVec3::ZERO);
+
+        solver.solve_velocities(32, 0.0001);
+
+        // all closing velocity removed, both bodies brought to rest.
+        for body in solver.bodies() {
+            assert!(body.velocity.length() < 0.001);
+        }
+
+        solver.solve_positions(32, 0.0001);
+
+        // the lower body is pushed out of the boundary, the upper body is pushed out of the lower
+        // body, including the displacement of the lower body.
+        let bodies = solver.bodies();
+        assert!((bodies[lower].translation.y - 1.1).abs() < 0.001);
+        assert!((bodies[upper].translation.y - 3.3).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_warm_start() {
+        // A resting contact warm started with the full impulse needed requires no iterations.
+        let material = PhysicsMaterial::new(0.0, 0.0, 0.0);
+        let mut solver = ContactSolver::new(0.2, 0.0001);
+
+        let body = solver.add_body(point_mass(DVec3::ZERO, DVec3::new(0.0, -1.0, 0.0)));
+        solver.add_contact([Some(body), None], -DVec3::Y, 0.0, [-DVec3::Y, DVec3::ZERO],
+                           material, DVec3::new(0.0, 1.0, 0.0));
+
+        solver.solve_velocities(0, 0.0001);
+        assert!(solver.bodies()[body].velocity.length() < 0.000001);
+        assert!((solver.impulse(0) - DVec3::new(0.0, 1.0, 0.0)).length() < 0.000001);
+
+        // An excessive warm start impulse is reduced rather than separating the bodies.
+        let mut solver = ContactSolver::new(0.2, 0.0001);
+
+        let body = solver.add_body(point_mass(DVec3::ZERO, DVec3::new(0.0, -1.0, 0.0)));
+        solver.add_contact([Some(body), None], -DVec3::Y, 0.0, [-DVec3::Y, DVec3::ZERO],
+                           material, DVec3::new(0.0, 3.0, 0.0));
+
+        solver.solve_velocities(1, 0.0001);
+        assert!(solver.bodies()[body].velocity.length() < 0.000001);
+        assert!((solver.impulse(0) - DVec3::new(0.0, 1.0, 0.0)).length() < 0.000001);
+    }
+
+    #[test]
+    fn test_sliding() {
+        // A point mass sliding along a boundary, whose fall in the step is almost entirely
+        // cancelled by the warm start, leaving a normal velocity within the epsilon.
+        let material = PhysicsMaterial::new(0.0, 0.5, 0.3);
+        let mut solver = ContactSolver::new(0.2, 0.0001);
+
+        let body = solver.add_body(point_mass(DVec3::ZERO, DVec3::new(5.0, -1.0, 0.0)));
+        solver.add_contact([Some(body), None], -DVec3::Y, 0.0, [-DVec3::Y, DVec3::ZERO],
+                           material, DVec3::new(0.0, 0.995, 0.0));
+
+        solver.solve_velocities(4, 0.01);
+
+        // friction still slows the body, in proportion to the normal impulse.
+        let velocity = solver.bodies()[body].velocity;
+        assert!((velocity - DVec3::new(4.7, 0.0, 0.0)).length() < 0.000001);
+    }
+
+    #[test]
+    fn test_kinematic() {
+        // A point mass on a kinematic platform moving upwards faster than it, which is also touching a
+        // static boundary. The platform carries the body with it, without being pushed back.
+        let material = PhysicsMaterial::new(0.0, 0.0, 0.0);
+        let mut solver = ContactSolver::new(0.2, 0.0001);
+
+        let platform = solver.add_body(SolverBody {
+            inverse_mass: 0.0,
+            ..point_mass(DVec3::ZERO, DVec3::new(0.0, 2.0, 0.0))
+        });
+        let body = solver.add_body(point_mass(DVec3::new(0.0, 1.0, 0.0), DVec3::new(0.0, 1.0, 0.0)));
+
+        // the platform and boundary can never be separated.
+        solver.add_contact([Some(platform), None], DVec3::Y, 0.1, [DVec3::Y, DVec3::ZERO],
+                           material, DVec3::ZERO);
+        solver.add_contact([Some(body), Some(platform)], -DVec3::Y, 0.0, [-DVec3::Y, DVec3::Y],
+                           material, DVec3::ZERO);
+
+        // the unresolvable contact does not use up the iterations.
+        solver.solve_velocities(2, 0.0001);
+
+        let bodies = solver.bodies();
+        assert!((bodies[body].velocity - DVec3::new(0.0, 2.0, 0.0)).length() < 0.000001);
+        assert_eq!(DVec3::new(0.0, 2.0, 0.0), bodies[platform].velocity);
+    }
+}
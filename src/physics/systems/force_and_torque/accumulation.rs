@@ -1,24 +1,57 @@
 use bevy::{
     prelude::*,
+    math::DVec3,
 };
 
-use crate::physics::components::{
-    Drag,
-    Force,
-    Gravity,
-    Mass,
-    PhysTransform,
-    Rotator,
-    Thrust,
-    Torque,
-    Velocity,
+use crate::{
+    physics::components::{
+        AngularVelocity,
+        Collider,
+        Drag,
+        ExternalForce,
+        ExternalImpulse,
+        FluidVolume,
+        Force,
+        ForceGenerator,
+        ForceGeneratorBody,
+        Gravity,
+        GravityScale,
+        GravityZone,
+        InertiaTensor,
+        Mass,
+        PhysTransform,
+        PointAttractor,
+        RigidBodyType,
+        Rotator,
+        Sleeping,
+        Spring,
+        SpringAnchor,
+        Thrust,
+        Torque,
+        Velocity,
+    },
+    physics::query::point_projection,
+    physics::shapes::{
+        Cuboid,
+        Sphere,
+    },
+    physics::resources::{
+        PhysicsConfig,
+        PhysicsTime,
+    },
+    physics::systems::force_and_torque,
 };
 
 /// Force and torque system labels.
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
 enum ForceAndTorqueSystems {
     Accumulator,
+    External,
+    Fluids,
+    Gravity,
+    Impulses,
     Reset,
+    Springs,
 }
 
 /// A SystemSet that resets and then recalculates the forces and torques applied on a body.
@@ -31,41 +64,268 @@ pub fn get_system_set() -> SystemSet {
                      .label(ForceAndTorqueSystems::Accumulator)
                      .after(ForceAndTorqueSystems::Reset)
         )
+        .with_system(gravity_accumulation.system()
+                     .label(ForceAndTorqueSystems::Gravity)
+                     .after(ForceAndTorqueSystems::Accumulator)
+        )
+        .with_system(fluid_accumulation.system()
+                     .label(ForceAndTorqueSystems::Fluids)
+                     .after(ForceAndTorqueSystems::Gravity)
+        )
+        .with_system(spring_accumulation.system()
+                     .label(ForceAndTorqueSystems::Springs)
+                     .after(ForceAndTorqueSystems::Fluids)
+        )
+        .with_system(external_force_accumulation.system()
+                     .label(ForceAndTorqueSystems::External)
+                     .after(ForceAndTorqueSystems::Springs)
+        )
+        .with_system(apply_external_impulses.system()
+                     .label(ForceAndTorqueSystems::Impulses)
+                     .after(ForceAndTorqueSystems::External)
+        )
+}
+
+/// A SystemSet that accumulates the forces and torques applied on a body by the user-defined
+/// ForceGenerator of the given type, once the accumulators have been reset.
+pub fn get_generator_system_set<T: ForceGenerator>() -> SystemSet {
+    SystemSet::new()
+        .with_system(generator_accumulation::<T>.system()
+                     .after(ForceAndTorqueSystems::Reset)
+        )
 }
 
 /// A system that calculates and accumulates various forces and associated torques applied on a
-/// body.
+/// body. Sleeping bodies are skipped.
+#[allow(clippy::type_complexity)]
 fn force_and_torque_accumulation(
     mut q: QuerySet<(
-        Query<(&mut Drag, &mut Force, &Velocity)>,
-        Query<(&Gravity, &mut Force, &Mass)>,
-        Query<(&Thrust, &mut Force)>,
-        Query<(&Rotator, &mut Force, &mut Torque, &PhysTransform)>,
+        Query<(&mut Drag, &mut Force, &Velocity), Without<Sleeping>>,
+        Query<(&Thrust, &mut Force), Without<Sleeping>>,
+        Query<(&Rotator, &mut Force, &mut Torque, &PhysTransform), Without<Sleeping>>,
     )>,
 ) {
     // Apply force generators.
     for (mut drag, mut f, v) in q.q0_mut().iter_mut() {
         drag.update_force(&mut f, v.vector());
     }
-    for (gravity, mut f, m) in q.q1_mut().iter_mut() {
-        // ensure the mass is not 0 or infinite (or subnormal/NaN).
-        if !m.is_normal() { break };
-        gravity.update_force(&mut f, m.value());
-    }
-    for (thrust, mut f) in q.q2_mut().iter_mut() {
+    for (thrust, mut f) in q.q1_mut().iter_mut() {
         thrust.update_force(&mut f);
     }
 
     // Apply force and torque generators.
-    for (rotator, mut f, mut torque, transform) in q.q3_mut().iter_mut() {
+    for (rotator, mut f, mut torque, transform) in q.q2_mut().iter_mut() {
         rotator.update_force_and_torque(&mut f, &mut torque, transform);
     }
 }
 
-/// A system that zeroes the Force and Torque accumulator components for all Entitys.
-fn reset_accumulators(mut query: Query<(&mut Force, &mut Torque)>) {
+/// A system that accumulates the force of gravity on each body with a Gravity component, including
+/// that of any GravityZones and PointAttractors, scaled by its GravityScale. Sleeping bodies are
+/// skipped.
+#[allow(clippy::type_complexity)]
+fn gravity_accumulation(
+    config: Res<PhysicsConfig>,
+    mut bodies: Query<(Entity, &Gravity, &mut Force, &Mass, &PhysTransform, Option<&GravityScale>),
+                      Without<Sleeping>>,
+    zones: Query<(&GravityZone, &Collider, &PhysTransform)>,
+    attractors: Query<(Entity, &PointAttractor, &PhysTransform)>,
+) {
+    for (entity, gravity, mut f, m, transform, scale) in bodies.iter_mut() {
+        // ensure the mass is not 0 or infinite (or subnormal/NaN).
+        if !m.is_normal() { continue };
+
+        let g = calc_gravity(entity, gravity, scale, transform.translation(), &config, &zones,
+                             &attractors);
+
+        force_and_torque::add_force(m.value() * g, &mut f);
+    }
+}
+
+/// A system that accumulates the buoyancy and drag applied on each Dynamic body with a Gravity
+/// component by every FluidVolume it reaches into, i.e. whose volume contains its centre, or whose
+/// surface its centre is no further above than its radius (or a Cuboid's half-diagonal). The
+/// buoyancy acts against the gravity on the body, including any GravityZones, PointAttractors and
+/// GravityScale. Sleeping bodies, and fluid volumes without a Cuboid Collider, are skipped.
+#[allow(clippy::type_complexity)]
+fn fluid_accumulation(
+    config: Res<PhysicsConfig>,
+    mut bodies: Query<(Entity, &Collider, &Mass, Option<&RigidBodyType>, &Gravity,
+                       Option<&GravityScale>, &PhysTransform, &Velocity, &AngularVelocity,
+                       &mut Force, &mut Torque), Without<Sleeping>>,
+    fluids: Query<(Entity, &FluidVolume, &Collider, &PhysTransform)>,
+    zones: Query<(&GravityZone, &Collider, &PhysTransform)>,
+    attractors: Query<(Entity, &PointAttractor, &PhysTransform)>,
+) {
+    for (fluid_entity, fluid, fluid_collider, fluid_transform) in fluids.iter() {
+        let cuboid = match fluid_collider.0.downcast_ref::<Cuboid>() {
+            Some(cuboid) => cuboid,
+            None => continue,
+        };
+
+        let up = fluid_transform.get_direction_in_global_space(DVec3::Y).normalize();
+        let surface = fluid_transform.get_point_in_global_space(DVec3::new(0.0, cuboid.extents().y, 0.0));
+
+        for (entity, collider, m, body_type, gravity, scale, transform, v, ang_v, mut f,
+             mut torque) in bodies.iter_mut()
+        {
+            if entity == fluid_entity || !RigidBodyType::of(body_type, m).is_dynamic() { continue; }
+
+            // the furthest the body's centre can be above the surface while touching it.
+            let primative = collider.0.as_ref();
+            let height = if let Some(sphere) = primative.downcast_ref::<Sphere>() {
+                sphere.radius()
+            } else if let Some(body_cuboid) = primative.downcast_ref::<Cuboid>() {
+                body_cuboid.extents().length()
+            } else {
+                continue;
+            };
+
+            if !fluid.contains(cuboid, fluid_transform, transform.translation(), height) { continue; }
+
+            let g = calc_gravity(entity, gravity, scale, transform.translation(), &config, &zones,
+                                 &attractors);
+
+            fluid.update_force_and_torque(&mut f, &mut torque, primative, transform, v.vector(),
+                                          ang_v.vector(), (surface, up), g);
+        }
+    }
+}
+
+/// A system that accumulates the forces and torques applied on a body by its Springs, which
+/// depend on the position and velocity of the Entity (if any) each is anchored to. Springs on
+/// sleeping bodies are also evaluated, and wake the body once their force has changed enough to
+/// disturb it, e.g. by the anchor being moved.
+#[allow(clippy::type_complexity)]
+fn spring_accumulation(
+    config: Res<PhysicsConfig>,
+    physics_time: Res<PhysicsTime>,
+    mut springs: Query<(&mut Spring, &mut Force, &mut Torque, &PhysTransform, &Velocity,
+                        &AngularVelocity, &Mass, Option<&Sleeping>)>,
+    anchors: Query<(&PhysTransform, Option<&Velocity>, Option<&AngularVelocity>)>,
+) {
+    for (mut spring, mut f, mut torque, transform, v, ang_v, m, sleeping) in springs.iter_mut() {
+        // the position and velocity of the spring's anchor.
+        let anchor = match spring.anchor() {
+            SpringAnchor::World(point) => (point, DVec3::ZERO),
+            SpringAnchor::Entity(entity, point) => match anchors.get(entity) {
+                Ok((anchor_transform, anchor_v, anchor_ang_v)) => {
+                    let point = anchor_transform.get_point_in_global_space(point);
+                    let relative_point = point - anchor_transform.translation();
+
+                    (point, anchor_v.map_or(DVec3::ZERO, |v| v.vector())
+                        + anchor_ang_v.map_or(DVec3::ZERO, |w| w.vector().cross(relative_point)))
+                },
+                Err(_) => continue,
+            },
+        };
+
+        if sleeping.is_some() {
+            spring.update_sleeping_force_and_torque(&mut f, &mut torque, transform, m, anchor,
+                                                    physics_time.step(),
+                                                    config.sleep_linear_threshold);
+        } else {
+            spring.update_force_and_torque(&mut f, &mut torque, transform, v.vector(),
+                                           ang_v.vector(), m, anchor, physics_time.step());
+        }
+    }
+}
+
+/// A system that accumulates the forces and torques applied on a body by its user-defined
+/// ForceGenerator of the given type. Generators on sleeping bodies are also updated, and any
+/// non-zero force or torque they add wakes the body.
+#[allow(clippy::type_complexity)]
+fn generator_accumulation<T: ForceGenerator>(
+    physics_time: Res<PhysicsTime>,
+    mut query: Query<(&mut T, &mut Force, &mut Torque, &Mass, &Velocity, &AngularVelocity,
+                      &PhysTransform, &InertiaTensor)>,
+) {
+    for (mut generator, mut f, mut torque, m, v, ang_v, transform, inertia_tensor)
+        in query.iter_mut()
+    {
+        let body = ForceGeneratorBody {
+            mass: m,
+            velocity: v,
+            angular_velocity: ang_v,
+            transform,
+            inertia_tensor,
+            step: physics_time.step(),
+        };
+
+        generator.update_force_and_torque(&body, &mut f, &mut torque);
+    }
+}
+
+/// A system that accumulates the forces and torques applied on a body through its ExternalForce.
+/// Sleeping bodies are skipped.
+fn external_force_accumulation(
+    mut query: Query<(&ExternalForce, &mut Force, &mut Torque, &PhysTransform), Without<Sleeping>>,
+) {
+    for (external, mut f, mut torque, transform) in query.iter_mut() {
+        external.update_force_and_torque(&mut f, &mut torque, transform);
+    }
+}
+
+/// A system that applies, then clears, the impulses held in each body's ExternalImpulse. Sleeping
+/// bodies are included, and are woken by the change in velocity. Impulses on Static and Kinematic
+/// bodies are discarded.
+#[allow(clippy::type_complexity)]
+fn apply_external_impulses(
+    mut query: Query<(&mut ExternalImpulse, &mut Velocity, &mut AngularVelocity, &Mass,
+                      &InertiaTensor, Option<&RigidBodyType>)>,
+) {
+    for (mut external, mut v, mut ang_v, m, inertia_tensor, body_type) in query.iter_mut() {
+        if external.is_zero() { continue; }
+
+        if RigidBodyType::of(body_type, m).is_dynamic() {
+            external.update_velocities(&mut v, &mut ang_v, m.inverse(),
+                                       inertia_tensor.inverse_global());
+        } else {
+            *external = ExternalImpulse::default();
+        }
+    }
+}
+
+/// A system that zeroes the Force and Torque accumulator components for all Entitys that are
+/// awake. Those of sleeping Entitys are left untouched, so that any Force or Torque added to them
+/// wakes them.
+fn reset_accumulators(mut query: Query<(&mut Force, &mut Torque), Without<Sleeping>>) {
     for (mut f, mut tq) in query.iter_mut() {
         f.reset();
         tq.reset();
     }
 }
+
+// --- Helper methods
+
+/// Returns the acceleration due to gravity of the given body, at the given point.
+///
+/// The value of 'g' acting on the body is that of its Gravity, or the PhysicsConfig, combined with
+/// that of every GravityZone containing the point. Every PointAttractor within range, other than
+/// the body itself, then adds its attraction, and the total is scaled by the GravityScale, if any.
+#[allow(clippy::type_complexity)]
+fn calc_gravity(
+    entity: Entity,
+    gravity: &Gravity,
+    scale: Option<&GravityScale>,
+    point: DVec3,
+    config: &PhysicsConfig,
+    zones: &Query<(&GravityZone, &Collider, &PhysTransform)>,
+    attractors: &Query<(Entity, &PointAttractor, &PhysTransform)>,
+) -> DVec3 {
+    let inside = zones.iter()
+        .filter(|(_, collider, zone_transform)| {
+            point_projection::calc_projection(collider.0.as_ref(), zone_transform, point).1
+        })
+        .map(|(zone, _, _)| zone);
+
+    let attraction = attractors.iter()
+        .filter(|(attractor_entity, _, _)| *attractor_entity != entity)
+        .map(|(_, attractor, attractor_transform)| attractor.acceleration(
+            attractor_transform.translation(), point, config.gravitational_constant,
+        ))
+        .fold(DVec3::ZERO, |total, a| total + a);
+
+    let g = GravityZone::combine(gravity.g(config.gravity), inside) + attraction;
+
+    scale.map_or(1.0, |s| s.0) * g
+}
@@ -1,7 +1,10 @@
 mod accumulation;
 mod add;
 
-pub use accumulation::get_system_set;
+pub use accumulation::{
+    get_generator_system_set,
+    get_system_set,
+};
 pub use add::{
     add_force,
     add_force_at_point,
@@ -1,16 +1,22 @@
 use bevy::prelude::*;
 
 use crate::{
-    constants,
     physics::components::{
         AngularVelocity,
         Force,
         InertiaTensor,
+        KinematicTarget,
         Mass,
         PhysTransform,
+        RigidBodyType,
+        Sleeping,
         Torque,
         Velocity,
     },
+    physics::resources::{
+        PhysicsConfig,
+        PhysicsTime,
+    },
 };
 
 /// System labels covering sub-systems in the integration process.
@@ -30,9 +36,15 @@ pub fn get_system_set() -> SystemSet {
 
 /// An integration system that updates Velocity/AngularVelocity and PhysTransform components based
 /// on the attributes of the Entitys (Mass/InertiaTensor), the currently applied Force and Torque,
-/// and the timestep.
+/// and the fixed physics timestep. Sleeping Entitys are skipped.
+///
+/// Static bodies never move. Kinematic bodies ignore forces and torques, moving at their current
+/// Velocity and AngularVelocity, unless they have a KinematicTarget, in which case their
+/// velocities are set to those that reach the target over the step.
+#[allow(clippy::type_complexity)]
 fn integrate(
-    time: Res<Time>,
+    config: Res<PhysicsConfig>,
+    physics_time: Res<PhysicsTime>,
     mut query: Query<(
         &mut AngularVelocity,
         &Force,
@@ -40,30 +52,53 @@ fn integrate(
         &Mass,
         &mut PhysTransform,
         &Torque,
-        &mut Velocity
-    )>,
+        &mut Velocity,
+        Option<&RigidBodyType>,
+        Option<&KinematicTarget>,
+    ), Without<Sleeping>>,
 ) {
-    let dt_secs = time.delta_seconds_f64();
+    let dt_secs = physics_time.step();
+
+    for (mut ang_v, f, inertia_tensor, m, mut transform, torque, mut v, body_type, target)
+        in query.iter_mut()
+    {
+        match RigidBodyType::of(body_type, m) {
+            // Static objects cannot move.
+            RigidBodyType::Static => continue,
+            RigidBodyType::Kinematic => {
+                if let Some(KinematicTarget(target)) = target {
+                    // Move exactly to the target, with the velocities that take it there.
+                    *v = Velocity::new((target.translation - transform.translation) / dt_secs);
 
-    for (mut ang_v, f, inertia_tensor, m, mut transform, torque, mut v) in query.iter_mut() {
-        // Infinite mass objects cannot move.
-        if m.is_infinite() { continue };
+                    let mut rotation = target.rotation * transform.rotation.conjugate();
+                    // take the shortest path.
+                    if rotation.w < 0.0 { rotation = -rotation; }
+                    let (axis, angle) = rotation.to_axis_angle();
+                    *ang_v = AngularVelocity::new(axis * angle / dt_secs);
 
-        // Calculate linear acceleration from currently applied forces.
-        let accel = f.vector() * m.inverse();
+                    transform.translation = target.translation;
+                    transform.rotation = target.rotation;
+                    continue;
+                }
+            },
+            RigidBodyType::Dynamic => {
+                // Calculate linear acceleration from currently applied forces.
+                let accel = f.vector() * m.inverse();
 
-        // Calculate angular acceleration from torques.
-        let ang_accel = inertia_tensor.inverse_global() * torque.vector();
+                // Calculate angular acceleration from torques.
+                let ang_accel = inertia_tensor.inverse_global() * torque.vector();
 
-        // Update linear velocity.
-        v.add(accel * dt_secs);
+                // Update linear velocity.
+                v.add(accel * dt_secs);
 
-        // Update angular velocity.
-        ang_v.add(ang_accel * dt_secs);
+                // Update angular velocity.
+                ang_v.add(ang_accel * dt_secs);
 
-        // Apply damping.
-        v.scale(constants::DAMPING_FACTOR.powf(dt_secs));
-        ang_v.scale(constants::ANGULAR_DAMPING_FACTOR.powf(dt_secs));
+                // Apply damping.
+                v.scale(config.damping_factor.powf(dt_secs));
+                ang_v.scale(config.angular_damping_factor.powf(dt_secs));
+            },
+        }
 
         // Update internal physics module rotation and translation.
         transform.rotation = (transform.rotation + ang_v.quaternion() * transform.rotation * dt_secs * 0.5)
@@ -71,8 +106,87 @@ fn integrate(
         transform.translation += v.vector() * dt_secs;
 
         // If velocity is very low, make it 0.
-        if v.vector().length_squared() < constants::LOW_VELOCITY_THRESHOLD {
+        if v.vector().length_squared() < config.low_velocity_threshold
+            && RigidBodyType::of(body_type, m).is_dynamic()
+        {
             v.zero();
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy::math::{
+        DQuat,
+        DVec3,
+    };
+
+    const EPSILON: f64 = 0.000001;
+
+    /// Runs one integration step of the given duration on the given World.
+    fn step(world: &mut World, dt: f64) {
+        world.insert_resource(PhysicsConfig::default());
+        world.insert_resource(PhysicsTime::new(dt, 1));
+
+        let mut stage = SystemStage::parallel().with_system(integrate.system());
+        stage.run(world);
+    }
+
+    /// Spawns a body at the origin, moving along x and pushed down by a Force.
+    fn spawn_body(world: &mut World, body_type: RigidBodyType) -> Entity {
+        let mut force = Force::default();
+        force.add(DVec3::new(0.0, -100.0, 0.0));
+
+        world.spawn()
+            .insert(AngularVelocity::new(DVec3::ZERO))
+            .insert(force)
+            .insert(InertiaTensor::sphere(1.0, 1.0))
+            .insert(Mass::new(1.0))
+            .insert(PhysTransform::from_xyz(0.0, 0.0, 0.0))
+            .insert(Torque::default())
+            .insert(Velocity::new(DVec3::new(1.0, 0.0, 0.0)))
+            .insert(body_type)
+            .id()
+    }
+
+    #[test]
+    fn test_kinematic_velocity() {
+        let mut world = World::new();
+        let kinematic = spawn_body(&mut world, RigidBodyType::Kinematic);
+        let fixed = spawn_body(&mut world, RigidBodyType::Static);
+
+        step(&mut world, 0.5);
+
+        // the kinematic body moves at its velocity, ignoring the force applied.
+        let transform = world.get::<PhysTransform>(kinematic).unwrap();
+        assert!((transform.translation() - DVec3::new(0.5, 0.0, 0.0)).length() < EPSILON);
+        assert_eq!(DVec3::new(1.0, 0.0, 0.0), world.get::<Velocity>(kinematic).unwrap().vector());
+
+        // the static body never moves.
+        assert_eq!(DVec3::ZERO, world.get::<PhysTransform>(fixed).unwrap().translation());
+    }
+
+    #[test]
+    fn test_kinematic_target() {
+        let mut world = World::new();
+        let kinematic = spawn_body(&mut world, RigidBodyType::Kinematic);
+
+        let rotation = DQuat::from_rotation_y(0.2);
+        world.entity_mut(kinematic).insert(KinematicTarget(
+            PhysTransform::from_rotation_translation(rotation, DVec3::new(0.0, 1.0, 0.0))
+        ));
+
+        step(&mut world, 0.5);
+
+        // the body reaches the target, with the velocities that took it there.
+        let transform = world.get::<PhysTransform>(kinematic).unwrap();
+        assert!((transform.translation() - DVec3::new(0.0, 1.0, 0.0)).length() < EPSILON);
+        assert!(transform.rotation().abs_diff_eq(rotation, EPSILON));
+
+        let v = world.get::<Velocity>(kinematic).unwrap().vector();
+        let ang_v = world.get::<AngularVelocity>(kinematic).unwrap().vector();
+        assert!((v - DVec3::new(0.0, 2.0, 0.0)).length() < EPSILON);
+        assert!((ang_v - DVec3::new(0.0, 0.4, 0.0)).length() < EPSILON);
+    }
+}
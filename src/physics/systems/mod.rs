@@ -1,6 +1,10 @@
 pub mod cache_update;
+pub mod ccd;
 pub mod collision_detection;
+pub mod collision_events;
 pub mod collision_response;
 pub mod force_and_torque;
 pub mod integrator;
+pub mod sleep;
+pub mod timestep;
 pub mod transform_sync;
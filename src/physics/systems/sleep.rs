@@ -0,0 +1,256 @@
+use bevy::{
+    prelude::*,
+    math::DVec3,
+};
+use std::collections::{
+    HashMap,
+    HashSet,
+};
+
+use crate::{
+    physics::components::{
+        AngularVelocity,
+        ExternalForce,
+        Force,
+        Joint,
+        Mass,
+        RigidBodyType,
+        SleepTimer,
+        Sleeping,
+        Thrust,
+        Torque,
+        Velocity,
+    },
+    physics::resources::{
+        ContactManifolds,
+        PhysicsConfig,
+        PhysicsTime,
+    },
+};
+
+/// System labels covering sub-systems in the sleep process.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+enum SleepSystems {
+    Update,
+}
+
+/// A SystemSet that puts islands of resting bodies to sleep and wakes them when disturbed.
+pub fn get_system_set() -> SystemSet {
+    SystemSet::new()
+        .with_system(update_sleep.system()
+                     .label(SleepSystems::Update)
+        )
+}
+
+/// Updates the SleepTimer of each Dynamic body, then groups the bodies into islands of
+/// bodies in contact with, or connected by a Joint to, one another.
+///
+/// An island is put to sleep once all of its bodies have been at rest for the time given in the
+/// PhysicsConfig, and woken as soon as any of its bodies moves. A sleeping body moves if it is
+/// given any velocity (e.g. by an impact), force, thrust or ExternalForce, if it loses contact
+/// with another body, or if it touches (or is joined to) a moving Kinematic body. Changes take
+/// effect from the next physics step.
+///
+/// Static bodies, and Kinematic bodies at rest, take no part in islands.
+#[allow(clippy::type_complexity)]
+fn update_sleep(
+    mut commands: Commands,
+    config: Res<PhysicsConfig>,
+    physics_time: Res<PhysicsTime>,
+    manifolds: Res<ContactManifolds>,
+    joints: Query<&Joint>,
+    mut bodies: Query<(
+        Entity,
+        &Mass,
+        Option<&RigidBodyType>,
+        &mut Velocity,
+        &mut AngularVelocity,
+        &mut Force,
+        &mut Torque,
+        Option<&Thrust>,
+        Option<&ExternalForce>,
+        Option<&mut SleepTimer>,
+        Option<&Sleeping>,
+    )>,
+) {
+    let mut entities = vec![];
+    // Whether each body is ready to sleep, and whether it is currently asleep.
+    let mut ready = vec![];
+    let mut asleep = vec![];
+    // Kinematic bodies that are moving, and so disturb any body they touch.
+    let mut moving = HashSet::new();
+
+    for (entity, mass, body_type, v, ang_v, f, torque, thrust, external, timer, sleeping)
+        in bodies.iter_mut()
+    {
+        // Static and Kinematic bodies do not sleep, nor join islands.
+        match RigidBodyType::of(body_type, mass) {
+            RigidBodyType::Dynamic => (),
+            RigidBodyType::Kinematic => {
+                if v.vector() != DVec3::ZERO || ang_v.vector() != DVec3::ZERO {
+                    moving.insert(entity);
+                }
+                continue;
+            },
+            RigidBodyType::Static => continue,
+        }
+
+        let mut timer = match timer {
+            Some(timer) => timer,
+            None => {
+                commands.entity(entity).insert(SleepTimer::default());
+                continue;
+            }
+        };
+
+        let thrust = thrust.map_or(DVec3::ZERO, |t| t.vector());
+        let pushed = matches!(external, Some(e) if !e.is_zero());
+
+        if sleeping.is_some() {
+            let disturbed = v.vector() != DVec3::ZERO
+                || ang_v.vector() != DVec3::ZERO
+                || f.vector() != DVec3::ZERO
+                || torque.vector() != DVec3::ZERO
+                || thrust != DVec3::ZERO
+                || pushed;
+
+            ready.push(config.sleep_enabled && !disturbed);
+        } else {
+            let at_rest = v.vector().length() < config.sleep_linear_threshold
+                && ang_v.vector().length() < config.sleep_angular_threshold
+                && thrust == DVec3::ZERO
+                && !pushed;
+
+            if at_rest {
+                timer.tick(physics_time.step());
+            } else {
+                timer.reset();
+            }
+
+            ready.push(config.sleep_enabled && timer.seconds() >= config.time_to_sleep);
+        }
+
+        entities.push(entity);
+        asleep.push(sleeping.is_some());
+    }
+
+    // Group the bodies into islands.
+    let indices: HashMap<Entity, usize> = entities.iter()
+        .enumerate()
+        .map(|(i, e)| (*e, i))
+        .collect();
+
+    let pairs: Vec<(Entity, Entity)> = manifolds.iter()
+        .map(|m| m.pair())
+        .chain(joints.iter().map(|j| (j.entities[0], j.entities[1])))
+        .collect();
+
+    let links = pairs.iter()
+        .filter_map(|(a, b)| Some((*indices.get(a)?, *indices.get(b)?)));
+
+    let islands = calc_islands(entities.len(), links);
+
+    // Bodies touching a moving Kinematic body must wake.
+    for (a, b) in pairs.iter() {
+        for (kinematic, other) in [(a, b), (b, a)].iter() {
+            if let (true, Some(i)) = (moving.contains(*kinematic), indices.get(*other)) {
+                ready[*i] = false;
+            }
+        }
+    }
+
+    // Bodies that have lost a contact (e.g. their support was removed) must wake.
+    for manifold in manifolds.expired() {
+        let (a, b) = manifold.pair();
+        for entity in [a, b].iter() {
+            if let Some(i) = indices.get(entity) {
+                ready[*i] = false;
+            }
+        }
+    }
+
+    // An island can only sleep if every body in it is ready to.
+    let mut island_ready: HashMap<usize, bool> = HashMap::new();
+    for (i, island) in islands.iter().enumerate() {
+        let r = island_ready.entry(*island).or_insert(true);
+        *r = *r && ready[i];
+    }
+
+    for (i, entity) in entities.iter().enumerate() {
+        let should_sleep = island_ready[&islands[i]];
+
+        if should_sleep && !asleep[i] {
+            let (_, _, _, mut v, mut ang_v, mut f, mut torque, _, _, _, _) = bodies.get_mut(*entity)
+                .expect("Entity does not exist!");
+
+            v.zero();
+            ang_v.zero();
+            f.reset();
+            torque.reset();
+
+            commands.entity(*entity).insert(Sleeping);
+        } else if !should_sleep && asleep[i] {
+            let (_, _, _, _, _, _, _, _, _, timer, _) = bodies.get_mut(*entity)
+                .expect("Entity does not exist!");
+
+            if let Some(mut timer) = timer {
+                timer.reset();
+            }
+
+            commands.entity(*entity).remove::<Sleeping>();
+        }
+    }
+}
+
+// --- Helper methods
+
+/// Groups the given number of bodies into islands, given the links between pairs of bodies by
+/// index. Returns the island of each body, identified by the index of one of its members.
+fn calc_islands(count: usize, links: impl Iterator<Item = (usize, usize)>) -> Vec<usize> {
+    // Disjoint-set forest, each body initially in its own set.
+    let mut parents: Vec<usize> = (0..count).collect();
+
+    fn find(parents: &mut [usize], i: usize) -> usize {
+        let mut root = i;
+        while parents[root] != root {
+            root = parents[root];
+        }
+
+        // path compression.
+        let mut i = i;
+        while parents[i] != root {
+            let next = parents[i];
+            parents[i] = root;
+            i = next;
+        }
+
+        root
+    }
+
+    for (a, b) in links {
+        let root_a = find(&mut parents, a);
+        let root_b = find(&mut parents, b);
+        parents[root_a] = root_b;
+    }
+
+    (0..count).map(|i| find(&mut parents, i)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_calc_islands() {
+        // 0-1-2 linked in a chain, 3 alone, 4-5 linked.
+        let links = vec![(0, 1), (2, 1), (4, 5)];
+        let islands = calc_islands(6, links.into_iter());
+
+        assert_eq!(islands[0], islands[1]);
+        assert_eq!(islands[1], islands[2]);
+        assert_eq!(islands[4], islands[5]);
+        assert_ne!(islands[0], islands[3]);
+        assert_ne!(islands[0], islands[4]);
+        assert_ne!(islands[3], islands[4]);
+    }
+}
@@ -0,0 +1,15 @@
+use bevy::{
+    prelude::*,
+    ecs::schedule::ShouldRun,
+};
+
+use crate::physics::resources::PhysicsTime;
+
+/// A run criteria that runs the physics schedule zero or more times per frame, consuming the
+/// frame time in fixed size steps as configured by the PhysicsTime resource.
+pub fn fixed_timestep(
+    time: Res<Time>,
+    mut physics_time: ResMut<PhysicsTime>,
+) -> ShouldRun {
+    physics_time.update(time.delta_seconds_f64())
+}
@@ -3,14 +3,18 @@ use bevy::prelude::*;
 use crate::{
     physics::components::{
         PhysTransform,
+        PreviousPhysTransform,
     },
+    physics::resources::PhysicsTime,
 };
 
 /// System labels covering sub-systems in the synchronisation process.
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
 enum SyncSystems {
+    RecordPrevious,
     SyncTransforms,
 }
+
 /// A SystemSet that performs syncronisation between 64-bit Bpm components and their 32-bit Bevy
 /// counterparts.
 pub fn get_system_set() -> SystemSet {
@@ -20,13 +24,46 @@ pub fn get_system_set() -> SystemSet {
         )
 }
 
-/// Updates the 32-bit Bevy Transform from the 64-bit Bpm PhysTransform.
+/// A SystemSet, run at the start of each physics step, that records the state of the
+/// PhysTransforms before they are updated so that they can later be interpolated.
+pub fn get_record_system_set() -> SystemSet {
+    SystemSet::new()
+        .with_system(record_previous_transforms.system()
+                     .label(SyncSystems::RecordPrevious)
+        )
+}
+
+/// Records the current PhysTransform of each Entity, adding a PreviousPhysTransform to any Entity
+/// that does not yet have one.
+fn record_previous_transforms(
+    mut commands: Commands,
+    mut recorded: Query<(&PhysTransform, &mut PreviousPhysTransform)>,
+    unrecorded: Query<(Entity, &PhysTransform), Without<PreviousPhysTransform>>,
+) {
+    for (transform, mut previous) in recorded.iter_mut() {
+        previous.record(transform);
+    }
+    for (entity, transform) in unrecorded.iter() {
+        commands.entity(entity).insert(PreviousPhysTransform::new(transform));
+    }
+}
+
+/// Updates the 32-bit Bevy Transform from the 64-bit Bpm PhysTransform, interpolating between the
+/// previous and current physics states according to the time remaining in the PhysicsTime
+/// accumulator.
 fn sync_transforms(
-    //mut transforms: Query<(&PhysTransform, &mut Transform), Changed<PhysTransform>>,
-    mut transforms: Query<(&PhysTransform, &mut Transform)>,
+    physics_time: Res<PhysicsTime>,
+    mut transforms: Query<(&PhysTransform, Option<&PreviousPhysTransform>, &mut Transform)>,
 ) {
-    for (phys_transform, mut transform) in transforms.iter_mut() {
-        transform.translation = phys_transform.translation.as_f32();
-        transform.rotation = phys_transform.rotation.as_f32();
+    let alpha = physics_time.alpha();
+
+    for (phys_transform, previous, mut transform) in transforms.iter_mut() {
+        let (rotation, translation) = match previous {
+            Some(previous) => previous.interpolate(phys_transform, alpha),
+            None => (phys_transform.rotation(), phys_transform.translation()),
+        };
+
+        transform.translation = translation.as_f32();
+        transform.rotation = rotation.as_f32();
     }
 }